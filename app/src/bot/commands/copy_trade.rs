@@ -0,0 +1,102 @@
+use crate::utils::copy_trade::{
+  create_wallet_follow, delete_wallet_follow, find_user_wallet_follows, format_wallet_follow,
+  update_wallet_follow, WalletFollowParams,
+};
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use teloxide::prelude::*;
+
+const COPY_TRADE_USAGE: &str = "Follow a wallet with /watchsolwallet address fixed|proportional size max_sol_per_trade [max_total_sol]\nfixed buys size SOL, proportional buys size% of what they spent\nManage one with /watchsolwallet on|off|copy_sells|skip_sells|unfollow follow_id";
+
+pub async fn handle_watch_wallet(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  args: String,
+) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))?;
+
+  let parts: Vec<&str> = args.split_whitespace().collect();
+
+  let response_message = match parts.as_slice() {
+    [] => {
+      let follows = find_user_wallet_follows(db, authorized_user.id).await?;
+      if follows.is_empty() {
+        format!("You don't follow any wallets.\n\n{}", COPY_TRADE_USAGE)
+      } else {
+        let follow_lines: Vec<String> = follows.iter().map(format_wallet_follow).collect();
+        format!("Wallets you follow:\n\n{}", follow_lines.join("\n\n"))
+      }
+    }
+    ["unfollow", follow_id] => {
+      let follow_id = follow_id
+        .parse::<i32>()
+        .map_err(|_| anyhow!("Invalid follow id {}", follow_id))?;
+
+      match delete_wallet_follow(db, authorized_user.id, follow_id).await {
+        Ok(()) => format!("Unfollowed {} ✅", follow_id),
+        Err(e) => format!("Could not unfollow {}: {}", follow_id, e),
+      }
+    }
+    [action @ ("on" | "off" | "copy_sells" | "skip_sells"), follow_id] => {
+      let follow_id = follow_id
+        .parse::<i32>()
+        .map_err(|_| anyhow!("Invalid follow id {}", follow_id))?;
+
+      match update_wallet_follow(db, authorized_user.id, follow_id, action).await {
+        Ok(follow) => format!("Follow updated ✅\n{}", format_wallet_follow(&follow)),
+        Err(e) => format!("Could not update follow {}: {}", follow_id, e),
+      }
+    }
+    [address, mode, size, max_sol, rest @ ..] if rest.len() <= 1 => {
+      let params = WalletFollowParams {
+        followed_address: address.to_string(),
+        size_mode: mode.to_string(),
+        size_value: size
+          .parse::<f64>()
+          .map_err(|_| anyhow!("Invalid size {}", size))?,
+        max_sol_per_trade: max_sol
+          .parse::<f64>()
+          .map_err(|_| anyhow!("Invalid max SOL per trade {}", max_sol))?,
+        max_total_sol: rest
+          .first()
+          .map(|max_total| {
+            max_total
+              .parse::<f64>()
+              .map_err(|_| anyhow!("Invalid max total SOL {}", max_total))
+          })
+          .transpose()?,
+      };
+
+      // copied swaps are made from the user's wallet
+      let wallet = wallets::Entity::find()
+        .filter(wallets::Column::UserId.eq(authorized_user.id))
+        .filter(wallets::Column::WatchOnly.eq(false))
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow!("No Solana wallet found for user"))?;
+
+      match create_wallet_follow(db, authorized_user.id, wallet.id, &params).await {
+        Ok(follow) => format!("Following ✅\n{}", format_wallet_follow(&follow)),
+        Err(e) => format!("Failed to follow {}: {}", address, e),
+      }
+    }
+    _ => COPY_TRADE_USAGE.to_string(),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
@@ -0,0 +1,91 @@
+use crate::utils::dca::{
+  create_dca_plan, find_user_dca_plans, format_dca_plan, parse_dca_end, update_dca_plan_status,
+  DcaPlanParams,
+};
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use teloxide::prelude::*;
+
+use super::trade::resolve_order_target;
+
+const DCA_USAGE: &str = "Start a plan with /dca ca sol_per_buy interval_minutes total_budget end (7d or 2024-12-31)\nManage one with /dca pause|resume|cancel plan_id";
+
+pub async fn handle_dca(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  args: String,
+) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))?;
+
+  let parts: Vec<&str> = args.split_whitespace().collect();
+
+  let response_message = match parts.as_slice() {
+    [] => {
+      let plans = find_user_dca_plans(db, authorized_user.id, true).await?;
+      if plans.is_empty() {
+        format!("You have no open DCA plans.\n\n{}", DCA_USAGE)
+      } else {
+        let plan_lines: Vec<String> = plans.iter().map(format_dca_plan).collect();
+        format!("Your DCA plans:\n\n{}", plan_lines.join("\n\n"))
+      }
+    }
+    [action @ ("pause" | "resume" | "cancel"), plan_id] => {
+      let plan_id = plan_id
+        .parse::<i32>()
+        .map_err(|_| anyhow!("Invalid plan id {}", plan_id))?;
+
+      match update_dca_plan_status(db, authorized_user.id, plan_id, action).await {
+        Ok(plan) => format!("DCA plan updated ✅\n{}", format_dca_plan(&plan)),
+        Err(e) => format!("Could not {} plan {}: {}", action, plan_id, e),
+      }
+    }
+    [ca, amount, interval, budget, end] => {
+      let params = DcaPlanParams {
+        amount_per_buy: amount
+          .parse::<f64>()
+          .map_err(|_| anyhow!("Invalid SOL amount per buy"))?,
+        interval_minutes: interval
+          .parse::<i32>()
+          .map_err(|_| anyhow!("Invalid interval, provide a number of minutes"))?,
+        total_budget: budget
+          .parse::<f64>()
+          .map_err(|_| anyhow!("Invalid total budget"))?,
+        ends_at: parse_dca_end(end)?,
+      };
+
+      let target = resolve_order_target(&msg, db, ca).await?;
+
+      match create_dca_plan(
+        db,
+        target.user_id,
+        target.wallet_id,
+        target.token_id,
+        ca,
+        &params,
+      )
+      .await
+      {
+        Ok(plan) => format!("DCA plan started ✅\n{}", format_dca_plan(&plan)),
+        Err(e) => format!("Failed to start DCA plan: {}", e),
+      }
+    }
+    _ => DCA_USAGE.to_string(),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
@@ -0,0 +1,122 @@
+use crate::utils::swap::history::{find_user_transactions_page, format_transaction};
+use anyhow::{anyhow, Result};
+use sea_orm::DatabaseConnection;
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+};
+
+use super::find_tg_user;
+
+// callback data of the page buttons, followed by the page they open
+pub const HISTORY_PAGE_CALLBACK: &str = "history:";
+
+// text and page buttons of a history page, pages count from 0
+async fn history_page(
+  db: &DatabaseConnection,
+  user_id: i32,
+  page: u64,
+) -> Result<(String, InlineKeyboardMarkup)> {
+  let (transactions, pages) = find_user_transactions_page(db, user_id, page).await?;
+
+  if pages == 0 {
+    return Ok((
+      "You have no transactions yet.".to_string(),
+      InlineKeyboardMarkup::default(),
+    ));
+  }
+
+  let text = if transactions.is_empty() {
+    format!("There is no page {}, you have {} pages.", page + 1, pages)
+  } else {
+    let transaction_lines: Vec<String> = transactions.iter().map(format_transaction).collect();
+    format!(
+      "Your transactions, page {} of {}:\n\n{}",
+      page + 1,
+      pages,
+      transaction_lines.join("\n\n")
+    )
+  };
+
+  let mut buttons = vec![];
+  if page > 0 {
+    buttons.push(InlineKeyboardButton::callback(
+      "◀ Newer",
+      format!("{}{}", HISTORY_PAGE_CALLBACK, page.min(pages) - 1),
+    ));
+  }
+  if page + 1 < pages {
+    buttons.push(InlineKeyboardButton::callback(
+      "Older ▶",
+      format!("{}{}", HISTORY_PAGE_CALLBACK, page + 1),
+    ));
+  }
+
+  Ok((text, InlineKeyboardMarkup::new([buttons])))
+}
+
+pub async fn handle_history(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  args: String,
+) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = find_tg_user(db, tg_user).await?;
+
+  let page = match args.trim() {
+    "" => 1,
+    page => page
+      .parse::<u64>()
+      .ok()
+      .filter(|page| *page > 0)
+      .ok_or_else(|| anyhow!("Invalid page {}, use /history or /history 2", page))?,
+  };
+
+  let (text, keyboard) = history_page(db, authorized_user.id, page - 1).await?;
+
+  bot
+    .send_message(msg.chat.id, text)
+    .reply_markup(keyboard)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
+
+/// Turn the page of a history message a page button was pressed on
+pub async fn handle_history_callback(
+  bot: Bot,
+  query: CallbackQuery,
+  db: &DatabaseConnection,
+  page: &str,
+) -> Result<()> {
+  let page = page
+    .parse::<u64>()
+    .map_err(|_| anyhow!("Invalid history page {}", page))?;
+
+  let authorized_user = find_tg_user(db, &query.from).await?;
+
+  let message = query
+    .message
+    .as_ref()
+    .ok_or_else(|| anyhow!("The history message is no longer available"))?;
+
+  let (text, keyboard) = history_page(db, authorized_user.id, page).await?;
+
+  bot
+    .edit_message_text(message.chat().id, message.id(), text)
+    .reply_markup(keyboard)
+    .await
+    .map_err(|e| anyhow!("Failed to edit message: {}", e))?;
+
+  bot
+    .answer_callback_query(query.id)
+    .await
+    .map_err(|e| anyhow!("Failed to answer callback: {}", e))?;
+
+  Ok(())
+}
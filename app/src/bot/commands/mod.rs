@@ -1 +1,24 @@
+pub mod copy_trade;
+pub mod dca;
+pub mod history;
+pub mod orders;
+pub mod portfolio;
+pub mod settings;
 pub mod trade;
+pub mod watch;
+
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+/// The user a Telegram account is linked to
+pub(super) async fn find_tg_user(
+  db: &DatabaseConnection,
+  tg_user: &teloxide::types::User,
+) -> Result<users::Model> {
+  users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))
+}
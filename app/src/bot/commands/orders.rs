@@ -0,0 +1,190 @@
+use crate::utils::orders::{
+  edit::{find_user_editable_orders, find_user_order, update_order_target, OrderTarget},
+  format_trade_order,
+  lifecycle::cancel_order,
+};
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::DatabaseConnection;
+use teloxide::{
+  prelude::*,
+  types::{InlineKeyboardButton, InlineKeyboardMarkup},
+};
+
+use super::find_tg_user;
+
+const ORDERS_USAGE: &str = "Cancel an order with /orders cancel order_id\nMove its target with /orders target order_id price, or a percentage such as 20%";
+
+// callback data of the order buttons, followed by the order id
+pub const CANCEL_ORDER_CALLBACK: &str = "order_cancel:";
+pub const EDIT_ORDER_CALLBACK: &str = "order_edit:";
+
+// orders listed per message, keeping it and its buttons within Telegram's limits
+const ORDERS_PER_MESSAGE: usize = 20;
+
+fn parse_order_id(order_id: &str) -> Result<i32> {
+  order_id
+    .parse::<i32>()
+    .map_err(|_| anyhow!("Invalid order id {}", order_id))
+}
+
+fn order_buttons(order: &trade_orders::Model) -> Vec<InlineKeyboardButton> {
+  vec![
+    InlineKeyboardButton::callback(
+      format!("Cancel #{}", order.id),
+      format!("{}{}", CANCEL_ORDER_CALLBACK, order.id),
+    ),
+    InlineKeyboardButton::callback(
+      format!("Edit target #{}", order.id),
+      format!("{}{}", EDIT_ORDER_CALLBACK, order.id),
+    ),
+  ]
+}
+
+fn format_orders(orders: &[trade_orders::Model]) -> String {
+  let order_lines: Vec<String> = orders.iter().map(format_trade_order).collect();
+  order_lines.join("\n")
+}
+
+fn orders_keyboard(orders: &[trade_orders::Model]) -> InlineKeyboardMarkup {
+  InlineKeyboardMarkup::new(orders.iter().map(order_buttons))
+}
+
+async fn cancel_user_order(db: &DatabaseConnection, user_id: i32, order_id: i32) -> Result<String> {
+  let order = find_user_order(db, user_id, order_id).await?;
+
+  if cancel_order(db, &order, None).await? {
+    Ok(format!("Cancelled ✅\n{}", format_trade_order(&order)))
+  } else {
+    Ok(format!(
+      "Order {} changed before it could be cancelled, check /orders",
+      order_id
+    ))
+  }
+}
+
+pub async fn handle_orders(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  args: String,
+) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = find_tg_user(db, tg_user).await?;
+
+  let parts: Vec<&str> = args.split_whitespace().collect();
+
+  let (response_message, keyboard) = match parts.as_slice() {
+    [] => {
+      let orders = find_user_editable_orders(db, authorized_user.id).await?;
+      let mut pages: Vec<&[trade_orders::Model]> = orders.chunks(ORDERS_PER_MESSAGE).collect();
+
+      match pages.pop() {
+        None => ("You have no active orders.".to_string(), None),
+        Some(last_page) => {
+          // long lists go out a message at a time, the last one carries the usage
+          for page in pages {
+            bot
+              .send_message(
+                msg.chat.id,
+                format!("Your active orders:\n\n{}", format_orders(page)),
+              )
+              .reply_markup(orders_keyboard(page))
+              .await
+              .map_err(|e| anyhow!("Failed to send message: {}", e))?;
+          }
+
+          (
+            format!(
+              "Your active orders:\n\n{}\n\n{}",
+              format_orders(last_page),
+              ORDERS_USAGE
+            ),
+            Some(orders_keyboard(last_page)),
+          )
+        }
+      }
+    }
+    ["cancel", order_id] => {
+      let message = match cancel_user_order(db, authorized_user.id, parse_order_id(order_id)?).await
+      {
+        Ok(message) => message,
+        Err(e) => format!("Could not cancel {}: {}", order_id, e),
+      };
+      (message, None)
+    }
+    ["target", order_id, target] => {
+      let order = find_user_order(db, authorized_user.id, parse_order_id(order_id)?).await?;
+
+      let message = match update_order_target(db, &order, OrderTarget::parse(target)?).await {
+        Ok(order) => format!("Target updated ✅\n{}", format_trade_order(&order)),
+        Err(e) => format!("Could not update {}: {}", order_id, e),
+      };
+      (message, None)
+    }
+    _ => (ORDERS_USAGE.to_string(), None),
+  };
+
+  let request = bot.send_message(msg.chat.id, response_message);
+  let request = match keyboard {
+    Some(keyboard) => request.reply_markup(keyboard),
+    None => request,
+  };
+
+  request
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
+
+/// Act on a cancel or edit target button pressed under an order list
+pub async fn handle_order_callback(
+  bot: Bot,
+  query: CallbackQuery,
+  db: &DatabaseConnection,
+  data: &str,
+) -> Result<()> {
+  let authorized_user = find_tg_user(db, &query.from).await?;
+
+  let chat_id = query
+    .message
+    .as_ref()
+    .map(|message| message.chat().id)
+    .ok_or_else(|| anyhow!("The order message is no longer available"))?;
+
+  let response_message = if let Some(order_id) = data.strip_prefix(CANCEL_ORDER_CALLBACK) {
+    let order_id = parse_order_id(order_id)?;
+
+    match cancel_user_order(db, authorized_user.id, order_id).await {
+      Ok(message) => message,
+      Err(e) => format!("Could not cancel {}: {}", order_id, e),
+    }
+  } else if let Some(order_id) = data.strip_prefix(EDIT_ORDER_CALLBACK) {
+    let order = find_user_order(db, authorized_user.id, parse_order_id(order_id)?).await?;
+
+    // a button can't take the new target, the user sends it as a command
+    format!(
+      "{}\n\nSend /orders target {} followed by the new price, or a percentage such as 20%",
+      format_trade_order(&order),
+      order.id
+    )
+  } else {
+    return Err(anyhow!("Unknown order action {}", data));
+  };
+
+  bot
+    .answer_callback_query(query.id)
+    .await
+    .map_err(|e| anyhow!("Failed to answer callback: {}", e))?;
+
+  bot
+    .send_message(chat_id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))?;
+
+  Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::utils::wallets::portfolio::{
+  format_portfolio_total, format_wallet_valuation, value_user_portfolio,
+};
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use teloxide::prelude::*;
+
+pub async fn handle_portfolio(bot: Bot, msg: Message, db: &DatabaseConnection) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))?;
+
+  let portfolio = value_user_portfolio(db, authorized_user.id).await?;
+
+  if portfolio.wallets.is_empty() {
+    return bot
+      .send_message(
+        msg.chat.id,
+        "You have no wallets yet, add one with /addsolwallet or /createsolwallet",
+      )
+      .await
+      .map_err(|e| anyhow!("Failed to send message: {}", e));
+  }
+
+  // a wallet per message keeps each under Telegram's length limit
+  for wallet in &portfolio.wallets {
+    bot
+      .send_message(
+        msg.chat.id,
+        format_wallet_valuation(wallet, portfolio.sol_price),
+      )
+      .await
+      .map_err(|e| anyhow!("Failed to send message: {}", e))?;
+  }
+
+  bot
+    .send_message(msg.chat.id, format_portfolio_total(&portfolio))
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
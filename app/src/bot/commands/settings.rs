@@ -0,0 +1,47 @@
+use crate::utils::settings::{
+  find_or_create_user_settings, format_user_settings, update_user_setting,
+};
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use teloxide::prelude::*;
+
+pub async fn handle_settings(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  args: String,
+) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))?;
+
+  let settings = find_or_create_user_settings(db, authorized_user.id).await?;
+
+  let mut parts = args.split_whitespace();
+
+  let response_message = match (parts.next(), parts.next()) {
+    (None, _) => format!(
+      "Your settings:\n{}\n\nUpdate one with /settings key value",
+      format_user_settings(&settings)
+    ),
+    (Some(key), Some(value)) => match update_user_setting(db, settings, key, value).await {
+      Ok(updated) => format!("Settings updated:\n{}", format_user_settings(&updated)),
+      Err(e) => format!("Could not update {}: {}", key, e),
+    },
+    (Some(key), None) => format!("Missing a value for {}", key),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
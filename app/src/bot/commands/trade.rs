@@ -1,12 +1,27 @@
 #![allow(deprecated)]
 
-use crate::{
-  integrations::raydium::RaydiumPriceFetcher,
-  utils::{swap::solana::execute_user_swap_txs, wallets::solana::get_wallet_sol_balance},
+use crate::utils::{
+  orders::{
+    ladder::{create_ladder_order, parse_ladder_rungs, LadderPlan},
+    limit_buy::create_limit_buy_order,
+  },
+  price::solana::fetch_token_price,
+  settings::find_or_create_user_settings,
+  swap::{
+    lock::{acquire_execution_lock, release_execution_lock, wallet_lock_key},
+    paper::{is_paper_wallet, wallet_token_balance},
+    provider::quote_best_swap,
+    slippage::{SlippagePolicy, SwapKind},
+    solana::{
+      create_take_profit_order, create_trailing_stop_order, execute_swap_with_escalation,
+      record_transaction, SwapTxResult,
+    },
+  },
+  wallets::solana::{find_or_create_token, get_token_details, get_wallet_sol_balance},
 };
 use ::entity::*;
 use anyhow::{anyhow, Result};
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use teloxide::prelude::*;
 
 pub async fn handle_buy_token(
@@ -35,85 +50,56 @@ pub async fn handle_buy_token(
   let wallet = wallets::Entity::find()
     .filter(wallets::Column::UserId.eq(authorized_user.id))
     .filter(wallets::Column::Chain.eq("solana"))
+    .filter(wallets::Column::WatchOnly.eq(false))
     .one(db)
     .await?
     .ok_or_else(|| anyhow!("No Solana wallet found for user"))?;
 
-  let wallet_sol_balance = get_wallet_sol_balance(&wallet.address)
-    .await
-    .map_err(|e| anyhow!("Failed to get wallet balance: {}", e))?;
-
-  if wallet_sol_balance < size_f64 {
-    return bot
-      .send_message(
-        msg.chat.id,
-        format!(
-          "Insufficient SOL balance. You have {} SOL but need {} SOL",
-          wallet_sol_balance, size
-        ),
-      )
+  // paper wallets don't hold the SOL they trade with
+  if !is_paper_wallet(db, &wallet).await? {
+    let wallet_sol_balance = get_wallet_sol_balance(&wallet.address)
       .await
-      .map_err(|e| anyhow!("Failed to send message: {}", e));
+      .map_err(|e| anyhow!("Failed to get wallet balance: {}", e))?;
+
+    if wallet_sol_balance < size_f64 {
+      return bot
+        .send_message(
+          msg.chat.id,
+          format!(
+            "Insufficient SOL balance. You have {} SOL but need {} SOL",
+            wallet_sol_balance, size
+          ),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to send message: {}", e));
+    }
   }
 
-  let raydium_client = RaydiumPriceFetcher::new();
-  let quote = raydium_client
-    .get_swap_quote(
-      "So11111111111111111111111111111111111111112",
-      &ca,
-      &(size_f64 * 1_000_000_000.0).to_string(),
-      "50",
-    )
-    .await
-    .map_err(|e| anyhow!("Failed to get a quote: {}", e))?;
-
-  let swap_tx = raydium_client
-    .get_swap_tx(
-      &wallet.address,
-      quote,
-      "So11111111111111111111111111111111111111112",
-      &ca,
-      &wallet.address,
-    )
-    .await
-    .map_err(|e| anyhow!("Failed to get a tx: {}", e))?;
+  // one swap at a time from a wallet, the price jobs and DCA plans take the same lock
+  let lock = match acquire_execution_lock(db, vec![wallet_lock_key(wallet.id)]).await? {
+    Some(lock) => lock,
+    None => {
+      return bot
+        .send_message(
+          msg.chat.id,
+          "Another swap from your wallet is in progress, try again in a moment",
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to send message: {}", e))
+    }
+  };
 
-  let swap_result = execute_user_swap_txs(authorized_user.id, wallet.id, db.clone(), swap_tx).await;
+  let swap_result = buy_token_with_wallet(db, authorized_user.id, &wallet, &ca, size_f64).await;
+
+  if let Err(e) = release_execution_lock(db, lock).await {
+    eprintln!("Failed to release lock of wallet {}: {:?}", wallet.id, e);
+  }
 
   match swap_result {
     Ok(attempt) => {
-      let transaction = onchain_transactions::ActiveModel {
-        user_id: Set(authorized_user.id),
-        wallet_id: Set(wallet.id),
-        transaction_hash: Set(Some(attempt.transaction_hash.clone())),
-        chain: Set("solana".to_string()),
-        source: Set(Some("raydium".to_string())),
-        status: Set(Some(
-          if attempt.success {
-            "confirmed"
-          } else {
-            "submitted"
-          }
-          .to_string(),
-        )),
-        r#type: Set(Some("swap".to_string())),
-        value_native: Set(Some(size_f64 as f32)),
-        value_usd: Set(Some(0.0)),
-        from_token: Set(Some(ca.clone())),
-        to_token: Set(Some(
-          "So11111111111111111111111111111111111111112".to_string(),
-        )),
-        ..Default::default()
-      };
-
-      if let Err(e) = onchain_transactions::Entity::insert(transaction)
-        .exec(db)
-        .await
-      {
-        eprintln!("Failed to record transaction: {}", e);
-      }
-
-      let status = if attempt.success {
+      let status = if attempt.simulated_fill.is_some() {
+        "simulated"
+      } else if attempt.success {
         "confirmed"
       } else {
         "submitted"
@@ -138,3 +124,309 @@ pub async fn handle_buy_token(
     }
   }
 }
+
+// quotes and sends the buy, recording it while the wallet is still locked
+async fn buy_token_with_wallet(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet: &wallets::Model,
+  ca: &str,
+  size: f64,
+) -> Result<SwapTxResult> {
+  let settings = find_or_create_user_settings(db, user_id).await?;
+  let policy = SlippagePolicy::for_kind(&settings, SwapKind::Buy);
+  let amount = ((size * 1_000_000_000.0).floor() as u64).to_string();
+
+  let quote = quote_best_swap(
+    "So11111111111111111111111111111111111111112",
+    ca,
+    &amount,
+    policy.initial_bps,
+  )
+  .await
+  .map_err(|e| anyhow!("Failed to get a quote: {}", e))?;
+
+  let attempt = execute_swap_with_escalation(user_id, wallet.id, db, quote, SwapKind::Buy).await?;
+
+  if let Err(e) = record_transaction(db, user_id, wallet.id, ca, attempt.clone(), size, 0.0).await {
+    eprintln!("Failed to record transaction: {}", e);
+  }
+
+  Ok(attempt)
+}
+
+pub(super) struct OrderTarget {
+  pub user_id: i32,
+  pub wallet_id: i32,
+  pub wallet: wallets::Model,
+  pub token_id: i32,
+  pub token_price: f64,
+}
+
+pub(super) async fn resolve_order_target(
+  msg: &Message,
+  db: &DatabaseConnection,
+  ca: &str,
+) -> Result<OrderTarget> {
+  let tg_user = msg
+    .from()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))?;
+
+  // Find associated wallet
+  let wallet = wallets::Entity::find()
+    .filter(wallets::Column::UserId.eq(authorized_user.id))
+    .filter(wallets::Column::WatchOnly.eq(false))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("No Solana wallet found for user"))?;
+
+  let token = get_token_details(ca)
+    .await
+    .map_err(|e| anyhow!("Failed to get token details: {}", e))?;
+
+  let token_id = find_or_create_token(db, &token, ca).await?;
+
+  let token_price = fetch_token_price(ca)
+    .await
+    .map_err(|e| anyhow!("Failed to fetch token price: {}", e))?;
+
+  Ok(OrderTarget {
+    user_id: authorized_user.id,
+    wallet_id: wallet.id,
+    wallet,
+    token_id,
+    token_price,
+  })
+}
+
+pub async fn handle_take_profit(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  ca: String,
+  multiple: String,
+) -> Result<Message> {
+  let target_multiple = multiple
+    .trim_end_matches('x')
+    .parse::<f64>()
+    .map_err(|_| anyhow!("Invalid multiple format. Please provide a number such as 2 for 2x"))?;
+
+  let target = resolve_order_target(&msg, db, &ca).await?;
+
+  let response_message = match create_take_profit_order(
+    db,
+    target.user_id,
+    target.wallet_id,
+    target.token_id,
+    &ca,
+    target.token_price,
+    target_multiple,
+  )
+  .await
+  {
+    Ok(order) => format!(
+      "Take profit set ✅\nToken: {}\nEntry Price: {}\nTarget Price: {} ({}x)",
+      ca, order.reference_price, order.target_price, target_multiple
+    ),
+    Err(e) => format!("Failed to set take profit: {}", e),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
+
+pub async fn handle_trailing_stop(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  ca: String,
+  percentage: String,
+) -> Result<Message> {
+  let trail_percentage = percentage
+    .trim_end_matches('%')
+    .parse::<f32>()
+    .map_err(|_| anyhow!("Invalid percentage format. Please provide a number such as 25"))?;
+
+  let target = resolve_order_target(&msg, db, &ca).await?;
+
+  let response_message = match create_trailing_stop_order(
+    db,
+    target.user_id,
+    target.wallet_id,
+    target.token_id,
+    &ca,
+    target.token_price,
+    trail_percentage,
+  )
+  .await
+  {
+    Ok(order) => format!(
+      "Trailing stop set ✅\nToken: {}\nEntry Price: {}\nStop Price: {} ({}% below the peak)",
+      ca, order.reference_price, order.target_price, trail_percentage
+    ),
+    Err(e) => format!("Failed to set trailing stop: {}", e),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
+
+pub async fn handle_ladder(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  ca: String,
+  rungs: String,
+  trail: String,
+) -> Result<Message> {
+  let rungs = parse_ladder_rungs(&rungs)?;
+
+  // a trail of 0 leaves whatever the rungs don't sell untouched
+  let trail_percentage = match trail
+    .trim_end_matches('%')
+    .parse::<f32>()
+    .map_err(|_| anyhow!("Invalid trail format. Please provide a number such as 25, or 0"))?
+  {
+    percentage if percentage > 0.0 => Some(percentage),
+    _ => None,
+  };
+
+  let target = resolve_order_target(&msg, db, &ca).await?;
+
+  let balance = wallet_token_balance(db, &target.wallet, &ca).await?;
+
+  let plan = LadderPlan {
+    reference_price: target.token_price,
+    position_amount: balance.ui_amount,
+    rungs,
+    trail_percentage,
+  };
+
+  let response_message = match create_ladder_order(
+    db,
+    target.user_id,
+    target.wallet_id,
+    target.token_id,
+    &ca,
+    &plan,
+  )
+  .await
+  {
+    Ok(order) => {
+      let rung_lines: Vec<String> = plan
+        .rungs
+        .iter()
+        .map(|rung| {
+          format!(
+            "Sell {}% at {} ({}x)",
+            rung.sell_fraction * 100.0,
+            order.reference_price as f64 * rung.target_multiple,
+            rung.target_multiple
+          )
+        })
+        .collect();
+
+      let trail_line = match plan.trail_percentage {
+        Some(percentage) => format!("\nRest trails {}% below the peak", percentage),
+        None => String::new(),
+      };
+
+      format!(
+        "Ladder set ✅\nToken: {}\nEntry Price: {}\nPosition: {}\n{}{}",
+        ca,
+        order.reference_price,
+        plan.position_amount,
+        rung_lines.join("\n"),
+        trail_line
+      )
+    }
+    Err(e) => format!("Failed to set ladder: {}", e),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
+
+pub async fn handle_limit_buy(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  ca: String,
+  size: String,
+  price: String,
+  stop_loss: String,
+) -> Result<Message> {
+  let sol_amount = size
+    .parse::<f64>()
+    .map_err(|_| anyhow!("Invalid size format. Please provide a valid number"))?;
+
+  let stop_loss_percentage = match stop_loss
+    .trim_end_matches('%')
+    .parse::<f32>()
+    .map_err(|_| anyhow!("Invalid stop loss format. Please provide a number such as 20, or 0"))?
+  {
+    percentage if percentage > 0.0 => Some(percentage),
+    _ => None,
+  };
+
+  let target = resolve_order_target(&msg, db, &ca).await?;
+
+  // a price ending in % is a dip below the current price, anything else is the price itself
+  let target_price = match price.strip_suffix('%') {
+    Some(dip) => {
+      let dip = dip
+        .trim_start_matches('-')
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid dip format. Please provide a percentage such as 20%"))?;
+      target.token_price * (1.0 - dip / 100.0)
+    }
+    None => price
+      .parse::<f64>()
+      .map_err(|_| anyhow!("Invalid price format. Please provide a price or a dip such as 20%"))?,
+  };
+
+  let response_message = match create_limit_buy_order(
+    db,
+    target.user_id,
+    target.wallet_id,
+    target.token_id,
+    &ca,
+    target.token_price,
+    target_price,
+    sol_amount,
+    stop_loss_percentage,
+    None,
+  )
+  .await
+  {
+    Ok(order) => format!(
+      "Limit buy set ✅\nToken: {}\nCurrent Price: {}\nBuy Price: {}\nAmount: {} SOL{}",
+      ca,
+      order.reference_price,
+      order.target_price,
+      sol_amount,
+      stop_loss_percentage
+        .map(|percentage| format!("\nStop loss: {}% below the fill", percentage))
+        .unwrap_or_default()
+    ),
+    Err(e) => format!("Failed to set limit buy: {}", e),
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
@@ -0,0 +1,74 @@
+use crate::utils::wallets::watch::{
+  create_watch_only_wallet, delete_watch_only_wallet, find_user_watch_only_wallets,
+  find_wallet_holdings, format_watch_only_wallet,
+};
+use ::entity::*;
+use anyhow::{anyhow, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use teloxide::prelude::*;
+
+const TRACK_WALLET_USAGE: &str = "Watch a wallet with /trackwallet address [title]\nYou get alerts when it sends, receives or buys a token\nStop watching one with /trackwallet remove wallet_id";
+
+pub async fn handle_track_wallet(
+  bot: Bot,
+  msg: Message,
+  db: &DatabaseConnection,
+  args: String,
+) -> Result<Message> {
+  let tg_user = msg
+    .from
+    .as_ref()
+    .ok_or_else(|| anyhow!("No Telegram user found"))?;
+
+  // Find authorized user
+  let authorized_user = users::Entity::find()
+    .filter(users::Column::TgId.eq(&tg_user.id.to_string()))
+    .one(db)
+    .await?
+    .ok_or_else(|| anyhow!("User not found in database"))?;
+
+  let parts: Vec<&str> = args.split_whitespace().collect();
+
+  let response_message = match parts.as_slice() {
+    [] => {
+      let wallets = find_user_watch_only_wallets(db, authorized_user.id).await?;
+      if wallets.is_empty() {
+        format!("You don't watch any wallets.\n\n{}", TRACK_WALLET_USAGE)
+      } else {
+        let mut wallet_lines = vec![];
+        for wallet in &wallets {
+          let holdings = find_wallet_holdings(db, wallet.id).await?;
+          wallet_lines.push(format_watch_only_wallet(wallet, &holdings));
+        }
+        format!("Wallets you watch:\n\n{}", wallet_lines.join("\n\n"))
+      }
+    }
+    ["remove", wallet_id] => {
+      let wallet_id = wallet_id
+        .parse::<i32>()
+        .map_err(|_| anyhow!("Invalid wallet id {}", wallet_id))?;
+
+      match delete_watch_only_wallet(db, authorized_user.id, wallet_id).await {
+        Ok(()) => format!("Stopped watching {} ✅", wallet_id),
+        Err(e) => format!("Could not stop watching {}: {}", wallet_id, e),
+      }
+    }
+    [address, title @ ..] => {
+      let title = if title.is_empty() {
+        "Watched wallet".to_string()
+      } else {
+        title.join(" ")
+      };
+
+      match create_watch_only_wallet(db, authorized_user.id, &title, address).await {
+        Ok(wallet) => format!("Watching ✅\n{}", format_watch_only_wallet(&wallet, &[])),
+        Err(e) => format!("Failed to watch {}: {}", address, e),
+      }
+    }
+  };
+
+  bot
+    .send_message(msg.chat.id, response_message)
+    .await
+    .map_err(|e| anyhow!("Failed to send message: {}", e))
+}
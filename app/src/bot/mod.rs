@@ -7,9 +7,20 @@ use crate::{
   },
 };
 use ::entity::*;
-use commands::trade::handle_buy_token;
+use commands::{
+  copy_trade::handle_watch_wallet,
+  dca::handle_dca,
+  history::{handle_history, handle_history_callback, HISTORY_PAGE_CALLBACK},
+  orders::{handle_order_callback, handle_orders, CANCEL_ORDER_CALLBACK, EDIT_ORDER_CALLBACK},
+  portfolio::handle_portfolio,
+  settings::handle_settings,
+  trade::{
+    handle_buy_token, handle_ladder, handle_limit_buy, handle_take_profit, handle_trailing_stop,
+  },
+  watch::handle_track_wallet,
+};
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
-use teloxide::{prelude::*, utils::command::BotCommands};
+use teloxide::{dispatching::UpdateHandler, prelude::*, utils::command::BotCommands, RequestError};
 
 mod commands;
 
@@ -21,7 +32,9 @@ mod commands;
 pub enum Command {
   #[command(description = "display this text.")]
   Help,
-  #[command(description = "Start with tg token")]
+  #[command(
+    description = "list the wallets you copy trade, follow one or turn one on or off by id"
+  )]
   WatchSolWallet(String),
   #[command(
     description = "Welcome add your email space password",
@@ -39,6 +52,60 @@ pub enum Command {
   CreateSolWallet,
   #[command(description = "attempt to buy a token", parse_with = "split")]
   BuyToken { ca: String, size: String },
+  #[command(
+    description = "buy a token when it dips with ca space size space price or dip% space stop loss percentage (0 for none)",
+    parse_with = "split"
+  )]
+  LimitBuy {
+    ca: String,
+    size: String,
+    price: String,
+    stop_loss: String,
+  },
+  #[command(
+    description = "sell a token when it hits a multiple with ca space multiple",
+    parse_with = "split"
+  )]
+  TakeProfit { ca: String, multiple: String },
+  #[command(
+    description = "sell a token when it falls below its peak with ca space percentage",
+    parse_with = "split"
+  )]
+  TrailingStop { ca: String, percentage: String },
+  #[command(
+    description = "sell a token in steps with ca space multiple:percentage,... space trail percentage (0 for none)",
+    parse_with = "split"
+  )]
+  Ladder {
+    ca: String,
+    rungs: String,
+    trail: String,
+  },
+  #[command(description = "view your trade settings or update one with key space value")]
+  Settings(String),
+  #[command(description = "list your dca plans, start one or pause, resume or cancel one by id")]
+  Dca(String),
+  #[command(
+    description = "list the wallets you watch, watch one with address space title or remove one by id"
+  )]
+  TrackWallet(String),
+  #[command(description = "value your wallets, their tokens and the orders on them")]
+  Portfolio,
+  #[command(description = "page through your transactions, optionally from a page number")]
+  History(String),
+  #[command(description = "list your active orders to cancel them or move their target")]
+  Orders(String),
+}
+
+/// Commands sent to the bot and the buttons pressed under its messages
+pub fn schema() -> UpdateHandler<RequestError> {
+  dptree::entry()
+    .branch(
+      Update::filter_message()
+        .filter_command::<Command>()
+        .endpoint(answer),
+    )
+    .branch(Update::filter_callback_query().endpoint(answer_callback))
 }
 
 pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
@@ -52,11 +119,19 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
         .send_message(msg.chat.id, Command::descriptions().to_string())
         .await?
     }
-    // TODO
-    Command::WatchSolWallet(address) => {
-      bot
-        .send_message(msg.chat.id, format!("Your address is @{address}."))
-        .await?
+    Command::WatchSolWallet(args) => {
+      match handle_watch_wallet(bot.clone(), msg.clone(), &db, args).await {
+        Ok(message) => message,
+        Err(e) => {
+          eprintln!("Failed to handle watched wallet {:?}", e);
+          bot
+            .send_message(
+              msg.chat.id,
+              format!("Failed to handle followed wallet: {}", e),
+            )
+            .await?
+        }
+      }
     }
     Command::TgToken { email, tg_token } => {
       // Query to find the existing user
@@ -136,6 +211,7 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
           let signer = recover_wallet_from_private_key(&pk).unwrap();
           let existing_wallet = wallets::Entity::find()
             .filter(wallets::Column::Chain.eq("Solana".to_string()))
+            .filter(wallets::Column::UserId.eq(user.id))
             .filter(wallets::Column::Address.eq(signer.public_key.clone()))
             .one(&db)
             .await;
@@ -148,9 +224,9 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
                 title: Set(title.clone()),
                 chain: Set("Solana".to_string()),
                 user_id: Set(user.id),
-                salt: Set(signer.salt.unwrap()),
-                secret_key: Set(signer.secret_key.unwrap()),
-                encrypted_private_key: Set(signer.encrypted_private_key.unwrap()),
+                salt: Set(signer.salt),
+                secret_key: Set(signer.secret_key),
+                encrypted_private_key: Set(signer.encrypted_private_key),
                 address: Set(signer.public_key),
                 encryption_schema: Set("From_PK".to_string()),
                 ..Default::default()
@@ -186,6 +262,7 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
           let signer = generate_wallet();
           let existing_wallet = wallets::Entity::find()
             .filter(wallets::Column::Chain.eq("Solana".to_string()))
+            .filter(wallets::Column::UserId.eq(user.id))
             .filter(wallets::Column::Address.eq(signer.public_key.clone()))
             .one(&db)
             .await;
@@ -198,9 +275,9 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
                 title: Set("Davids Sling Wallet".to_string()),
                 chain: Set("Solana".to_string()),
                 user_id: Set(user.id),
-                salt: Set(signer.salt.unwrap()),
-                secret_key: Set(signer.secret_key.unwrap()),
-                encrypted_private_key: Set(signer.encrypted_private_key.unwrap()),
+                salt: Set(signer.salt),
+                secret_key: Set(signer.secret_key),
+                encrypted_private_key: Set(signer.encrypted_private_key),
                 address: Set(signer.public_key),
                 encryption_schema: Set("Sling Wallet".to_string()),
                 ..Default::default()
@@ -230,7 +307,140 @@ pub async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()>
         }
       }
     }
+    Command::LimitBuy {
+      ca,
+      size,
+      price,
+      stop_loss,
+    } => match handle_limit_buy(bot.clone(), msg.clone(), &db, ca, size, price, stop_loss).await {
+      Ok(message) => message,
+      Err(e) => {
+        eprintln!("Failed to set limit buy {:?}", e);
+        bot
+          .send_message(msg.chat.id, format!("Failed to set limit buy: {}", e))
+          .await?
+      }
+    },
+    Command::TakeProfit { ca, multiple } => {
+      match handle_take_profit(bot.clone(), msg.clone(), &db, ca, multiple).await {
+        Ok(message) => message,
+        Err(e) => {
+          eprintln!("Failed to set take profit {:?}", e);
+          bot
+            .send_message(msg.chat.id, format!("Failed to set take profit: {}", e))
+            .await?
+        }
+      }
+    }
+    Command::TrailingStop { ca, percentage } => {
+      match handle_trailing_stop(bot.clone(), msg.clone(), &db, ca, percentage).await {
+        Ok(message) => message,
+        Err(e) => {
+          eprintln!("Failed to set trailing stop {:?}", e);
+          bot
+            .send_message(msg.chat.id, format!("Failed to set trailing stop: {}", e))
+            .await?
+        }
+      }
+    }
+    Command::Ladder { ca, rungs, trail } => {
+      match handle_ladder(bot.clone(), msg.clone(), &db, ca, rungs, trail).await {
+        Ok(message) => message,
+        Err(e) => {
+          eprintln!("Failed to set ladder {:?}", e);
+          bot
+            .send_message(msg.chat.id, format!("Failed to set ladder: {}", e))
+            .await?
+        }
+      }
+    }
+    Command::Settings(args) => match handle_settings(bot.clone(), msg.clone(), &db, args).await {
+      Ok(message) => message,
+      Err(e) => {
+        eprintln!("Failed to handle settings {:?}", e);
+        bot.send_message(msg.chat.id, "An error occured").await?
+      }
+    },
+    Command::Dca(args) => match handle_dca(bot.clone(), msg.clone(), &db, args).await {
+      Ok(message) => message,
+      Err(e) => {
+        eprintln!("Failed to handle dca {:?}", e);
+        bot
+          .send_message(msg.chat.id, format!("Failed to handle dca: {}", e))
+          .await?
+      }
+    },
+    Command::TrackWallet(args) => {
+      match handle_track_wallet(bot.clone(), msg.clone(), &db, args).await {
+        Ok(message) => message,
+        Err(e) => {
+          eprintln!("Failed to handle tracked wallet {:?}", e);
+          bot
+            .send_message(
+              msg.chat.id,
+              format!("Failed to handle tracked wallet: {}", e),
+            )
+            .await?
+        }
+      }
+    }
+    Command::History(args) => match handle_history(bot.clone(), msg.clone(), &db, args).await {
+      Ok(message) => message,
+      Err(e) => {
+        eprintln!("Failed to handle history {:?}", e);
+        bot
+          .send_message(msg.chat.id, format!("Failed to handle history: {}", e))
+          .await?
+      }
+    },
+    Command::Orders(args) => match handle_orders(bot.clone(), msg.clone(), &db, args).await {
+      Ok(message) => message,
+      Err(e) => {
+        eprintln!("Failed to handle orders {:?}", e);
+        bot
+          .send_message(msg.chat.id, format!("Failed to handle orders: {}", e))
+          .await?
+      }
+    },
+    Command::Portfolio => match handle_portfolio(bot.clone(), msg.clone(), &db).await {
+      Ok(message) => message,
+      Err(e) => {
+        eprintln!("Failed to value portfolio {:?}", e);
+        bot
+          .send_message(msg.chat.id, format!("Failed to value portfolio: {}", e))
+          .await?
+      }
+    },
+  };
+
+  Ok(())
+}
+
+pub async fn answer_callback(bot: Bot, query: CallbackQuery) -> ResponseResult<()> {
+  let db = db::connect_db()
+    .await
+    .expect("Failed to connect to the database");
+
+  let query_id = query.id.clone();
+  let data = query.data.clone().unwrap_or_default();
+
+  let result = if let Some(page) = data.strip_prefix(HISTORY_PAGE_CALLBACK) {
+    handle_history_callback(bot.clone(), query, &db, page).await
+  } else if data.starts_with(CANCEL_ORDER_CALLBACK) || data.starts_with(EDIT_ORDER_CALLBACK) {
+    handle_order_callback(bot.clone(), query, &db, &data).await
+  } else {
+    Err(anyhow::anyhow!("Unknown callback {}", data))
   };
 
+  // the button stops spinning either way, with the error shown when there was one
+  if let Err(e) = result {
+    eprintln!("Failed to handle callback {:?}", e);
+    bot
+      .answer_callback_query(query_id)
+      .text(format!("Failed: {}", e))
+      .show_alert(true)
+      .await?;
+  }
+
   Ok(())
 }
@@ -0,0 +1,202 @@
+use crate::{
+  gql::schemas::{root::Context, trade_order::resolve_order_target},
+  utils::dca::{
+    create_dca_plan, find_user_dca_plans, parse_dca_end, update_dca_plan_status, DcaPlanParams,
+  },
+};
+use ::entity::*;
+use juniper::{graphql_object, GraphQLInputObject};
+
+/// Recurring buy of a token until its budget or end date runs out
+#[derive(Default, Debug)]
+pub struct DcaPlan {
+  pub id: i32,
+  pub wallet_id: i32,
+  pub token_id: i32,
+  pub contract_address: String,
+  pub amount_per_buy: f64,
+  pub interval_minutes: i32,
+  pub total_budget: f64,
+  pub spent_sol: f64,
+  pub buys_completed: i32,
+  pub status: String,
+  pub next_buy_at: String,
+  pub last_buy_at: Option<String>,
+  pub ends_at: String,
+  pub created_at: String,
+}
+
+impl From<dca_plans::Model> for DcaPlan {
+  fn from(p: dca_plans::Model) -> Self {
+    DcaPlan {
+      id: p.id,
+      wallet_id: p.wallet_id,
+      token_id: p.token_id,
+      contract_address: p.contract_address,
+      amount_per_buy: p.amount_per_buy as f64,
+      interval_minutes: p.interval_minutes,
+      total_budget: p.total_budget as f64,
+      spent_sol: p.spent_sol as f64,
+      buys_completed: p.buys_completed,
+      status: p.status,
+      next_buy_at: p.next_buy_at.to_string(),
+      last_buy_at: p.last_buy_at.map(|last_buy_at| last_buy_at.to_string()),
+      ends_at: p.ends_at.to_string(),
+      created_at: p.created_at.to_string(),
+    }
+  }
+}
+
+#[graphql_object(context = Context)]
+impl DcaPlan {
+  fn id(&self) -> i32 {
+    self.id
+  }
+
+  fn wallet_id(&self) -> i32 {
+    self.wallet_id
+  }
+
+  fn token_id(&self) -> i32 {
+    self.token_id
+  }
+
+  fn contract_address(&self) -> &str {
+    &self.contract_address
+  }
+
+  fn amount_per_buy(&self) -> f64 {
+    self.amount_per_buy
+  }
+
+  fn interval_minutes(&self) -> i32 {
+    self.interval_minutes
+  }
+
+  fn total_budget(&self) -> f64 {
+    self.total_budget
+  }
+
+  fn spent_sol(&self) -> f64 {
+    self.spent_sol
+  }
+
+  fn buys_completed(&self) -> i32 {
+    self.buys_completed
+  }
+
+  fn status(&self) -> &str {
+    &self.status
+  }
+
+  fn next_buy_at(&self) -> &str {
+    &self.next_buy_at
+  }
+
+  fn last_buy_at(&self) -> Option<&str> {
+    self.last_buy_at.as_deref()
+  }
+
+  fn ends_at(&self) -> &str {
+    &self.ends_at
+  }
+
+  fn created_at(&self) -> &str {
+    &self.created_at
+  }
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(
+  description = "Input for a DCA plan, ends_at is a number of days such as 7d, a date or an RFC 3339 timestamp"
+)]
+pub struct NewDcaPlanInput {
+  pub wallet_id: i32,
+  pub contract_address: String,
+  pub amount_per_buy: f64,
+  pub interval_minutes: i32,
+  pub total_budget: f64,
+  pub ends_at: String,
+}
+
+pub struct DcaPlanQuery;
+
+#[graphql_object(context = Context)]
+impl DcaPlanQuery {
+  async fn plans(context: &Context, open_only: Option<bool>) -> Result<Vec<DcaPlan>, String> {
+    let plan_user = context.user.as_ref().ok_or("User not found")?;
+
+    let plans = find_user_dca_plans(&context.db, plan_user.id, open_only.unwrap_or(true))
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(plans.into_iter().map(DcaPlan::from).collect())
+  }
+}
+
+pub struct DcaPlanMutation;
+
+#[graphql_object(context = Context)]
+impl DcaPlanMutation {
+  async fn create_dca_plan(context: &Context, input: NewDcaPlanInput) -> Result<DcaPlan, String> {
+    let plan_user = context.user.as_ref().ok_or("User not found")?;
+
+    let params = DcaPlanParams {
+      amount_per_buy: input.amount_per_buy,
+      interval_minutes: input.interval_minutes,
+      total_budget: input.total_budget,
+      ends_at: parse_dca_end(&input.ends_at).map_err(|e| e.to_string())?,
+    };
+
+    let (wallet, token_id, _) = resolve_order_target(
+      context,
+      plan_user.id,
+      input.wallet_id,
+      &input.contract_address,
+    )
+    .await?;
+
+    let plan = create_dca_plan(
+      &context.db,
+      plan_user.id,
+      wallet.id,
+      token_id,
+      &input.contract_address,
+      &params,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(plan.into())
+  }
+
+  async fn pause_dca_plan(context: &Context, id: i32) -> Result<DcaPlan, String> {
+    let plan_user = context.user.as_ref().ok_or("User not found")?;
+
+    let plan = update_dca_plan_status(&context.db, plan_user.id, id, "pause")
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(plan.into())
+  }
+
+  async fn resume_dca_plan(context: &Context, id: i32) -> Result<DcaPlan, String> {
+    let plan_user = context.user.as_ref().ok_or("User not found")?;
+
+    let plan = update_dca_plan_status(&context.db, plan_user.id, id, "resume")
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(plan.into())
+  }
+
+  async fn cancel_dca_plan(context: &Context, id: i32) -> Result<DcaPlan, String> {
+    let plan_user = context.user.as_ref().ok_or("User not found")?;
+
+    let plan = update_dca_plan_status(&context.db, plan_user.id, id, "cancel")
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(plan.into())
+  }
+}
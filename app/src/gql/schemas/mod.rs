@@ -1,3 +1,7 @@
+pub mod dca_plan;
+pub mod position;
 pub mod root;
+pub mod settings;
+pub mod trade_order;
 pub mod user;
 pub mod wallet;
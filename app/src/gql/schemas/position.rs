@@ -0,0 +1,126 @@
+use crate::{
+  gql::schemas::root::Context,
+  utils::positions::{find_user_positions, position_quantity, position_unrealized_pnl},
+};
+use ::entity::*;
+use juniper::graphql_object;
+
+/// What a wallet holds of a token, as its confirmed swaps add up
+#[derive(Default, Debug)]
+pub struct Position {
+  pub id: i32,
+  pub wallet_id: i32,
+  pub contract_address: String,
+  pub quantity: f64,
+  pub cost_basis_sol: f64,
+  pub cost_basis_usd: f64,
+  pub average_entry_price: f64,
+  pub average_entry_price_usd: f64,
+  pub realized_pnl_sol: f64,
+  pub unrealized_pnl_sol: Option<f64>,
+  pub fees_sol: f64,
+  pub simulated: bool,
+  pub updated_at: String,
+}
+
+impl Position {
+  fn new(p: positions::Model, unrealized_pnl_sol: Option<f64>) -> Self {
+    Position {
+      id: p.id,
+      wallet_id: p.wallet_id,
+      quantity: position_quantity(&p),
+      contract_address: p.contract_address,
+      cost_basis_sol: p.cost_basis_sol as f64,
+      cost_basis_usd: p.cost_basis_usd as f64,
+      average_entry_price: p.average_entry_price as f64,
+      average_entry_price_usd: p.average_entry_price_usd as f64,
+      realized_pnl_sol: p.realized_pnl_sol as f64,
+      unrealized_pnl_sol,
+      fees_sol: p.fees_lamports as f64 / 1_000_000_000.0,
+      simulated: p.simulated,
+      updated_at: p.updated_at.to_string(),
+    }
+  }
+}
+
+#[graphql_object(context = Context)]
+impl Position {
+  fn id(&self) -> i32 {
+    self.id
+  }
+
+  fn wallet_id(&self) -> i32 {
+    self.wallet_id
+  }
+
+  fn contract_address(&self) -> &str {
+    &self.contract_address
+  }
+
+  fn quantity(&self) -> f64 {
+    self.quantity
+  }
+
+  fn cost_basis_sol(&self) -> f64 {
+    self.cost_basis_sol
+  }
+
+  fn cost_basis_usd(&self) -> f64 {
+    self.cost_basis_usd
+  }
+
+  fn average_entry_price(&self) -> f64 {
+    self.average_entry_price
+  }
+
+  fn average_entry_price_usd(&self) -> f64 {
+    self.average_entry_price_usd
+  }
+
+  fn realized_pnl_sol(&self) -> f64 {
+    self.realized_pnl_sol
+  }
+
+  /// PnL of what is still held at the latest recorded price, null without one
+  fn unrealized_pnl_sol(&self) -> Option<f64> {
+    self.unrealized_pnl_sol
+  }
+
+  fn fees_sol(&self) -> f64 {
+    self.fees_sol
+  }
+
+  /// Whether the position was built from paper fills
+  fn simulated(&self) -> bool {
+    self.simulated
+  }
+
+  fn updated_at(&self) -> &str {
+    &self.updated_at
+  }
+}
+
+pub struct PositionQuery;
+
+#[graphql_object(context = Context)]
+impl PositionQuery {
+  async fn positions(context: &Context, open_only: Option<bool>) -> Result<Vec<Position>, String> {
+    let position_user = context.user.as_ref().ok_or("User not found")?;
+
+    let positions = find_user_positions(&context.db, position_user.id, open_only.unwrap_or(true))
+      .await
+      .map_err(|e| e.to_string())?;
+
+    let mut result = Vec::with_capacity(positions.len());
+
+    for position in positions {
+      let unrealized_pnl_sol = position_unrealized_pnl(&context.db, &position)
+        .await
+        .map_err(|e| e.to_string())?;
+
+      result.push(Position::new(position, unrealized_pnl_sol));
+    }
+
+    Ok(result)
+  }
+}
@@ -9,6 +9,10 @@ pub struct Context {
 impl juniper::Context for Context {}
 
 use super::{
+  dca_plan::{DcaPlanMutation, DcaPlanQuery},
+  position::PositionQuery,
+  settings::{SettingsMutation, SettingsQuery},
+  trade_order::{TradeOrderMutation, TradeOrderQuery},
   user::{UserMutation, UserQuery},
   wallet::{WalletMutation, WalletQuery},
 };
@@ -28,6 +32,18 @@ impl Query {
   fn wallet() -> WalletQuery {
     WalletQuery
   }
+  fn settings() -> SettingsQuery {
+    SettingsQuery
+  }
+  fn order() -> TradeOrderQuery {
+    TradeOrderQuery
+  }
+  fn dca() -> DcaPlanQuery {
+    DcaPlanQuery
+  }
+  fn position() -> PositionQuery {
+    PositionQuery
+  }
 }
 
 pub struct Mutation;
@@ -41,6 +57,15 @@ impl Mutation {
   fn wallet() -> WalletMutation {
     WalletMutation
   }
+  fn settings() -> SettingsMutation {
+    SettingsMutation
+  }
+  fn order() -> TradeOrderMutation {
+    TradeOrderMutation
+  }
+  fn dca() -> DcaPlanMutation {
+    DcaPlanMutation
+  }
 }
 
 pub type Schema = RootNode<'static, Query, Mutation, EmptySubscription<Context>>;
@@ -0,0 +1,286 @@
+use crate::{
+  gql::schemas::root::Context,
+  utils::settings::{find_or_create_user_settings, parse_launch_classes, save_user_settings},
+};
+use ::entity::*;
+use juniper::{graphql_object, GraphQLInputObject};
+
+/// Trading settings of a user
+#[derive(Default, Debug)]
+pub struct UserSettings {
+  pub id: i32,
+  pub user_id: i32,
+  pub auto_buy: bool,
+  pub buy_size_percentage: f64,
+  pub min_sol_per_trade: f64,
+  pub max_sol_per_trade: f64,
+  pub launch_stop_loss_percentage: f64,
+  pub default_stop_loss_percentage: f64,
+  pub min_watchlist_usd: f64,
+  pub slippage_bps: i32,
+  pub sell_slippage_bps: i32,
+  pub max_buy_slippage_bps: i32,
+  pub max_sell_slippage_bps: i32,
+  pub max_stop_loss_slippage_bps: i32,
+  pub launch_classes: Vec<String>,
+  pub paper_trading: bool,
+  pub max_price_impact_percentage: f64,
+  pub jito_tip_sol: f64,
+  pub jito_launch_buys: bool,
+  pub jito_stop_losses: bool,
+  pub updated_at: String,
+}
+
+impl From<user_settings::Model> for UserSettings {
+  fn from(s: user_settings::Model) -> Self {
+    UserSettings {
+      id: s.id,
+      user_id: s.user_id,
+      auto_buy: s.auto_buy,
+      buy_size_percentage: s.buy_size_percentage as f64,
+      min_sol_per_trade: s.min_sol_per_trade as f64,
+      max_sol_per_trade: s.max_sol_per_trade as f64,
+      launch_stop_loss_percentage: s.launch_stop_loss_percentage as f64,
+      default_stop_loss_percentage: s.default_stop_loss_percentage as f64,
+      min_watchlist_usd: s.min_watchlist_usd as f64,
+      slippage_bps: s.slippage_bps,
+      sell_slippage_bps: s.sell_slippage_bps,
+      max_buy_slippage_bps: s.max_buy_slippage_bps,
+      max_sell_slippage_bps: s.max_sell_slippage_bps,
+      max_stop_loss_slippage_bps: s.max_stop_loss_slippage_bps,
+      launch_classes: s
+        .launch_classes
+        .split(',')
+        .filter(|class| !class.is_empty())
+        .map(|class| class.to_string())
+        .collect(),
+      paper_trading: s.paper_trading,
+      max_price_impact_percentage: s.max_price_impact_percentage as f64,
+      jito_tip_sol: s.jito_tip_sol as f64,
+      jito_launch_buys: s.jito_launch_buys,
+      jito_stop_losses: s.jito_stop_losses,
+      updated_at: s.updated_at.to_string(),
+    }
+  }
+}
+
+#[graphql_object(context = Context)]
+impl UserSettings {
+  fn id(&self) -> i32 {
+    self.id
+  }
+
+  fn user_id(&self) -> i32 {
+    self.user_id
+  }
+
+  fn auto_buy(&self) -> bool {
+    self.auto_buy
+  }
+
+  fn buy_size_percentage(&self) -> f64 {
+    self.buy_size_percentage
+  }
+
+  fn min_sol_per_trade(&self) -> f64 {
+    self.min_sol_per_trade
+  }
+
+  fn max_sol_per_trade(&self) -> f64 {
+    self.max_sol_per_trade
+  }
+
+  fn launch_stop_loss_percentage(&self) -> f64 {
+    self.launch_stop_loss_percentage
+  }
+
+  fn default_stop_loss_percentage(&self) -> f64 {
+    self.default_stop_loss_percentage
+  }
+
+  fn min_watchlist_usd(&self) -> f64 {
+    self.min_watchlist_usd
+  }
+
+  fn slippage_bps(&self) -> i32 {
+    self.slippage_bps
+  }
+
+  fn sell_slippage_bps(&self) -> i32 {
+    self.sell_slippage_bps
+  }
+
+  fn max_buy_slippage_bps(&self) -> i32 {
+    self.max_buy_slippage_bps
+  }
+
+  fn max_sell_slippage_bps(&self) -> i32 {
+    self.max_sell_slippage_bps
+  }
+
+  fn max_stop_loss_slippage_bps(&self) -> i32 {
+    self.max_stop_loss_slippage_bps
+  }
+
+  fn launch_classes(&self) -> &Vec<String> {
+    &self.launch_classes
+  }
+
+  fn paper_trading(&self) -> bool {
+    self.paper_trading
+  }
+
+  fn max_price_impact_percentage(&self) -> f64 {
+    self.max_price_impact_percentage
+  }
+
+  fn jito_tip_sol(&self) -> f64 {
+    self.jito_tip_sol
+  }
+
+  fn jito_launch_buys(&self) -> bool {
+    self.jito_launch_buys
+  }
+
+  fn jito_stop_losses(&self) -> bool {
+    self.jito_stop_losses
+  }
+
+  fn updated_at(&self) -> String {
+    self.updated_at.to_string()
+  }
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Input for updating the trading settings of the current user")]
+pub struct UpdateSettingsInput {
+  pub auto_buy: Option<bool>,
+  pub buy_size_percentage: Option<f64>,
+  pub min_sol_per_trade: Option<f64>,
+  pub max_sol_per_trade: Option<f64>,
+  pub launch_stop_loss_percentage: Option<f64>,
+  pub default_stop_loss_percentage: Option<f64>,
+  pub min_watchlist_usd: Option<f64>,
+  pub slippage_bps: Option<i32>,
+  pub sell_slippage_bps: Option<i32>,
+  pub max_buy_slippage_bps: Option<i32>,
+  pub max_sell_slippage_bps: Option<i32>,
+  pub max_stop_loss_slippage_bps: Option<i32>,
+  pub launch_classes: Option<Vec<String>>,
+  pub paper_trading: Option<bool>,
+  pub max_price_impact_percentage: Option<f64>,
+  pub jito_tip_sol: Option<f64>,
+  pub jito_launch_buys: Option<bool>,
+  pub jito_stop_losses: Option<bool>,
+}
+
+pub struct SettingsQuery;
+
+#[graphql_object(context = Context)]
+impl SettingsQuery {
+  async fn settings(context: &Context) -> Result<UserSettings, String> {
+    let settings_user = context.user.as_ref().ok_or("User not found")?;
+
+    let settings = find_or_create_user_settings(&context.db, settings_user.id)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(settings.into())
+  }
+}
+
+pub struct SettingsMutation;
+
+#[graphql_object(context = Context)]
+impl SettingsMutation {
+  async fn update_settings(
+    context: &Context,
+    input: UpdateSettingsInput,
+  ) -> Result<UserSettings, String> {
+    let settings_user = context.user.as_ref().ok_or("User not found")?;
+
+    let mut settings = find_or_create_user_settings(&context.db, settings_user.id)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    for percentage in [
+      input.buy_size_percentage,
+      input.launch_stop_loss_percentage,
+      input.default_stop_loss_percentage,
+      input.max_price_impact_percentage,
+    ]
+    .into_iter()
+    .flatten()
+    {
+      if !(0.0..=100.0).contains(&percentage) {
+        return Err("Percentages must be between 0 and 100".to_string());
+      }
+    }
+
+    if let Some(auto_buy) = input.auto_buy {
+      settings.auto_buy = auto_buy;
+    }
+    if let Some(buy_size_percentage) = input.buy_size_percentage {
+      settings.buy_size_percentage = buy_size_percentage as f32;
+    }
+    if let Some(min_sol_per_trade) = input.min_sol_per_trade {
+      settings.min_sol_per_trade = min_sol_per_trade as f32;
+    }
+    if let Some(max_sol_per_trade) = input.max_sol_per_trade {
+      settings.max_sol_per_trade = max_sol_per_trade as f32;
+    }
+    if let Some(launch_stop_loss_percentage) = input.launch_stop_loss_percentage {
+      settings.launch_stop_loss_percentage = launch_stop_loss_percentage as f32;
+    }
+    if let Some(default_stop_loss_percentage) = input.default_stop_loss_percentage {
+      settings.default_stop_loss_percentage = default_stop_loss_percentage as f32;
+    }
+    if let Some(min_watchlist_usd) = input.min_watchlist_usd {
+      settings.min_watchlist_usd = min_watchlist_usd as f32;
+    }
+    if let Some(slippage_bps) = input.slippage_bps {
+      settings.slippage_bps = slippage_bps;
+    }
+    if let Some(sell_slippage_bps) = input.sell_slippage_bps {
+      settings.sell_slippage_bps = sell_slippage_bps;
+    }
+    if let Some(max_buy_slippage_bps) = input.max_buy_slippage_bps {
+      settings.max_buy_slippage_bps = max_buy_slippage_bps;
+    }
+    if let Some(max_sell_slippage_bps) = input.max_sell_slippage_bps {
+      settings.max_sell_slippage_bps = max_sell_slippage_bps;
+    }
+    if let Some(max_stop_loss_slippage_bps) = input.max_stop_loss_slippage_bps {
+      settings.max_stop_loss_slippage_bps = max_stop_loss_slippage_bps;
+    }
+    if let Some(launch_classes) = input.launch_classes {
+      settings.launch_classes =
+        parse_launch_classes(&launch_classes.join(",")).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(paper_trading) = input.paper_trading {
+      settings.paper_trading = paper_trading;
+    }
+    if let Some(max_price_impact_percentage) = input.max_price_impact_percentage {
+      settings.max_price_impact_percentage = max_price_impact_percentage as f32;
+    }
+    if let Some(jito_tip_sol) = input.jito_tip_sol {
+      if jito_tip_sol < 0.0 {
+        return Err("Jito tip must not be negative".to_string());
+      }
+      settings.jito_tip_sol = jito_tip_sol as f32;
+    }
+    if let Some(jito_launch_buys) = input.jito_launch_buys {
+      settings.jito_launch_buys = jito_launch_buys;
+    }
+    if let Some(jito_stop_losses) = input.jito_stop_losses {
+      settings.jito_stop_losses = jito_stop_losses;
+    }
+
+    let updated = save_user_settings(&context.db, settings)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(updated.into())
+  }
+}
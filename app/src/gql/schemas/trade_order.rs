@@ -0,0 +1,446 @@
+use crate::{
+  gql::schemas::root::Context,
+  utils::{
+    orders::{
+      ladder::{create_ladder_order, parse_ladder_rungs, LadderPlan},
+      lifecycle::{cancel_order, reactivate_order, OPEN_ORDER_STATUSES},
+      limit_buy::create_limit_buy_order,
+    },
+    price::solana::fetch_token_price,
+    swap::{
+      paper::wallet_token_balance,
+      solana::{create_take_profit_order, create_trailing_stop_order},
+    },
+    wallets::solana::{find_or_create_token, get_token_details},
+  },
+};
+use ::entity::*;
+use chrono::{Duration, Utc};
+use juniper::{graphql_object, GraphQLInputObject};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+/// Automated order on a token held in one of the user's wallets
+#[derive(Default, Debug)]
+pub struct TradeOrder {
+  pub id: i32,
+  pub wallet_id: i32,
+  pub token_id: i32,
+  pub contract_address: String,
+  pub strategy: String,
+  pub reference_price: f64,
+  pub target_price: f64,
+  pub target_percentage: f64,
+  pub status: String,
+  pub parent_id: Option<i32>,
+  pub sell_fraction: Option<f64>,
+  pub created_by: String,
+  pub triggered_at: Option<String>,
+  pub filled_at: Option<String>,
+  pub failed_at: Option<String>,
+  pub cancelled_at: Option<String>,
+  pub expired_at: Option<String>,
+  pub expires_at: Option<String>,
+  pub failure_reason: Option<String>,
+  pub filled_transaction_id: Option<i32>,
+  pub created_at: String,
+  pub updated_at: String,
+}
+
+impl From<trade_orders::Model> for TradeOrder {
+  fn from(o: trade_orders::Model) -> Self {
+    TradeOrder {
+      id: o.id,
+      wallet_id: o.wallet_id,
+      token_id: o.token_id,
+      contract_address: o.contract_address,
+      strategy: o.strategy,
+      reference_price: o.reference_price as f64,
+      target_price: o.target_price as f64,
+      target_percentage: o.target_percentage as f64,
+      status: o.status,
+      parent_id: o.parent_id,
+      sell_fraction: o.sell_fraction.map(|f| f as f64),
+      created_by: o.created_by,
+      triggered_at: o.triggered_at.map(|at| at.to_string()),
+      filled_at: o.filled_at.map(|at| at.to_string()),
+      failed_at: o.failed_at.map(|at| at.to_string()),
+      cancelled_at: o.cancelled_at.map(|at| at.to_string()),
+      expired_at: o.expired_at.map(|at| at.to_string()),
+      expires_at: o.expires_at.map(|at| at.to_string()),
+      failure_reason: o.failure_reason,
+      filled_transaction_id: o.filled_transaction_id,
+      created_at: o.created_at.to_string(),
+      updated_at: o.updated_at.to_string(),
+    }
+  }
+}
+
+#[graphql_object(context = Context)]
+impl TradeOrder {
+  fn id(&self) -> i32 {
+    self.id
+  }
+
+  fn wallet_id(&self) -> i32 {
+    self.wallet_id
+  }
+
+  fn token_id(&self) -> i32 {
+    self.token_id
+  }
+
+  fn contract_address(&self) -> &str {
+    &self.contract_address
+  }
+
+  fn strategy(&self) -> &str {
+    &self.strategy
+  }
+
+  fn reference_price(&self) -> f64 {
+    self.reference_price
+  }
+
+  fn target_price(&self) -> f64 {
+    self.target_price
+  }
+
+  fn target_percentage(&self) -> f64 {
+    self.target_percentage
+  }
+
+  fn status(&self) -> &str {
+    &self.status
+  }
+
+  fn parent_id(&self) -> Option<i32> {
+    self.parent_id
+  }
+
+  fn sell_fraction(&self) -> Option<f64> {
+    self.sell_fraction
+  }
+
+  fn created_by(&self) -> &str {
+    &self.created_by
+  }
+
+  fn triggered_at(&self) -> Option<&str> {
+    self.triggered_at.as_deref()
+  }
+
+  fn filled_at(&self) -> Option<&str> {
+    self.filled_at.as_deref()
+  }
+
+  fn failed_at(&self) -> Option<&str> {
+    self.failed_at.as_deref()
+  }
+
+  fn cancelled_at(&self) -> Option<&str> {
+    self.cancelled_at.as_deref()
+  }
+
+  fn expired_at(&self) -> Option<&str> {
+    self.expired_at.as_deref()
+  }
+
+  fn expires_at(&self) -> Option<&str> {
+    self.expires_at.as_deref()
+  }
+
+  fn failure_reason(&self) -> Option<&str> {
+    self.failure_reason.as_deref()
+  }
+
+  fn filled_transaction_id(&self) -> Option<i32> {
+    self.filled_transaction_id
+  }
+
+  fn created_at(&self) -> String {
+    self.created_at.to_string()
+  }
+
+  fn updated_at(&self) -> String {
+    self.updated_at.to_string()
+  }
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Input for a take profit order, target_multiple of 2 sells at 2x")]
+pub struct NewTakeProfitInput {
+  pub wallet_id: i32,
+  pub contract_address: String,
+  pub target_multiple: f64,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(
+  description = "Input for a trailing stop, trail_percentage of 25 sells 25% below the peak"
+)]
+pub struct NewTrailingStopInput {
+  pub wallet_id: i32,
+  pub contract_address: String,
+  pub trail_percentage: f64,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(
+  description = "Input for a ladder, rungs written as multiple:percentage pairs such as 2:25,3:25"
+)]
+pub struct NewLadderInput {
+  pub wallet_id: i32,
+  pub contract_address: String,
+  pub rungs: String,
+  pub trail_percentage: Option<f64>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(
+  description = "Input for a limit buy of sol_amount SOL once the price falls to target_price"
+)]
+pub struct NewLimitBuyInput {
+  pub wallet_id: i32,
+  pub contract_address: String,
+  pub target_price: f64,
+  pub sol_amount: f64,
+  pub stop_loss_percentage: Option<f64>,
+  pub expires_in_hours: Option<i32>,
+}
+
+pub struct TradeOrderQuery;
+
+#[graphql_object(context = Context)]
+impl TradeOrderQuery {
+  async fn orders(context: &Context, open_only: Option<bool>) -> Result<Vec<TradeOrder>, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+
+    let mut query = trade_orders::Entity::find()
+      .filter(trade_orders::Column::UserId.eq(order_user.id))
+      .order_by_desc(trade_orders::Column::CreatedAt);
+
+    if open_only.unwrap_or(true) {
+      query = query.filter(trade_orders::Column::Status.is_in(OPEN_ORDER_STATUSES));
+    }
+
+    let orders = query.all(&context.db).await.map_err(|e| e.to_string())?;
+
+    Ok(orders.into_iter().map(TradeOrder::from).collect())
+  }
+}
+
+// (wallet, token id, current token price) for an order the user wants to open
+pub(super) async fn resolve_order_target(
+  context: &Context,
+  user_id: i32,
+  wallet_id: i32,
+  contract_address: &str,
+) -> Result<(wallets::Model, i32, f64), String> {
+  let wallet = wallets::Entity::find_by_id(wallet_id)
+    .filter(wallets::Column::UserId.eq(user_id))
+    .one(&context.db)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or("Wallet not found")?;
+
+  let token = get_token_details(contract_address)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let token_id = find_or_create_token(&context.db, &token, contract_address)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let token_price = fetch_token_price(contract_address)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok((wallet, token_id, token_price))
+}
+
+async fn find_user_order(
+  context: &Context,
+  user_id: i32,
+  order_id: i32,
+) -> Result<trade_orders::Model, String> {
+  trade_orders::Entity::find_by_id(order_id)
+    .filter(trade_orders::Column::UserId.eq(user_id))
+    .one(&context.db)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "Order not found".to_string())
+}
+
+pub struct TradeOrderMutation;
+
+#[graphql_object(context = Context)]
+impl TradeOrderMutation {
+  async fn create_take_profit_order(
+    context: &Context,
+    input: NewTakeProfitInput,
+  ) -> Result<TradeOrder, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+
+    let (wallet, token_id, token_price) = resolve_order_target(
+      context,
+      order_user.id,
+      input.wallet_id,
+      &input.contract_address,
+    )
+    .await?;
+
+    let order = create_take_profit_order(
+      &context.db,
+      order_user.id,
+      wallet.id,
+      token_id,
+      &input.contract_address,
+      token_price,
+      input.target_multiple,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(order.into())
+  }
+
+  async fn create_trailing_stop_order(
+    context: &Context,
+    input: NewTrailingStopInput,
+  ) -> Result<TradeOrder, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+
+    let (wallet, token_id, token_price) = resolve_order_target(
+      context,
+      order_user.id,
+      input.wallet_id,
+      &input.contract_address,
+    )
+    .await?;
+
+    let order = create_trailing_stop_order(
+      &context.db,
+      order_user.id,
+      wallet.id,
+      token_id,
+      &input.contract_address,
+      token_price,
+      input.trail_percentage as f32,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(order.into())
+  }
+
+  async fn create_ladder_order(
+    context: &Context,
+    input: NewLadderInput,
+  ) -> Result<TradeOrder, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+
+    let rungs = parse_ladder_rungs(&input.rungs).map_err(|e| e.to_string())?;
+
+    let (wallet, token_id, token_price) = resolve_order_target(
+      context,
+      order_user.id,
+      input.wallet_id,
+      &input.contract_address,
+    )
+    .await?;
+
+    let balance = wallet_token_balance(&context.db, &wallet, &input.contract_address)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    let plan = LadderPlan {
+      reference_price: token_price,
+      position_amount: balance.ui_amount,
+      rungs,
+      trail_percentage: input
+        .trail_percentage
+        .filter(|percentage| *percentage > 0.0)
+        .map(|percentage| percentage as f32),
+    };
+
+    let order = create_ladder_order(
+      &context.db,
+      order_user.id,
+      wallet.id,
+      token_id,
+      &input.contract_address,
+      &plan,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(order.into())
+  }
+
+  async fn create_limit_buy_order(
+    context: &Context,
+    input: NewLimitBuyInput,
+  ) -> Result<TradeOrder, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+
+    let (wallet, token_id, token_price) = resolve_order_target(
+      context,
+      order_user.id,
+      input.wallet_id,
+      &input.contract_address,
+    )
+    .await?;
+
+    let order = create_limit_buy_order(
+      &context.db,
+      order_user.id,
+      wallet.id,
+      token_id,
+      &input.contract_address,
+      token_price,
+      input.target_price,
+      input.sol_amount,
+      input
+        .stop_loss_percentage
+        .map(|percentage| percentage as f32),
+      input
+        .expires_in_hours
+        .map(|hours| Utc::now().naive_utc() + Duration::hours(hours as i64)),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(order.into())
+  }
+
+  async fn cancel_order(context: &Context, id: i32) -> Result<TradeOrder, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+    let order = find_user_order(context, order_user.id, id).await?;
+
+    if !cancel_order(&context.db, &order, None)
+      .await
+      .map_err(|e| e.to_string())?
+    {
+      return Err("Order changed while cancelling, try again".to_string());
+    }
+
+    Ok(find_user_order(context, order_user.id, id).await?.into())
+  }
+
+  async fn reactivate_order(context: &Context, id: i32) -> Result<TradeOrder, String> {
+    let order_user = context.user.as_ref().ok_or("User not found")?;
+    let order = find_user_order(context, order_user.id, id).await?;
+
+    if !reactivate_order(&context.db, order.id)
+      .await
+      .map_err(|e| e.to_string())?
+    {
+      return Err(format!(
+        "Only failed orders can be reactivated, this one is {}",
+        order.status
+      ));
+    }
+
+    Ok(find_user_order(context, order_user.id, id).await?.into())
+  }
+}
@@ -62,6 +62,8 @@ impl User {
         chain: w.chain,
         address: w.address,
         user_id: w.user_id,
+        paper_trading: w.paper_trading,
+        watch_only: w.watch_only,
         created_at: w.created_at.to_string(),
         updated_at: w.updated_at.to_string(),
       })
@@ -1,12 +1,14 @@
 use crate::{
   gql::schemas::{root::Context, user::User},
-  utils::wallets::solana::{
-    generate_wallet, recover_wallet_from_private_key, register_wallet_tokens,
+  utils::wallets::{
+    solana::{generate_wallet, recover_wallet_from_private_key, register_wallet_tokens},
+    watch::{create_watch_only_wallet, delete_watch_only_wallet, find_wallet_holdings},
   },
 };
 use ::entity::{prelude::*, *};
+use chrono::Utc;
 use juniper::{graphql_object, GraphQLEnum, GraphQLInputObject};
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 
 /// Wallet
 #[derive(Default, Debug)]
@@ -16,10 +18,25 @@ pub struct Wallet {
   pub chain: String,
   pub address: String,
   pub user_id: i32,
+  // None follows the user's paper_trading setting
+  pub paper_trading: Option<bool>,
+  pub watch_only: bool,
   pub created_at: String,
   pub updated_at: String,
 }
 
+/// Token held by a watch-only wallet
+#[derive(Default, Debug)]
+pub struct WalletHolding {
+  pub id: i32,
+  pub wallet_id: i32,
+  pub token_id: Option<i32>,
+  pub contract_address: String,
+  pub balance: f64,
+  pub value_usd: Option<f64>,
+  pub updated_at: String,
+}
+
 #[derive(GraphQLEnum)]
 enum Chain {
   Solana,
@@ -52,6 +69,14 @@ impl Wallet {
     self.user_id
   }
 
+  fn paper_trading(&self) -> Option<bool> {
+    self.paper_trading
+  }
+
+  fn watch_only(&self) -> bool {
+    self.watch_only
+  }
+
   fn created_at(&self) -> String {
     self.created_at.to_string()
   }
@@ -75,6 +100,59 @@ impl Wallet {
       updated_at: u.updated_at.to_string(),
     }))
   }
+
+  /// Tokens the wallet held at the last holdings update, tracked for watch-only wallets
+  async fn holdings(&self, context: &Context) -> Result<Vec<WalletHolding>, String> {
+    let holdings = find_wallet_holdings(&context.db, self.id)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(
+      holdings
+        .into_iter()
+        .map(|h| WalletHolding {
+          id: h.id,
+          wallet_id: h.wallet_id,
+          token_id: h.token_id,
+          contract_address: h.contract_address,
+          balance: h.balance,
+          value_usd: h.value_usd.map(|value_usd| value_usd as f64),
+          updated_at: h.updated_at.to_string(),
+        })
+        .collect(),
+    )
+  }
+}
+
+#[graphql_object(context = Context)]
+impl WalletHolding {
+  fn id(&self) -> i32 {
+    self.id
+  }
+
+  fn wallet_id(&self) -> i32 {
+    self.wallet_id
+  }
+
+  fn token_id(&self) -> Option<i32> {
+    self.token_id
+  }
+
+  fn contract_address(&self) -> &str {
+    &self.contract_address
+  }
+
+  fn balance(&self) -> f64 {
+    self.balance
+  }
+
+  fn value_usd(&self) -> Option<f64> {
+    self.value_usd
+  }
+
+  fn updated_at(&self) -> String {
+    self.updated_at.to_string()
+  }
 }
 
 #[derive(GraphQLInputObject)]
@@ -82,6 +160,7 @@ pub struct NewWalletInput {
   pub title: String,
   pub chain: String,
   pub address: Option<String>,
+  pub paper_trading: Option<bool>,
 }
 
 pub struct WalletQuery;
@@ -100,6 +179,8 @@ impl WalletQuery {
       chain: w.chain,
       address: w.address,
       user_id: w.user_id,
+      paper_trading: w.paper_trading,
+      watch_only: w.watch_only,
       created_at: w.created_at.to_string(),
       updated_at: w.updated_at.to_string(),
     }))
@@ -124,6 +205,8 @@ impl WalletQuery {
           chain: w.chain,
           address: w.address,
           user_id: w.user_id,
+          paper_trading: w.paper_trading,
+          watch_only: w.watch_only,
           created_at: w.created_at.to_string(),
           updated_at: w.updated_at.to_string(),
         })
@@ -145,6 +228,7 @@ impl WalletMutation {
       chain: Set(input.chain.clone()),
       user_id: Set(wallet_user.id),
       encryption_schema: Set("default".to_string()),
+      paper_trading: Set(input.paper_trading),
       ..Default::default()
     };
 
@@ -155,6 +239,7 @@ impl WalletMutation {
 
         let existing_wallet = wallets::Entity::find()
           .filter(wallets::Column::Chain.eq(input.chain.clone()))
+          .filter(wallets::Column::UserId.eq(wallet_user.id))
           .filter(wallets::Column::Address.eq(signer.public_key.clone()))
           .one(&context.db)
           .await
@@ -165,21 +250,16 @@ impl WalletMutation {
         }
 
         wallet.address = Set(signer.public_key);
-        if let Some(salt) = signer.salt {
-          wallet.salt = Set(salt);
-        }
-        if let Some(secret_key) = signer.secret_key {
-          wallet.secret_key = Set(secret_key);
-        }
-        if let Some(encrypted_private_key) = signer.encrypted_private_key {
-          wallet.encrypted_private_key = Set(encrypted_private_key);
-        }
+        wallet.salt = Set(signer.salt);
+        wallet.secret_key = Set(signer.secret_key);
+        wallet.encrypted_private_key = Set(signer.encrypted_private_key);
       }
       None => {
         let signer = generate_wallet();
 
         let existing_wallet = wallets::Entity::find()
           .filter(wallets::Column::Chain.eq(input.chain.clone()))
+          .filter(wallets::Column::UserId.eq(wallet_user.id))
           .filter(wallets::Column::Address.eq(signer.public_key.clone()))
           .one(&context.db)
           .await
@@ -191,15 +271,9 @@ impl WalletMutation {
 
         // Set the wallet fields
         wallet.address = Set(signer.public_key);
-        if let Some(salt) = signer.salt {
-          wallet.salt = Set(salt);
-        }
-        if let Some(secret_key) = signer.secret_key {
-          wallet.secret_key = Set(secret_key);
-        }
-        if let Some(encrypted_private_key) = signer.encrypted_private_key {
-          wallet.encrypted_private_key = Set(encrypted_private_key);
-        }
+        wallet.salt = Set(signer.salt);
+        wallet.secret_key = Set(signer.secret_key);
+        wallet.encrypted_private_key = Set(signer.encrypted_private_key);
       }
     }
 
@@ -229,8 +303,83 @@ impl WalletMutation {
       chain: record.chain,
       address: record.address,
       user_id: record.user_id,
+      paper_trading: record.paper_trading,
+      watch_only: record.watch_only,
       created_at: record.created_at.to_string(),
       updated_at: record.updated_at.to_string(),
     })
   }
+  /// Simulate swaps from a wallet, or not, whatever the user setting says. Pass null to follow
+  /// the user setting again.
+  async fn set_wallet_paper_trading(
+    context: &Context,
+    id: i32,
+    paper_trading: Option<bool>,
+  ) -> Result<Wallet, String> {
+    let wallet_user = context.user.as_ref().ok_or("User not found")?;
+
+    let wallet = wallets::Entity::find_by_id(id)
+      .filter(wallets::Column::UserId.eq(wallet_user.id))
+      .one(&context.db)
+      .await
+      .map_err(|e| e.to_string())?
+      .ok_or("Wallet not found")?;
+
+    let mut active_wallet: wallets::ActiveModel = wallet.into();
+    active_wallet.paper_trading = Set(paper_trading);
+    active_wallet.updated_at = Set(Utc::now().naive_utc());
+
+    let record = active_wallet
+      .update(&context.db)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(Wallet {
+      id: record.id,
+      title: record.title,
+      chain: record.chain,
+      address: record.address,
+      user_id: record.user_id,
+      paper_trading: record.paper_trading,
+      watch_only: record.watch_only,
+      created_at: record.created_at.to_string(),
+      updated_at: record.updated_at.to_string(),
+    })
+  }
+
+  /// Watch an address without its keys. Its holdings are tracked and its activity is sent to
+  /// the user on Telegram.
+  async fn watch_wallet(
+    context: &Context,
+    title: String,
+    address: String,
+  ) -> Result<Wallet, String> {
+    let wallet_user = context.user.as_ref().ok_or("User not found")?;
+
+    let record = create_watch_only_wallet(&context.db, wallet_user.id, &title, &address)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(Wallet {
+      id: record.id,
+      title: record.title,
+      chain: record.chain,
+      address: record.address,
+      user_id: record.user_id,
+      paper_trading: record.paper_trading,
+      watch_only: record.watch_only,
+      created_at: record.created_at.to_string(),
+      updated_at: record.updated_at.to_string(),
+    })
+  }
+
+  async fn remove_watch_only_wallet(context: &Context, id: i32) -> Result<bool, String> {
+    let wallet_user = context.user.as_ref().ok_or("User not found")?;
+
+    delete_watch_only_wallet(&context.db, wallet_user.id, id)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    Ok(true)
+  }
 }
@@ -0,0 +1,121 @@
+use std::{env, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use base64::encode;
+use bincode::serialize;
+use rand::seq::SliceRandom;
+use reqwest::Client;
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+
+// Bundles sent to a Jito block engine land all at once, in order, or not at all. A bundle pays
+// the validator through a SOL transfer to one of the tip accounts, which only lands with it.
+
+const TIP_ACCOUNTS: [&str; 8] = [
+  "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+  "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+  "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+  "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+  "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+  "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+  "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+  "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+// the block engine drops bundles tipping less than this
+pub const MIN_TIP_LAMPORTS: u64 = 1_000;
+
+// a bundle holds at most this many transactions, the tip included
+pub const MAX_BUNDLE_TXS: usize = 5;
+
+pub struct JitoClient {
+  client: Client,
+}
+
+impl JitoClient {
+  pub fn new() -> Self {
+    JitoClient {
+      client: Client::new(),
+    }
+  }
+
+  fn url_base() -> String {
+    env::var("JITO_BLOCK_ENGINE_URL")
+      .unwrap_or_else(|_| "https://mainnet.block-engine.jito.wtf".to_string())
+  }
+
+  /// A tip account picked at random, spreading tips over them as Jito asks
+  pub fn tip_account() -> Result<Pubkey> {
+    let account = TIP_ACCOUNTS
+      .choose(&mut rand::thread_rng())
+      .ok_or_else(|| anyhow!("No Jito tip account"))?;
+
+    Pubkey::from_str(account).map_err(|e| anyhow!("Invalid Jito tip account: {}", e))
+  }
+
+  async fn call(&self, method: &str, params: Value) -> Result<Value> {
+    let url = format!("{}/api/v1/bundles", Self::url_base());
+
+    let request_body = json!({
+      "jsonrpc": "2.0",
+      "id": 1,
+      "method": method,
+      "params": params,
+    });
+
+    let response = self
+      .client
+      .post(&url)
+      .json(&request_body)
+      .send()
+      .await
+      .context("Failed to send a request")?;
+
+    if !response.status().is_success() {
+      let status = response.status();
+      let body = response.text().await.unwrap_or_default();
+      return Err(anyhow!(
+        "Block engine request failed with status: {} {}",
+        status,
+        body
+      ));
+    }
+
+    let json: Value = response
+      .json()
+      .await
+      .context("Failed to parse block engine response as JSON")?;
+
+    if let Some(error) = json.get("error") {
+      return Err(anyhow!("Block engine {} failed: {}", method, error));
+    }
+
+    Ok(json["result"].clone())
+  }
+
+  /// Send signed transactions as one bundle, returning its id
+  pub async fn send_bundle(&self, txs: &[VersionedTransaction]) -> Result<String> {
+    if txs.is_empty() || txs.len() > MAX_BUNDLE_TXS {
+      return Err(anyhow!(
+        "A bundle holds 1 to {} transactions, not {}",
+        MAX_BUNDLE_TXS,
+        txs.len()
+      ));
+    }
+
+    let encoded_txs = txs
+      .iter()
+      .map(|tx| serialize(tx).map(encode))
+      .collect::<Result<Vec<String>, _>>()
+      .context("Failed to serialize bundle transaction")?;
+
+    let result = self
+      .call("sendBundle", json!([encoded_txs, { "encoding": "base64" }]))
+      .await?;
+
+    result
+      .as_str()
+      .map(|bundle_id| bundle_id.to_string())
+      .ok_or_else(|| anyhow!("Unexpected sendBundle result: {}", result))
+  }
+}
@@ -0,0 +1,182 @@
+use std::env;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use base64::decode;
+use bincode::deserialize;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::transaction::VersionedTransaction;
+
+use crate::utils::swap::provider::{parse_quote_amount, SwapProvider, SwapQuote, SwapRoute};
+
+pub struct JupiterSwapClient {
+  client: Client,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JupiterQuoteResponse {
+  #[serde(rename = "inputMint")]
+  pub input_mint: String,
+  #[serde(rename = "inAmount")]
+  pub in_amount: String,
+  #[serde(rename = "outputMint")]
+  pub output_mint: String,
+  #[serde(rename = "outAmount")]
+  pub out_amount: String,
+  #[serde(rename = "otherAmountThreshold")]
+  pub other_amount_threshold: String,
+  #[serde(rename = "slippageBps")]
+  pub slippage_bps: u32,
+  // a fraction, 0.01 is 1%
+  #[serde(rename = "priceImpactPct")]
+  pub price_impact_pct: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JupiterSwapRequest {
+  quote_response: Value,
+  user_public_key: String,
+  wrap_and_unwrap_sol: bool,
+  dynamic_compute_unit_limit: bool,
+  prioritization_fee_lamports: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterSwapResponse {
+  swap_transaction: String,
+}
+
+impl JupiterSwapClient {
+  pub fn new() -> Self {
+    JupiterSwapClient {
+      client: Client::new(),
+    }
+  }
+
+  fn url_base() -> String {
+    env::var("JUPITER_SWAP_URL").unwrap_or_else(|_| "https://quote-api.jup.ag/v6".to_string())
+  }
+
+  pub async fn get_swap_quote(
+    &self,
+    input_mint: &str,
+    output_mint: &str,
+    amount: &str,
+    slippage: &str,
+  ) -> Result<Value> {
+    let url = format!(
+      "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
+      Self::url_base(),
+      input_mint,
+      output_mint,
+      amount,
+      slippage
+    );
+
+    let response = self
+      .client
+      .get(&url)
+      .send()
+      .await
+      .context("Failed to send a request")?;
+
+    if !response.status().is_success() {
+      let status = response.status();
+      let body = response.text().await.unwrap_or_default();
+      return Err(anyhow!(
+        "API request failed with status: {} {}",
+        status,
+        body
+      ));
+    }
+
+    let json: Value = response
+      .json()
+      .await
+      .context("Failed to parse swap quote response as JSON")?;
+
+    Ok(json)
+  }
+
+  pub async fn get_swap_tx(
+    &self,
+    taker_address: &str,
+    swap_quote: Value,
+  ) -> Result<VersionedTransaction> {
+    let url = format!("{}/swap", Self::url_base());
+
+    let request_body = JupiterSwapRequest {
+      quote_response: swap_quote,
+      user_public_key: taker_address.to_string(),
+      wrap_and_unwrap_sol: true,
+      dynamic_compute_unit_limit: true,
+      prioritization_fee_lamports: "auto".to_string(),
+    };
+
+    let response = self.client.post(url).json(&request_body).send().await?;
+
+    // Ensure the response is successful
+    response.error_for_status_ref()?;
+
+    let swap_response: JupiterSwapResponse = response.json().await?;
+    let tx_buf =
+      decode(swap_response.swap_transaction).context("Failed to decode base64 transaction data")?;
+
+    deserialize(&tx_buf).context("Failed to deserialize VersionedTransaction")
+  }
+}
+
+#[async_trait]
+impl SwapProvider for JupiterSwapClient {
+  async fn quote_swap(
+    &self,
+    input_mint: &str,
+    output_mint: &str,
+    amount: &str,
+    slippage_bps: u32,
+  ) -> Result<SwapQuote> {
+    let quote = self
+      .get_swap_quote(input_mint, output_mint, amount, &slippage_bps.to_string())
+      .await?;
+
+    if let Some(error) = quote["error"].as_str() {
+      return Err(anyhow!("Jupiter could not quote the swap: {}", error));
+    }
+
+    let parsed: JupiterQuoteResponse =
+      serde_json::from_value(quote.clone()).map_err(|e| anyhow!("Unexpected swap quote: {}", e))?;
+
+    let price_impact = parsed.price_impact_pct.parse::<f64>().map_err(|_| {
+      anyhow!(
+        "Quote price impact {} is not a number",
+        parsed.price_impact_pct
+      )
+    })?;
+
+    Ok(SwapQuote {
+      route: SwapRoute::Jupiter,
+      input_amount: parse_quote_amount(&parsed.in_amount, "input")?,
+      output_amount: parse_quote_amount(&parsed.out_amount, "output")?,
+      minimum_output_amount: parse_quote_amount(&parsed.other_amount_threshold, "minimum output")?,
+      input_mint: parsed.input_mint,
+      output_mint: parsed.output_mint,
+      slippage_bps: parsed.slippage_bps,
+      price_impact_pct: price_impact * 100.0,
+      raw: quote,
+    })
+  }
+
+  async fn build_swap_txs(
+    &self,
+    taker_address: &str,
+    quote: &SwapQuote,
+  ) -> Result<Vec<VersionedTransaction>> {
+    let tx = self.get_swap_tx(taker_address, quote.raw.clone()).await?;
+
+    Ok(vec![tx])
+  }
+}
@@ -1,2 +1,5 @@
 pub mod dexscreener;
+pub mod jito;
+pub mod jupiter;
+pub mod pump_fun;
 pub mod raydium;
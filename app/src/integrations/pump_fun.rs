@@ -0,0 +1,397 @@
+use std::{env, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncClient;
+use solana_sdk::{
+  commitment_config::CommitmentConfig,
+  compute_budget::ComputeBudgetInstruction,
+  instruction::{AccountMeta, Instruction},
+  message::Message,
+  pubkey::Pubkey,
+  system_program, sysvar,
+  transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{
+  integrations::raydium::RaydiumPriceFetcher,
+  utils::swap::provider::{SwapProvider, SwapQuote, SwapRoute},
+};
+
+// Tokens launched on pump.fun trade against a bonding curve until it completes and their
+// liquidity migrates to Raydium. The curve is a constant product over virtual reserves, so
+// prices and swap amounts come straight from its account.
+
+pub const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+const PUMP_FUN_GLOBAL: &str = "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf";
+const PUMP_FUN_FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+const PUMP_FUN_EVENT_AUTHORITY: &str = "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+// pump.fun mints all have 6 decimals
+pub const PUMP_FUN_TOKEN_DECIMALS: i32 = 6;
+
+// taken by the program from the SOL side of every trade
+const PUMP_FUN_FEE_BPS: u128 = 100;
+
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+const COMPUTE_UNIT_LIMIT: u32 = 120_000;
+const DEFAULT_COMPUTE_UNIT_PRICE: u64 = 100_000;
+
+/// State of a pump.fun bonding curve account
+#[derive(Debug, Clone, Copy)]
+pub struct BondingCurve {
+  pub virtual_token_reserves: u64,
+  pub virtual_sol_reserves: u64,
+  pub real_token_reserves: u64,
+  pub real_sol_reserves: u64,
+  pub token_total_supply: u64,
+  // set once the curve sold out and the token moved to Raydium
+  pub complete: bool,
+}
+
+impl BondingCurve {
+  fn from_account_data(data: &[u8]) -> Result<BondingCurve> {
+    // an 8 byte discriminator, five u64 and a bool
+    if data.len() < 49 {
+      return Err(anyhow!("Bonding curve account is too short"));
+    }
+
+    let read_u64 = |offset: usize| {
+      let mut bytes = [0u8; 8];
+      bytes.copy_from_slice(&data[offset..offset + 8]);
+      u64::from_le_bytes(bytes)
+    };
+
+    Ok(BondingCurve {
+      virtual_token_reserves: read_u64(8),
+      virtual_sol_reserves: read_u64(16),
+      real_token_reserves: read_u64(24),
+      real_sol_reserves: read_u64(32),
+      token_total_supply: read_u64(40),
+      complete: data[48] != 0,
+    })
+  }
+
+  /// Price of a whole token in SOL
+  pub fn price_in_sol(&self) -> f64 {
+    if self.virtual_token_reserves == 0 {
+      return 0.0;
+    }
+
+    (self.virtual_sol_reserves as f64 / 1_000_000_000.0)
+      / (self.virtual_token_reserves as f64 / 10f64.powi(PUMP_FUN_TOKEN_DECIMALS))
+  }
+
+  /// SOL deposited into the curve by buys so far
+  pub fn sol_liquidity(&self) -> f64 {
+    self.real_sol_reserves as f64 / 1_000_000_000.0
+  }
+
+  /// Tokens a buy spending `lamports`, fee included, gets
+  pub fn buy_quote(&self, lamports: u64) -> u64 {
+    let sol_in = lamports as u128 * 10_000 / (10_000 + PUMP_FUN_FEE_BPS);
+    let virtual_sol = self.virtual_sol_reserves as u128;
+    let virtual_tokens = self.virtual_token_reserves as u128;
+
+    let tokens_out = virtual_tokens * sol_in / (virtual_sol + sol_in);
+
+    (tokens_out as u64).min(self.real_token_reserves)
+  }
+
+  /// Lamports a sell of `token_amount` gets, fee taken
+  pub fn sell_quote(&self, token_amount: u64) -> u64 {
+    let sol_out = self.sell_quote_before_fee(token_amount);
+
+    (sol_out - sol_out * PUMP_FUN_FEE_BPS / 10_000) as u64
+  }
+
+  fn sell_quote_before_fee(&self, token_amount: u64) -> u128 {
+    let virtual_sol = self.virtual_sol_reserves as u128;
+    let virtual_tokens = self.virtual_token_reserves as u128;
+    let token_amount = token_amount as u128;
+
+    (virtual_sol * token_amount / (virtual_tokens + token_amount))
+      .min(self.real_sol_reserves as u128)
+  }
+}
+
+fn pubkey(address: &str) -> Result<Pubkey> {
+  Pubkey::from_str(address).map_err(|e| anyhow!("Invalid Solana address {}: {}", address, e))
+}
+
+fn bonding_curve_address(mint: &Pubkey) -> Result<Pubkey> {
+  let program_id = pubkey(PUMP_FUN_PROGRAM_ID)?;
+
+  Ok(Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &program_id).0)
+}
+
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey> {
+  let program_id = pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?;
+
+  Ok(
+    Pubkey::find_program_address(
+      &[owner.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+      &program_id,
+    )
+    .0,
+  )
+}
+
+/// The bonding curve of a mint, None when the mint wasn't launched on pump.fun
+pub async fn fetch_bonding_curve(mint: &str) -> Result<Option<BondingCurve>> {
+  let rpc_url =
+    env::var("SOLANA_RPC_URL").context("Failed to retrieve SOLANA_RPC_URL from environment")?;
+  let client = AsyncClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+
+  let curve_address = bonding_curve_address(&pubkey(mint)?)?;
+
+  let account = client
+    .get_account_with_commitment(&curve_address, CommitmentConfig::confirmed())
+    .await
+    .map_err(|e| anyhow!("Failed to fetch bonding curve of {}: {}", mint, e))?
+    .value;
+
+  match account {
+    Some(account) if account.owner == pubkey(PUMP_FUN_PROGRAM_ID)? => {
+      Ok(Some(BondingCurve::from_account_data(&account.data)?))
+    }
+    _ => Ok(None),
+  }
+}
+
+pub struct PumpFunSwapClient;
+
+impl PumpFunSwapClient {
+  pub fn new() -> Self {
+    PumpFunSwapClient
+  }
+
+  async fn active_bonding_curve(&self, mint: &str) -> Result<BondingCurve> {
+    let curve = fetch_bonding_curve(mint)
+      .await?
+      .ok_or_else(|| anyhow!("{} is not a pump.fun token", mint))?;
+
+    if curve.complete {
+      return Err(anyhow!(
+        "The bonding curve of {} is complete, it trades on Raydium",
+        mint
+      ));
+    }
+
+    Ok(curve)
+  }
+
+  async fn compute_budget_instructions(&self) -> Vec<Instruction> {
+    let compute_unit_price = RaydiumPriceFetcher::new()
+      .get_priority_fee()
+      .await
+      .ok()
+      .and_then(|gas| {
+        gas
+          .to_hashmap()
+          .get("h")
+          .and_then(|h| h.parse::<u64>().ok())
+      })
+      .unwrap_or(DEFAULT_COMPUTE_UNIT_PRICE);
+
+    vec![
+      ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+      ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+    ]
+  }
+}
+
+fn trade_instruction(
+  user: &Pubkey,
+  mint: &Pubkey,
+  amount: u64,
+  sol_limit: u64,
+  is_buy: bool,
+) -> Result<Instruction> {
+  let program_id = pubkey(PUMP_FUN_PROGRAM_ID)?;
+  let bonding_curve = bonding_curve_address(mint)?;
+
+  let mut accounts = vec![
+    AccountMeta::new_readonly(pubkey(PUMP_FUN_GLOBAL)?, false),
+    AccountMeta::new(pubkey(PUMP_FUN_FEE_RECIPIENT)?, false),
+    AccountMeta::new_readonly(*mint, false),
+    AccountMeta::new(bonding_curve, false),
+    AccountMeta::new(associated_token_address(&bonding_curve, mint)?, false),
+    AccountMeta::new(associated_token_address(user, mint)?, false),
+    AccountMeta::new(*user, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+  ];
+
+  // the two instructions order their program accounts differently
+  if is_buy {
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+  } else {
+    accounts.push(AccountMeta::new_readonly(
+      pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+      false,
+    ));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+  }
+
+  accounts.push(AccountMeta::new_readonly(
+    pubkey(PUMP_FUN_EVENT_AUTHORITY)?,
+    false,
+  ));
+  accounts.push(AccountMeta::new_readonly(program_id, false));
+
+  let mut data = if is_buy {
+    BUY_DISCRIMINATOR.to_vec()
+  } else {
+    SELL_DISCRIMINATOR.to_vec()
+  };
+  data.extend_from_slice(&amount.to_le_bytes());
+  data.extend_from_slice(&sol_limit.to_le_bytes());
+
+  Ok(Instruction {
+    program_id,
+    accounts,
+    data,
+  })
+}
+
+// creates the user's token account for the mint unless it already exists
+fn create_associated_token_account_instruction(
+  user: &Pubkey,
+  mint: &Pubkey,
+) -> Result<Instruction> {
+  Ok(Instruction {
+    program_id: pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+    accounts: vec![
+      AccountMeta::new(*user, true),
+      AccountMeta::new(associated_token_address(user, mint)?, false),
+      AccountMeta::new_readonly(*user, false),
+      AccountMeta::new_readonly(*mint, false),
+      AccountMeta::new_readonly(system_program::id(), false),
+      AccountMeta::new_readonly(spl_token::id(), false),
+    ],
+    // CreateIdempotent
+    data: vec![1],
+  })
+}
+
+fn slippage_floor(amount: u64, slippage_bps: u32) -> u64 {
+  let slippage_bps = (slippage_bps as u128).min(10_000);
+
+  (amount as u128 * (10_000 - slippage_bps) / 10_000) as u64
+}
+
+#[async_trait]
+impl SwapProvider for PumpFunSwapClient {
+  async fn quote_swap(
+    &self,
+    input_mint: &str,
+    output_mint: &str,
+    amount: &str,
+    slippage_bps: u32,
+  ) -> Result<SwapQuote> {
+    let input_amount = amount
+      .parse::<u64>()
+      .map_err(|_| anyhow!("Swap amount {} is not a number", amount))?;
+
+    let (mint, is_buy) = match (input_mint, output_mint) {
+      (SOL_MINT, mint) => (mint, true),
+      (mint, SOL_MINT) => (mint, false),
+      _ => return Err(anyhow!("pump.fun only swaps tokens against SOL")),
+    };
+
+    let curve = self.active_bonding_curve(mint).await?;
+
+    // what the swap would get at the current price, to measure its price impact against
+    let (output_amount, spot_output) = if is_buy {
+      let spot_output = input_amount as f64 * curve.virtual_token_reserves as f64
+        / curve.virtual_sol_reserves as f64;
+      (
+        curve.buy_quote(input_amount),
+        spot_output * 10_000.0 / (10_000 + PUMP_FUN_FEE_BPS) as f64,
+      )
+    } else {
+      let spot_output = input_amount as f64 * curve.virtual_sol_reserves as f64
+        / curve.virtual_token_reserves as f64;
+      (
+        curve.sell_quote(input_amount),
+        spot_output * (10_000 - PUMP_FUN_FEE_BPS) as f64 / 10_000.0,
+      )
+    };
+
+    if output_amount == 0 {
+      return Err(anyhow!(
+        "The bonding curve of {} has no output for this swap",
+        mint
+      ));
+    }
+
+    let price_impact_pct = if spot_output > 0.0 {
+      ((spot_output - output_amount as f64) / spot_output * 100.0).max(0.0)
+    } else {
+      0.0
+    };
+
+    Ok(SwapQuote {
+      route: SwapRoute::PumpFun,
+      input_mint: input_mint.to_string(),
+      output_mint: output_mint.to_string(),
+      input_amount,
+      output_amount,
+      minimum_output_amount: slippage_floor(output_amount, slippage_bps),
+      slippage_bps,
+      price_impact_pct,
+      raw: json!({
+        "virtualTokenReserves": curve.virtual_token_reserves,
+        "virtualSolReserves": curve.virtual_sol_reserves,
+        "realTokenReserves": curve.real_token_reserves,
+        "realSolReserves": curve.real_sol_reserves,
+      }),
+    })
+  }
+
+  async fn build_swap_txs(
+    &self,
+    taker_address: &str,
+    quote: &SwapQuote,
+  ) -> Result<Vec<VersionedTransaction>> {
+    let user = pubkey(taker_address)?;
+    let mut instructions = self.compute_budget_instructions().await;
+
+    if quote.input_mint == SOL_MINT {
+      let mint = pubkey(&quote.output_mint)?;
+
+      // buys take the slippage as fewer tokens for at most the quoted SOL
+      instructions.push(create_associated_token_account_instruction(&user, &mint)?);
+      instructions.push(trade_instruction(
+        &user,
+        &mint,
+        quote.minimum_output_amount,
+        quote.input_amount,
+        true,
+      )?);
+    } else {
+      let mint = pubkey(&quote.input_mint)?;
+
+      instructions.push(trade_instruction(
+        &user,
+        &mint,
+        quote.input_amount,
+        quote.minimum_output_amount,
+        false,
+      )?);
+    }
+
+    let message = Message::new(&instructions, Some(&user));
+
+    Ok(vec![VersionedTransaction::from(Transaction::new_unsigned(
+      message,
+    ))])
+  }
+}
@@ -3,15 +3,17 @@
 use base64::decode;
 use std::{collections::HashMap, env};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
 use bincode::deserialize;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use solana_sdk::transaction::VersionedTransaction;
 
-use crate::utils::cache::{
-  self, get_memcache_string_hash, set_memcache_hashmap, set_memcache_string_hashmap,
+use crate::utils::{
+  cache::{self, get_memcache_string_hash, set_memcache_hashmap, set_memcache_string_hashmap},
+  swap::provider::{parse_quote_amount, SwapProvider, SwapQuote, SwapRoute},
 };
 
 pub struct RaydiumPriceFetcher {
@@ -86,9 +88,10 @@ pub struct RoutePlan {
   pub fee_rate: u32,
   #[serde(rename = "feeAmount")]
   pub fee_amount: String,
-  #[serde(rename = "remainingAccounts")]
+  // not every pool type sends these
+  #[serde(rename = "remainingAccounts", default)]
   pub remaining_accounts: Vec<String>,
-  #[serde(rename = "lastPoolPriceX64")]
+  #[serde(rename = "lastPoolPriceX64", default)]
   pub last_pool_price_x64: String,
 }
 
@@ -262,7 +265,7 @@ impl RaydiumPriceFetcher {
     let request_body = SwapRequest {
       computeUnitPriceMicroLamports: gas.h,
       swapResponse: swap_quote,
-      txVersion: "LEGACY".to_string(),
+      txVersion: "V0".to_string(),
       wallet: taker_address.to_string(),
       wrapSol: wrap_sol,
       unwrapSol: unwrap_sol,
@@ -339,3 +342,59 @@ impl RaydiumPriceFetcher {
     Ok(*price_usd)
   }
 }
+
+#[async_trait]
+impl SwapProvider for RaydiumPriceFetcher {
+  async fn quote_swap(
+    &self,
+    input_mint: &str,
+    output_mint: &str,
+    amount: &str,
+    slippage_bps: u32,
+  ) -> Result<SwapQuote> {
+    let quote = self
+      .get_swap_quote(input_mint, output_mint, amount, &slippage_bps.to_string())
+      .await?;
+
+    if quote["success"].as_bool() == Some(false) {
+      return Err(anyhow!(
+        "Raydium could not quote the swap: {}",
+        quote["msg"].as_str().unwrap_or("no reason given")
+      ));
+    }
+
+    let parsed: RaydiumSwapResponse =
+      serde_json::from_value(quote.clone()).map_err(|e| anyhow!("Unexpected swap quote: {}", e))?;
+
+    Ok(SwapQuote {
+      route: SwapRoute::Raydium,
+      input_amount: parse_quote_amount(&parsed.data.input_amount, "input")?,
+      output_amount: parse_quote_amount(&parsed.data.output_amount, "output")?,
+      minimum_output_amount: parse_quote_amount(
+        &parsed.data.other_amount_threshold,
+        "minimum output",
+      )?,
+      input_mint: parsed.data.input_mint,
+      output_mint: parsed.data.output_mint,
+      slippage_bps: parsed.data.slippage_bps,
+      price_impact_pct: parsed.data.price_impact_pct,
+      raw: quote,
+    })
+  }
+
+  async fn build_swap_txs(
+    &self,
+    taker_address: &str,
+    quote: &SwapQuote,
+  ) -> Result<Vec<VersionedTransaction>> {
+    self
+      .get_swap_tx(
+        taker_address,
+        quote.raw.clone(),
+        &quote.input_mint,
+        &quote.output_mint,
+        taker_address,
+      )
+      .await
+  }
+}
@@ -0,0 +1,13 @@
+use crate::{db, utils::swap::confirmation::track_submitted_transactions};
+
+pub async fn run_transaction_confirmations() -> Result<(), Box<dyn std::error::Error>> {
+  let db = db::connect_db().await?;
+
+  track_submitted_transactions(&db)
+    .await
+    .map_err(|e| format!("Confirmation error: {:?}", e))?;
+
+  let _ = db.close().await;
+
+  Ok(())
+}
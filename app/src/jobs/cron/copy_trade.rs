@@ -0,0 +1,13 @@
+use crate::{db, utils::copy_trade::run_enabled_wallet_follows};
+
+pub async fn run_copy_trades() -> Result<(), Box<dyn std::error::Error>> {
+  let db = db::connect_db().await?;
+
+  run_enabled_wallet_follows(&db)
+    .await
+    .map_err(|e| format!("Copy trading error: {:?}", e))?;
+
+  let _ = db.close().await;
+
+  Ok(())
+}
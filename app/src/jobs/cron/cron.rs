@@ -1,9 +1,12 @@
 //  https://github.com/patrick-fitzgerald/actix-web-cron-example/blob/main/src/main.rs
 
 use crate::jobs::cron::{
+  confirmations::run_transaction_confirmations,
+  copy_trade::run_copy_trades,
+  dca::run_dca_plans,
   price::{refresh_sol_token_prices, track_launch_event_token_prices},
   trade_strat::default_stop_loss_strategy_solana,
-  wallets::update_wallet_token_list,
+  wallets::{run_watch_only_alerts, update_wallet_token_list},
 };
 
 use chrono::{Local, Utc};
@@ -53,10 +56,42 @@ pub async fn start_cron() {
     }
   });
 
+  let run_dca = every(1).minutes().in_timezone(&Utc).perform(|| async {
+    println!(" running dca plans - {:?}", Local::now());
+    if let Err(err) = run_dca_plans().await {
+      eprintln!("Failed to run dca plans: {:?}", err);
+    }
+  });
+
+  let run_copy_trading = every(15).seconds().in_timezone(&Utc).perform(|| async {
+    println!(" running copy trading - {:?}", Local::now());
+    if let Err(err) = run_copy_trades().await {
+      eprintln!("Failed to copy trades: {:?}", err);
+    }
+  });
+
+  let run_watch_only_wallet_alerts = every(30).seconds().in_timezone(&Utc).perform(|| async {
+    println!(" running watch-only wallet alerts - {:?}", Local::now());
+    if let Err(err) = run_watch_only_alerts().await {
+      eprintln!("Failed to alert watch-only wallet activity: {:?}", err);
+    }
+  });
+
+  let run_confirmations = every(5).seconds().in_timezone(&Utc).perform(|| async {
+    println!(" running transaction confirmations - {:?}", Local::now());
+    if let Err(err) = run_transaction_confirmations().await {
+      eprintln!("Failed to confirm transactions: {:?}", err);
+    }
+  });
+
   tokio::spawn(every_second);
   tokio::spawn(refresh_sol_tokens_to_watch);
   tokio::spawn(sol_price_update);
   tokio::spawn(update_spl_tokens_in_wallet);
   tokio::spawn(run_default_stop_loss);
   tokio::spawn(run_track_spied_launch);
+  tokio::spawn(run_dca);
+  tokio::spawn(run_copy_trading);
+  tokio::spawn(run_watch_only_wallet_alerts);
+  tokio::spawn(run_confirmations);
 }
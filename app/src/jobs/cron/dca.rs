@@ -0,0 +1,13 @@
+use crate::{db, utils::dca::run_due_dca_plans};
+
+pub async fn run_dca_plans() -> Result<(), Box<dyn std::error::Error>> {
+  let db = db::connect_db().await?;
+
+  run_due_dca_plans(&db)
+    .await
+    .map_err(|e| format!("DCA error: {:?}", e))?;
+
+  let _ = db.close().await;
+
+  Ok(())
+}
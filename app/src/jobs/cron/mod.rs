@@ -1,4 +1,7 @@
+pub mod confirmations;
+pub mod copy_trade;
 pub mod cron;
+pub mod dca;
 pub mod price;
 pub mod trade_strat;
 pub mod wallets;
@@ -1,7 +1,13 @@
 use crate::{
   db,
   integrations::{dexscreener, raydium::RaydiumPriceFetcher},
-  utils::{cache, event::price::handle_price_update},
+  utils::{
+    cache,
+    event::price::handle_price_update,
+    orders::{
+      engine::evaluate_orders, strategy::PriceSnapshot, trailing_stop::ratchet_trailing_stops,
+    },
+  },
 };
 use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use entity::{raydium_token_launches, token_prices as prices, tokens};
@@ -37,7 +43,7 @@ pub async fn refresh_sol_token_prices() -> Result<(), Box<dyn std::error::Error>
       {
         Ok(price) => {
           let current_price = prices::ActiveModel {
-            contract_address: Set(contract_address),
+            contract_address: Set(contract_address.clone()),
             chain: Set("solana".to_string()),
             price: Set(Some(price as f32)),
             price_native: Set(Some(token_price_sol as f32)),
@@ -50,6 +56,26 @@ pub async fn refresh_sol_token_prices() -> Result<(), Box<dyn std::error::Error>
               token.contract_address, e
             );
           }
+
+          if let Err(e) = ratchet_trailing_stops(&db_clone, &contract_address, price).await {
+            eprintln!(
+              "Failed to ratchet trailing stops for {}: {:?}",
+              contract_address, e
+            );
+          }
+
+          let snapshot = PriceSnapshot {
+            contract_address: contract_address.clone(),
+            price,
+            recent_prices: vec![],
+          };
+
+          if let Err(e) = evaluate_orders(&db_clone, &snapshot).await {
+            eprintln!(
+              "Failed to evaluate orders for {}: {:?}",
+              contract_address, e
+            );
+          }
         }
         Err(err) => {
           eprintln!(
@@ -1,4 +1,10 @@
-use crate::{db, utils::wallets::solana::register_wallet_tokens};
+use crate::{
+  db,
+  utils::wallets::{
+    solana::register_wallet_tokens,
+    watch::{run_watch_only_wallet_alerts, update_wallet_holdings},
+  },
+};
 use entity::wallets;
 use sea_orm::EntityTrait;
 
@@ -13,9 +19,32 @@ pub async fn update_wallet_token_list() -> Result<(), Box<dyn std::error::Error>
     .map_err(|e| e.to_string())?;
 
   for wallet in wallets {
+    // watch-only wallets aren't traded, so only their holdings are kept
+    if wallet.watch_only {
+      let database = db.clone();
+      tokio::spawn(async move {
+        if let Err(e) = update_wallet_holdings(&database, &wallet).await {
+          eprintln!("Failed to update holdings of wallet {}: {:?}", wallet.id, e);
+        }
+      });
+      continue;
+    }
+
     tokio::spawn(async move {
       let _ = register_wallet_tokens(&wallet.address, wallet.user_id).await;
     });
   }
   Ok(())
 }
+
+pub async fn run_watch_only_alerts() -> Result<(), Box<dyn std::error::Error>> {
+  let db = db::connect_db().await?;
+
+  run_watch_only_wallet_alerts(&db)
+    .await
+    .map_err(|e| format!("Watch-only wallet alert error: {:?}", e))?;
+
+  let _ = db.close().await;
+
+  Ok(())
+}
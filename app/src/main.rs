@@ -1,13 +1,14 @@
 use actix_cors::Cors;
 use actix_web::{get, middleware::Logger, App, HttpResponse, HttpServer, Responder};
 
-use bot::{answer, Command};
+use bot::{schema, Command};
 use dotenvy::dotenv;
 use jobs::cron::cron::start_cron;
 use std::env;
 use std::sync::Arc;
 use teloxide::{prelude::*, utils::command::BotCommands};
 use tokio::join;
+use utils::backtest::run_backtest_command;
 
 mod bot;
 mod db;
@@ -42,6 +43,16 @@ async fn hello() -> impl Responder {
 async fn main() -> std::io::Result<()> {
   dotenv().ok();
   pretty_env_logger::init();
+
+  // `app backtest [options]` replays recorded launches instead of starting the bot and server
+  let args: Vec<String> = env::args().collect();
+  if args.get(1).map(String::as_str) == Some("backtest") {
+    if let Err(e) = run_backtest_command(&args[2..]).await {
+      eprintln!("Backtest failed: {:?}", e);
+    }
+    return Ok(());
+  }
+
   log::info!("Starting command bot and HTTP server...");
 
   let db = Arc::new(
@@ -78,8 +89,12 @@ async fn main() -> std::io::Result<()> {
   .bind(("127.0.0.1", 9000))?
   .run();
 
-  // Run the Teloxide bot
-  let bot_handler = Command::repl(bot, answer);
+  // Run the Teloxide bot, updates other than commands and button presses are ignored
+  let mut dispatcher = Dispatcher::builder(bot, schema())
+    .default_handler(|_update| async {})
+    .enable_ctrlc_handler()
+    .build();
+  let bot_handler = dispatcher.dispatch();
 
   // Run the server and bot concurrently
   let (server_result, _) = join!(server, bot_handler);
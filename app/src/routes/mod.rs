@@ -3,4 +3,5 @@ pub mod webhook;
 
 pub fn init_routes(cfg: &mut actix_web::web::ServiceConfig) {
   cfg.service(webhook::raydium_token_event);
+  cfg.service(webhook::pump_fun_token_event);
 }
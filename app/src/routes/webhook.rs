@@ -1,6 +1,9 @@
 use actix_web::{post, web::Json, HttpRequest, HttpResponse, Responder};
 
-use crate::utils::{auth::authorize_davids_sight, event::raydium::handle_token_created_event};
+use crate::utils::{
+  auth::authorize_davids_sight,
+  event::{pump_fun::handle_curve_token_created_event, raydium::handle_token_created_event},
+};
 
 #[post("/webhooks/raydium_token_event")]
 async fn raydium_token_event(
@@ -17,3 +20,19 @@ async fn raydium_token_event(
 
   HttpResponse::Ok().finish()
 }
+
+#[post("/webhooks/pump_fun_token_event")]
+async fn pump_fun_token_event(
+  req: HttpRequest,
+  body: Json<crate::utils::event::pump_fun::PumpFunTokenEvent>,
+) -> impl Responder {
+  if !authorize_davids_sight(&req) {
+    return HttpResponse::NotFound().finish();
+  }
+
+  tokio::spawn(async {
+    handle_curve_token_created_event(body.into_inner()).await;
+  });
+
+  HttpResponse::Ok().finish()
+}
@@ -0,0 +1,790 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use entity::{raydium_token_launches, token_prices as prices, trade_orders};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::BTreeMap;
+
+use crate::{
+  db,
+  utils::{
+    event::{pump_fun::CURVE_LAUNCH_CLASS, raydium::LiquidityTiers},
+    orders::{
+      strategy::{find_strategy, PriceSnapshot, Signal},
+      trailing_stop::{high_water_mark, trailing_target_price, with_high_water_mark},
+    },
+    settings::{parse_launch_classes, SNIPEABLE_LAUNCH_CLASSES},
+  },
+};
+
+// Replays recorded launches and their token_prices through the launch classification and the
+// order strategies, so liquidity tiers and stop losses can be tuned before changing them live.
+// Every replayed launch is bought at its first price and held until the strategy sells or the
+// price history runs out.
+
+// same window the price jobs hand to the strategies
+const RECENT_PRICES_MINUTES: i64 = 5;
+
+/// An exit strategy to replay, with the parameter its orders would be created with
+#[derive(Debug, Clone)]
+pub struct BacktestStrategy {
+  pub strategy: &'static str,
+  // the multiple for take_profit, a percentage for every other strategy
+  pub parameter: f64,
+}
+
+impl BacktestStrategy {
+  pub fn label(&self) -> String {
+    match self.strategy {
+      "take_profit" => format!("{} {}x", self.strategy, self.parameter),
+      _ => format!("{} {}%", self.strategy, self.parameter),
+    }
+  }
+
+  // the order the strategy would have opened on a buy at `entry_price`
+  fn order(&self, ca: &str, entry_price: f64, opened_at: NaiveDateTime) -> trade_orders::Model {
+    let (target_percentage, target_price, metadata) = match self.strategy {
+      "take_profit" => (
+        (self.parameter - 1.0) * 100.0,
+        entry_price * self.parameter,
+        None,
+      ),
+      "trailing_stop" => (
+        self.parameter,
+        trailing_target_price(entry_price, self.parameter as f32),
+        Some(with_high_water_mark(None, entry_price)),
+      ),
+      _ => (
+        self.parameter,
+        entry_price * (1.0 - self.parameter / 100.0),
+        None,
+      ),
+    };
+
+    trade_orders::Model {
+      id: 0,
+      user_id: 0,
+      wallet_id: 0,
+      token_id: 0,
+      reference_price: entry_price as f32,
+      target_price: target_price as f32,
+      target_percentage: target_percentage as f32,
+      contract_address: ca.to_string(),
+      strategy: self.strategy.to_string(),
+      status: "active".to_string(),
+      created_by: "backtest".to_string(),
+      metadata,
+      parent_id: None,
+      sell_fraction: None,
+      triggered_at: None,
+      filled_at: None,
+      failed_at: None,
+      cancelled_at: None,
+      expired_at: None,
+      expires_at: None,
+      failure_reason: None,
+      filled_transaction_id: None,
+      created_at: opened_at,
+      updated_at: opened_at,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+  pub tiers: LiquidityTiers,
+  // launch classes that get bought
+  pub launch_classes: Vec<String>,
+  pub strategies: Vec<BacktestStrategy>,
+  pub since: Option<NaiveDateTime>,
+  pub position_size_usd: f64,
+}
+
+impl Default for BacktestConfig {
+  fn default() -> Self {
+    BacktestConfig {
+      tiers: LiquidityTiers::from_env(),
+      launch_classes: SNIPEABLE_LAUNCH_CLASSES
+        .iter()
+        .filter(|class| **class != CURVE_LAUNCH_CLASS)
+        .map(|class| class.to_string())
+        .collect(),
+      strategies: vec![
+        BacktestStrategy {
+          strategy: "launch_stop_loss",
+          parameter: 40.0,
+        },
+        BacktestStrategy {
+          strategy: "stop_loss",
+          parameter: 20.0,
+        },
+        BacktestStrategy {
+          strategy: "stop_loss",
+          parameter: 40.0,
+        },
+        BacktestStrategy {
+          strategy: "trailing_stop",
+          parameter: 20.0,
+        },
+        BacktestStrategy {
+          strategy: "take_profit",
+          parameter: 2.0,
+        },
+      ],
+      since: None,
+      position_size_usd: 100.0,
+    }
+  }
+}
+
+fn parse_numbers(flag: &str, value: &str) -> Result<Vec<f64>> {
+  value
+    .split(',')
+    .filter(|number| !number.trim().is_empty())
+    .map(|number| {
+      number
+        .trim()
+        .trim_end_matches(['%', 'x'])
+        .parse::<f64>()
+        .map_err(|_| anyhow!("{} takes comma separated numbers, got {}", flag, value))
+    })
+    .collect()
+}
+
+impl BacktestConfig {
+  /// Build a config from `--flag value` pairs. Strategy flags replace the default strategies.
+  ///
+  /// --since 2024-11-01, --size 100, --classes mid_launch,pro_launch, --tiers 30,70,100,250,
+  /// --stop-loss 20,40, --trailing 20, --take-profit 2,3, --launch-stop-loss on|off|30,40
+  pub fn from_args(args: &[String]) -> Result<Self> {
+    let mut config = BacktestConfig::default();
+    let mut strategies = vec![];
+
+    for pair in args.chunks(2) {
+      let (flag, value) = match pair {
+        [flag, value] => (flag.as_str(), value.as_str()),
+        [flag] => return Err(anyhow!("{} needs a value", flag)),
+        _ => continue,
+      };
+
+      match flag {
+        "--since" => {
+          let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| anyhow!("--since takes a date such as 2024-11-01"))?;
+          config.since = date.and_hms_opt(0, 0, 0);
+        }
+        "--size" => {
+          config.position_size_usd = value
+            .parse::<f64>()
+            .map_err(|_| anyhow!("--size must be a number"))?
+        }
+        "--classes" => {
+          config.launch_classes = parse_launch_classes(value)?
+            .split(',')
+            .map(|class| class.to_string())
+            .collect()
+        }
+        "--tiers" => match parse_numbers(flag, value)?[..] {
+          [lower_limit, mid_limit, normal_limit, pro_limit] => {
+            config.tiers = LiquidityTiers {
+              lower_limit,
+              mid_limit,
+              normal_limit,
+              pro_limit,
+            }
+          }
+          _ => return Err(anyhow!("--tiers takes four limits, lower,mid,normal,pro")),
+        },
+        "--stop-loss" | "--trailing" | "--take-profit" => {
+          let strategy = match flag {
+            "--stop-loss" => "stop_loss",
+            "--trailing" => "trailing_stop",
+            _ => "take_profit",
+          };
+
+          for parameter in parse_numbers(flag, value)? {
+            strategies.push(BacktestStrategy {
+              strategy,
+              parameter,
+            });
+          }
+        }
+        // on replays a 40% floor, percentages replay those floors instead
+        "--launch-stop-loss" if value == "on" => strategies.push(BacktestStrategy {
+          strategy: "launch_stop_loss",
+          parameter: 40.0,
+        }),
+        "--launch-stop-loss" if value == "off" => {}
+        "--launch-stop-loss" => {
+          for parameter in parse_numbers(flag, value)? {
+            strategies.push(BacktestStrategy {
+              strategy: "launch_stop_loss",
+              parameter,
+            });
+          }
+        }
+        _ => return Err(anyhow!("Unknown backtest option {}", flag)),
+      }
+    }
+
+    if !strategies.is_empty() {
+      config.strategies = strategies;
+    }
+
+    Ok(config)
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestTrade {
+  pub contract_address: String,
+  pub launch_class: &'static str,
+  pub entry_price: f64,
+  pub exit_price: f64,
+  pub exit_at: NaiveDateTime,
+  pub pnl_usd: f64,
+  // false when the history ran out before the strategy sold, the exit is the last price then
+  pub closed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyReport {
+  pub label: String,
+  // None for the row covering every launch class
+  pub launch_class: Option<&'static str>,
+  pub trades: usize,
+  pub open_trades: usize,
+  pub win_rate: f64,
+  pub pnl_usd: f64,
+  pub max_drawdown_usd: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct BacktestReport {
+  pub launches: usize,
+  // launches without dexscreener liquidity to classify them by
+  pub unclassified: usize,
+  // bought launches with no recorded prices to replay
+  pub without_prices: usize,
+  pub launch_classes: BTreeMap<&'static str, usize>,
+  pub strategies: Vec<StrategyReport>,
+}
+
+struct PricePoint {
+  price: f64,
+  at: NaiveDateTime,
+}
+
+fn replay_strategy(
+  strategy: &BacktestStrategy,
+  launch: &raydium_token_launches::Model,
+  launch_class: &'static str,
+  history: &[PricePoint],
+  position_size_usd: f64,
+) -> Option<BacktestTrade> {
+  let first = history.first()?;
+  let entry_price = launch
+    .launch_price_usd
+    .map(|price| price as f64)
+    .filter(|price| *price > 0.0)
+    .unwrap_or(first.price);
+
+  let evaluator = find_strategy(strategy.strategy)?;
+  let mut order = strategy.order(&launch.contract_address, entry_price, first.at);
+  let mut window_start = 0;
+
+  for (index, point) in history.iter().enumerate() {
+    // mirrors ratchet_trailing_stops running ahead of the evaluation
+    if order.strategy == "trailing_stop" && point.price > high_water_mark(&order) {
+      order.target_price = trailing_target_price(point.price, order.target_percentage) as f32;
+      order.metadata = Some(with_high_water_mark(order.metadata.take(), point.price));
+    }
+
+    while history[window_start].at <= point.at - Duration::minutes(RECENT_PRICES_MINUTES) {
+      window_start += 1;
+    }
+
+    let snapshot = PriceSnapshot {
+      contract_address: launch.contract_address.clone(),
+      price: point.price,
+      recent_prices: history[window_start..=index]
+        .iter()
+        .map(|point| point.price)
+        .collect(),
+    };
+
+    if evaluator.evaluate(&order, &snapshot) == Signal::Sell {
+      return Some(BacktestTrade {
+        contract_address: launch.contract_address.clone(),
+        launch_class,
+        entry_price,
+        exit_price: point.price,
+        exit_at: point.at,
+        pnl_usd: position_size_usd * (point.price / entry_price - 1.0),
+        closed: true,
+      });
+    }
+  }
+
+  let last = history.last()?;
+
+  Some(BacktestTrade {
+    contract_address: launch.contract_address.clone(),
+    launch_class,
+    entry_price,
+    exit_price: last.price,
+    exit_at: last.at,
+    pnl_usd: position_size_usd * (last.price / entry_price - 1.0),
+    closed: false,
+  })
+}
+
+fn summarize(
+  label: &str,
+  launch_class: Option<&'static str>,
+  trades: &[&BacktestTrade],
+) -> StrategyReport {
+  let mut ordered: Vec<&&BacktestTrade> = trades.iter().collect();
+  ordered.sort_by_key(|trade| trade.exit_at);
+
+  let mut equity = 0.0;
+  let mut peak = 0.0;
+  let mut max_drawdown_usd: f64 = 0.0;
+
+  for trade in &ordered {
+    equity += trade.pnl_usd;
+    if equity > peak {
+      peak = equity;
+    }
+    max_drawdown_usd = max_drawdown_usd.max(peak - equity);
+  }
+
+  let wins = trades.iter().filter(|trade| trade.pnl_usd > 0.0).count();
+
+  StrategyReport {
+    label: label.to_string(),
+    launch_class,
+    trades: trades.len(),
+    open_trades: trades.iter().filter(|trade| !trade.closed).count(),
+    win_rate: if trades.is_empty() {
+      0.0
+    } else {
+      wins as f64 / trades.len() as f64 * 100.0
+    },
+    pnl_usd: equity,
+    max_drawdown_usd,
+  }
+}
+
+async fn load_price_history(
+  db: &DatabaseConnection,
+  launch: &raydium_token_launches::Model,
+) -> Result<Vec<PricePoint>> {
+  let records = prices::Entity::find()
+    .filter(prices::Column::ContractAddress.eq(&launch.contract_address))
+    .filter(prices::Column::CreatedAt.gte(launch.created_at))
+    .order_by_asc(prices::Column::CreatedAt)
+    .all(db)
+    .await
+    .context("Failed to retrieve token prices")?;
+
+  Ok(
+    records
+      .into_iter()
+      .filter_map(|record| {
+        record
+          .price
+          .filter(|price| *price > 0.0)
+          .map(|price| PricePoint {
+            price: price as f64,
+            at: record.created_at,
+          })
+      })
+      .collect(),
+  )
+}
+
+/// Replay every recorded launch through the config and report how each strategy did
+pub async fn run_backtest(
+  db: &DatabaseConnection,
+  config: &BacktestConfig,
+) -> Result<BacktestReport> {
+  // curve launches have no pool yet, their liquidity doesn't sort into the tiers
+  let mut query = raydium_token_launches::Entity::find().filter(
+    Condition::any()
+      .add(raydium_token_launches::Column::LaunchClass.is_null())
+      .add(raydium_token_launches::Column::LaunchClass.ne(CURVE_LAUNCH_CLASS)),
+  );
+  if let Some(since) = config.since {
+    query = query.filter(raydium_token_launches::Column::CreatedAt.gte(since));
+  }
+
+  let launches = query
+    .order_by_asc(raydium_token_launches::Column::CreatedAt)
+    .all(db)
+    .await
+    .context("Failed to retrieve token launches")?;
+
+  let mut report = BacktestReport {
+    launches: launches.len(),
+    ..Default::default()
+  };
+  let mut trades: Vec<Vec<BacktestTrade>> = vec![vec![]; config.strategies.len()];
+
+  for launch in &launches {
+    // the launch event isn't stored, dexscreener's pool liquidity is the closest record of it
+    let pool_sol_liquidity = match launch
+      .meta
+      .as_ref()
+      .and_then(|meta| meta.pointer("/liquidity/quote"))
+      .and_then(|quote| quote.as_f64())
+    {
+      Some(liquidity) => liquidity,
+      None => {
+        report.unclassified += 1;
+        continue;
+      }
+    };
+
+    let launch_class = config.tiers.classify(pool_sol_liquidity);
+    *report.launch_classes.entry(launch_class).or_default() += 1;
+
+    if !config
+      .launch_classes
+      .iter()
+      .any(|class| class == launch_class)
+    {
+      continue;
+    }
+
+    let history = load_price_history(db, launch).await?;
+    if history.is_empty() {
+      report.without_prices += 1;
+      continue;
+    }
+
+    for (strategy, strategy_trades) in config.strategies.iter().zip(trades.iter_mut()) {
+      if let Some(trade) = replay_strategy(
+        strategy,
+        launch,
+        launch_class,
+        &history,
+        config.position_size_usd,
+      ) {
+        strategy_trades.push(trade);
+      }
+    }
+  }
+
+  for (strategy, strategy_trades) in config.strategies.iter().zip(trades.iter()) {
+    let label = strategy.label();
+    let all_trades: Vec<&BacktestTrade> = strategy_trades.iter().collect();
+    report.strategies.push(summarize(&label, None, &all_trades));
+
+    for launch_class in &config.launch_classes {
+      let class_trades: Vec<&BacktestTrade> = strategy_trades
+        .iter()
+        .filter(|trade| trade.launch_class == launch_class)
+        .collect();
+
+      if let Some(trade) = class_trades.first() {
+        report
+          .strategies
+          .push(summarize(&label, Some(trade.launch_class), &class_trades));
+      }
+    }
+  }
+
+  Ok(report)
+}
+
+pub fn format_backtest_report(config: &BacktestConfig, report: &BacktestReport) -> String {
+  let mut lines = vec![
+    format!(
+      "Tiers: lower {} / mid {} / normal {} / pro {} SOL, buying {} at ${} each",
+      config.tiers.lower_limit,
+      config.tiers.mid_limit,
+      config.tiers.normal_limit,
+      config.tiers.pro_limit,
+      config.launch_classes.join(","),
+      config.position_size_usd
+    ),
+    format!(
+      "Launches: {} ({} without liquidity, {} bought without prices)",
+      report.launches, report.unclassified, report.without_prices
+    ),
+  ];
+
+  for (launch_class, count) in &report.launch_classes {
+    lines.push(format!("  {}: {}", launch_class, count));
+  }
+
+  lines.push(String::new());
+  lines.push(format!(
+    "{:<22} {:<14} {:>7} {:>6} {:>9} {:>12} {:>12}",
+    "strategy", "class", "trades", "open", "win rate", "pnl", "drawdown"
+  ));
+
+  for row in &report.strategies {
+    lines.push(format!(
+      "{:<22} {:<14} {:>7} {:>6} {:>8.1}% {:>12.2} {:>12.2}",
+      row.label,
+      row.launch_class.unwrap_or("all"),
+      row.trades,
+      row.open_trades,
+      row.win_rate,
+      row.pnl_usd,
+      row.max_drawdown_usd
+    ));
+  }
+
+  lines.join("\n")
+}
+
+/// Entry point of `app backtest [options]`, prints the report to stdout
+pub async fn run_backtest_command(args: &[String]) -> Result<()> {
+  let config = BacktestConfig::from_args(args)?;
+
+  let db = db::connect_db()
+    .await
+    .context("Failed to connect to the database")?;
+
+  let report = run_backtest(&db, &config).await?;
+  println!("{}", format_backtest_report(&config, &report));
+
+  let _ = db.close().await;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(minutes: i64) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 12, 1)
+      .unwrap()
+      .and_hms_opt(0, 0, 0)
+      .unwrap()
+      + Duration::minutes(minutes)
+  }
+
+  fn launch(launch_price_usd: Option<f32>) -> raydium_token_launches::Model {
+    raydium_token_launches::Model {
+      id: 1,
+      contract_address: "token".to_string(),
+      creator_address: "creator".to_string(),
+      evaluation: None,
+      launch_class: Some("mid_launch".to_string()),
+      launch_liquidity: 80.0,
+      launch_liquidity_usd: 16_000.0,
+      launch_price_usd,
+      rugged_at: None,
+      lifespan: None,
+      meta: None,
+      has_boost: None,
+      created_at: at(0),
+      updated_at: at(0),
+    }
+  }
+
+  fn history(prices: &[f64]) -> Vec<PricePoint> {
+    prices
+      .iter()
+      .enumerate()
+      .map(|(minute, price)| PricePoint {
+        price: *price,
+        at: at(minute as i64),
+      })
+      .collect()
+  }
+
+  fn trade(pnl_usd: f64, exit_minute: i64, closed: bool) -> BacktestTrade {
+    BacktestTrade {
+      contract_address: "token".to_string(),
+      launch_class: "mid_launch",
+      entry_price: 1.0,
+      exit_price: 1.0,
+      exit_at: at(exit_minute),
+      pnl_usd,
+      closed,
+    }
+  }
+
+  fn strategy(strategy: &'static str, parameter: f64) -> BacktestStrategy {
+    BacktestStrategy {
+      strategy,
+      parameter,
+    }
+  }
+
+  #[test]
+  fn a_stop_loss_sells_at_its_floor() {
+    let trade = replay_strategy(
+      &strategy("stop_loss", 20.0),
+      &launch(Some(1.0)),
+      "mid_launch",
+      &history(&[1.0, 0.9, 0.79, 0.5]),
+      100.0,
+    )
+    .unwrap();
+
+    assert!(trade.closed);
+    assert_eq!(trade.exit_price, 0.79);
+    assert!((trade.pnl_usd + 21.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn a_take_profit_sells_at_its_multiple() {
+    let trade = replay_strategy(
+      &strategy("take_profit", 2.0),
+      &launch(Some(1.0)),
+      "mid_launch",
+      &history(&[1.0, 1.5, 2.1, 3.0]),
+      100.0,
+    )
+    .unwrap();
+
+    assert_eq!(trade.exit_price, 2.1);
+    assert!((trade.pnl_usd - 110.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn a_trailing_stop_follows_the_peak() {
+    let trade = replay_strategy(
+      &strategy("trailing_stop", 20.0),
+      &launch(Some(1.0)),
+      "mid_launch",
+      &history(&[1.0, 2.0, 1.7, 1.5]),
+      100.0,
+    )
+    .unwrap();
+
+    assert_eq!(trade.exit_price, 1.5);
+  }
+
+  #[test]
+  fn a_strategy_that_never_sold_stays_open_at_the_last_price() {
+    let trade = replay_strategy(
+      &strategy("stop_loss", 50.0),
+      &launch(Some(1.0)),
+      "mid_launch",
+      &history(&[1.0, 0.8, 1.2]),
+      100.0,
+    )
+    .unwrap();
+
+    assert!(!trade.closed);
+    assert_eq!(trade.exit_price, 1.2);
+  }
+
+  #[test]
+  fn entry_falls_back_to_the_first_price_without_a_launch_price() {
+    for launch_price_usd in [None, Some(0.0)] {
+      let trade = replay_strategy(
+        &strategy("stop_loss", 20.0),
+        &launch(launch_price_usd),
+        "mid_launch",
+        &history(&[2.0, 1.5]),
+        100.0,
+      )
+      .unwrap();
+
+      assert_eq!(trade.entry_price, 2.0);
+      assert_eq!(trade.exit_price, 1.5);
+    }
+  }
+
+  #[test]
+  fn nothing_to_replay_without_prices() {
+    assert!(replay_strategy(
+      &strategy("stop_loss", 20.0),
+      &launch(Some(1.0)),
+      "mid_launch",
+      &[],
+      100.0
+    )
+    .is_none());
+  }
+
+  #[test]
+  fn summaries_count_wins_and_open_trades() {
+    let trades = [
+      trade(50.0, 1, true),
+      trade(-20.0, 2, true),
+      trade(10.0, 3, false),
+    ];
+    let trades: Vec<&BacktestTrade> = trades.iter().collect();
+
+    let report = summarize("stop_loss 20%", Some("mid_launch"), &trades);
+
+    assert_eq!(report.trades, 3);
+    assert_eq!(report.open_trades, 1);
+    assert!((report.win_rate - 200.0 / 3.0).abs() < 1e-9);
+    assert_eq!(report.pnl_usd, 40.0);
+  }
+
+  #[test]
+  fn drawdown_follows_the_order_trades_exited_in() {
+    // listed out of exit order, the loss comes after the peak of 50
+    let trades = [
+      trade(-30.0, 3, true),
+      trade(50.0, 1, true),
+      trade(10.0, 5, true),
+    ];
+    let trades: Vec<&BacktestTrade> = trades.iter().collect();
+
+    let report = summarize("stop_loss 20%", None, &trades);
+
+    assert_eq!(report.max_drawdown_usd, 30.0);
+    assert_eq!(report.pnl_usd, 30.0);
+  }
+
+  #[test]
+  fn an_empty_summary_has_no_win_rate() {
+    let report = summarize("stop_loss 20%", None, &[]);
+
+    assert_eq!(report.trades, 0);
+    assert_eq!(report.win_rate, 0.0);
+    assert_eq!(report.max_drawdown_usd, 0.0);
+  }
+
+  #[test]
+  fn strategy_flags_replace_the_default_strategies() {
+    let args: Vec<String> = [
+      "--stop-loss",
+      "20,40%",
+      "--take-profit",
+      "3x",
+      "--launch-stop-loss",
+      "on",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    let config = BacktestConfig::from_args(&args).unwrap();
+    let labels: Vec<String> = config.strategies.iter().map(|s| s.label()).collect();
+
+    assert_eq!(
+      labels,
+      [
+        "stop_loss 20%",
+        "stop_loss 40%",
+        "take_profit 3x",
+        "launch_stop_loss 40%"
+      ]
+    );
+  }
+
+  #[test]
+  fn malformed_flags_are_refused() {
+    for args in [
+      vec!["--stop-loss"],
+      vec!["--stop-loss", "twenty"],
+      vec!["--tiers", "30,70"],
+      vec!["--speed", "fast"],
+    ] {
+      let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+      assert!(BacktestConfig::from_args(&args).is_err(), "{:?}", args);
+    }
+  }
+}
@@ -0,0 +1,571 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use entity::{onchain_transactions, users, wallet_follows, wallets};
+use sea_orm::{
+  sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+  QueryOrder, Set,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::utils::{
+  notifications::notify_user_by_telegram,
+  price::solana::fetch_token_price,
+  swap::{
+    lock::{
+      acquire_execution_lock, release_execution_lock, wallet_follow_lock_key, wallet_lock_key,
+    },
+    paper::wallet_token_balance,
+    slippage::SwapKind,
+    solana::{
+      execute_buy_trade, execute_sell_trade, fail_reserved_transaction, reserve_transaction,
+      settle_reserved_transaction,
+    },
+  },
+  wallets::activity::{fetch_signatures_since, fetch_wallet_swap, WalletSwap, WalletSwapSide},
+};
+
+// Copy trading follows another wallet's swaps and makes the same ones from the user's wallet.
+// Buys are sized per follow, sells let go of the same share of the position the followed
+// wallet sold.
+
+pub const COPY_SIZE_MODES: [&str; 2] = ["fixed", "proportional"];
+
+// copied buys below this much SOL are skipped
+const MIN_COPY_BUY_SOL: f64 = 0.001;
+
+#[derive(Debug)]
+pub struct WalletFollowParams {
+  pub followed_address: String,
+  // fixed: SOL per buy, proportional: percentage of the SOL the followed wallet spent
+  pub size_mode: String,
+  pub size_value: f64,
+  pub max_sol_per_trade: f64,
+  pub max_total_sol: Option<f64>,
+}
+
+fn validate_follow_params(params: &WalletFollowParams) -> Result<()> {
+  Pubkey::from_str(&params.followed_address)
+    .map_err(|_| anyhow!("{} is not a Solana address", params.followed_address))?;
+
+  if !COPY_SIZE_MODES.contains(&params.size_mode.as_str()) {
+    return Err(anyhow!(
+      "Unknown size mode {}. Valid modes are: {}",
+      params.size_mode,
+      COPY_SIZE_MODES.join(", ")
+    ));
+  }
+
+  if params.size_value <= 0.0 {
+    return Err(anyhow!("Size must be greater than 0"));
+  }
+
+  if params.max_sol_per_trade <= 0.0 {
+    return Err(anyhow!("Max SOL per trade must be greater than 0"));
+  }
+
+  if params
+    .max_total_sol
+    .is_some_and(|max_total_sol| max_total_sol <= 0.0)
+  {
+    return Err(anyhow!("Max total SOL must be greater than 0"));
+  }
+
+  Ok(())
+}
+
+/// Follow a wallet. Only swaps made after this are copied.
+pub async fn create_wallet_follow(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  params: &WalletFollowParams,
+) -> Result<wallet_follows::Model> {
+  validate_follow_params(params)?;
+
+  let existing_follow = wallet_follows::Entity::find()
+    .filter(wallet_follows::Column::UserId.eq(user_id))
+    .filter(wallet_follows::Column::FollowedAddress.eq(&params.followed_address))
+    .one(db)
+    .await
+    .context("Failed to retrieve wallet follows")?;
+
+  if let Some(follow) = existing_follow {
+    return Err(anyhow!(
+      "You already follow {} (follow {})",
+      follow.followed_address,
+      follow.id
+    ));
+  }
+
+  let new_follow = wallet_follows::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    followed_address: Set(params.followed_address.clone()),
+    size_mode: Set(params.size_mode.clone()),
+    size_value: Set(params.size_value as f32),
+    max_sol_per_trade: Set(params.max_sol_per_trade as f32),
+    max_total_sol: Set(
+      params
+        .max_total_sol
+        .map(|max_total_sol| max_total_sol as f32),
+    ),
+    ..Default::default()
+  };
+
+  new_follow
+    .insert(db)
+    .await
+    .context("Failed to create wallet follow")
+}
+
+pub async fn find_user_wallet_follows(
+  db: &DatabaseConnection,
+  user_id: i32,
+) -> Result<Vec<wallet_follows::Model>> {
+  wallet_follows::Entity::find()
+    .filter(wallet_follows::Column::UserId.eq(user_id))
+    .order_by_desc(wallet_follows::Column::CreatedAt)
+    .all(db)
+    .await
+    .context("Failed to retrieve wallet follows")
+}
+
+async fn find_user_wallet_follow(
+  db: &DatabaseConnection,
+  user_id: i32,
+  follow_id: i32,
+) -> Result<wallet_follows::Model> {
+  wallet_follows::Entity::find_by_id(follow_id)
+    .filter(wallet_follows::Column::UserId.eq(user_id))
+    .one(db)
+    .await
+    .context("Failed to retrieve wallet follow")?
+    .ok_or_else(|| anyhow!("Follow {} not found", follow_id))
+}
+
+/// Turn copying of one of the user's follows on or off, or choose whether its sells are copied
+pub async fn update_wallet_follow(
+  db: &DatabaseConnection,
+  user_id: i32,
+  follow_id: i32,
+  action: &str,
+) -> Result<wallet_follows::Model> {
+  let follow = find_user_wallet_follow(db, user_id, follow_id).await?;
+
+  let mut active_model: wallet_follows::ActiveModel = follow.clone().into();
+
+  match action {
+    "on" => {
+      active_model.enabled = Set(true);
+      // swaps made while copying was off aren't caught up on
+      if !follow.enabled {
+        active_model.last_signature = Set(None);
+      }
+    }
+    "off" => active_model.enabled = Set(false),
+    "copy_sells" => active_model.mirror_sells = Set(true),
+    "skip_sells" => active_model.mirror_sells = Set(false),
+    _ => return Err(anyhow!("Unknown action {}", action)),
+  }
+
+  active_model.updated_at = Set(Utc::now().naive_utc());
+
+  active_model
+    .update(db)
+    .await
+    .context("Failed to update wallet follow")
+}
+
+pub async fn delete_wallet_follow(
+  db: &DatabaseConnection,
+  user_id: i32,
+  follow_id: i32,
+) -> Result<()> {
+  let follow = find_user_wallet_follow(db, user_id, follow_id).await?;
+
+  wallet_follows::Entity::delete_by_id(follow.id)
+    .exec(db)
+    .await
+    .context("Failed to delete wallet follow")?;
+
+  Ok(())
+}
+
+pub fn format_wallet_follow(follow: &wallet_follows::Model) -> String {
+  let size = if follow.size_mode == "proportional" {
+    format!("{}% of their size", follow.size_value)
+  } else {
+    format!("{} SOL per buy", follow.size_value)
+  };
+
+  let total = match follow.max_total_sol {
+    Some(max_total_sol) => format!("{} of {} SOL spent", follow.spent_sol, max_total_sol),
+    None => format!("{} SOL spent", follow.spent_sol),
+  };
+
+  format!(
+    "#{} {} [{}]\n{}, at most {} SOL per trade, {}\nsells: {}",
+    follow.id,
+    follow.followed_address,
+    if follow.enabled { "on" } else { "off" },
+    size,
+    follow.max_sol_per_trade,
+    total,
+    if follow.mirror_sells {
+      "copied"
+    } else {
+      "ignored"
+    }
+  )
+}
+
+/// SOL to spend copying a buy of `followed_sol`, within the follow's limits
+pub fn copy_buy_size(follow: &wallet_follows::Model, followed_sol: f64) -> f64 {
+  let size = if follow.size_mode == "proportional" {
+    followed_sol * follow.size_value as f64 / 100.0
+  } else {
+    follow.size_value as f64
+  };
+
+  let remaining = follow
+    .max_total_sol
+    .map(|max_total_sol| (max_total_sol - follow.spent_sol) as f64)
+    .unwrap_or(f64::MAX);
+
+  size.min(follow.max_sol_per_trade as f64).min(remaining)
+}
+
+async fn advance_follow_cursor(
+  db: &DatabaseConnection,
+  follow_id: i32,
+  last_signature: &str,
+) -> Result<()> {
+  let cursor_update_model = wallet_follows::ActiveModel {
+    id: Set(follow_id),
+    last_signature: Set(Some(last_signature.to_string())),
+    updated_at: Set(Utc::now().naive_utc()),
+    ..Default::default()
+  };
+
+  wallet_follows::Entity::update(cursor_update_model)
+    .exec(db)
+    .await
+    .context("Failed to update wallet follow")?;
+
+  Ok(())
+}
+
+async fn add_follow_spent_sol(db: &DatabaseConnection, follow_id: i32, sol: f64) -> Result<()> {
+  wallet_follows::Entity::update_many()
+    .col_expr(
+      wallet_follows::Column::SpentSol,
+      Expr::col(wallet_follows::Column::SpentSol).add(sol as f32),
+    )
+    .filter(wallet_follows::Column::Id.eq(follow_id))
+    .exec(db)
+    .await
+    .context("Failed to update wallet follow spending")?;
+
+  Ok(())
+}
+
+// point the copied swap's row at the swap it copied
+async fn reference_source_transaction(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+  source_signature: &str,
+) -> Result<()> {
+  let source_update_model = onchain_transactions::ActiveModel {
+    id: Set(transaction_id),
+    source_transaction_hash: Set(Some(source_signature.to_string())),
+    ..Default::default()
+  };
+
+  onchain_transactions::Entity::update(source_update_model)
+    .exec(db)
+    .await
+    .context("Failed to reference source transaction")?;
+
+  Ok(())
+}
+
+// Returns the message for the user, None when the swap wasn't copied
+async fn copy_swap(
+  db: &DatabaseConnection,
+  follow: &wallet_follows::Model,
+  wallet: &wallets::Model,
+  swap: &WalletSwap,
+  sol_price: f64,
+) -> Result<Option<String>> {
+  let (from_token, to_token) = match swap.side {
+    WalletSwapSide::Buy => (
+      "So11111111111111111111111111111111111111112",
+      swap.mint.as_str(),
+    ),
+    WalletSwapSide::Sell => (
+      swap.mint.as_str(),
+      "So11111111111111111111111111111111111111112",
+    ),
+  };
+
+  let buy_size = copy_buy_size(follow, swap.sol_amount);
+
+  match swap.side {
+    WalletSwapSide::Buy if buy_size < MIN_COPY_BUY_SOL => return Ok(None),
+    WalletSwapSide::Sell => {
+      if !follow.mirror_sells {
+        return Ok(None);
+      }
+
+      let balance = wallet_token_balance(db, wallet, &swap.mint).await?;
+      if balance.amount <= 0.0 {
+        return Ok(None);
+      }
+    }
+    _ => {}
+  }
+
+  let transaction_id = match reserve_transaction(
+    db,
+    &format!("copy-{}-{}", follow.id, swap.signature),
+    follow.user_id,
+    wallet.id,
+    from_token,
+    to_token,
+  )
+  .await?
+  {
+    Some(transaction_id) => transaction_id,
+    None => return Ok(None),
+  };
+
+  reference_source_transaction(db, transaction_id, &swap.signature).await?;
+
+  let message = match swap.side {
+    WalletSwapSide::Buy => {
+      let purchase = match execute_buy_trade(
+        follow.user_id,
+        wallet.id,
+        &swap.mint,
+        buy_size,
+        SwapKind::Buy,
+        db,
+      )
+      .await
+      {
+        Ok(purchase) => purchase,
+        Err(e) => {
+          fail_reserved_transaction(db, transaction_id, &e).await?;
+          return Err(e);
+        }
+      };
+
+      let transaction_hash = purchase.attempt.transaction_hash.clone();
+      settle_reserved_transaction(
+        db,
+        transaction_id,
+        purchase.attempt,
+        purchase.sol_amount,
+        purchase.sol_amount * sol_price,
+      )
+      .await?;
+      add_follow_spent_sol(db, follow.id, purchase.sol_amount).await?;
+
+      format!(
+        "Copied a buy of {} by {}: {} SOL (they spent {:.4} SOL)\nTransaction Hash: {}",
+        swap.mint, follow.followed_address, purchase.sol_amount, swap.sol_amount, transaction_hash
+      )
+    }
+    WalletSwapSide::Sell => {
+      let sale = match execute_sell_trade(
+        follow.user_id,
+        wallet,
+        &swap.mint,
+        swap.sold_fraction,
+        SwapKind::Sell,
+        db,
+      )
+      .await
+      {
+        Ok(sale) => sale,
+        Err(e) => {
+          fail_reserved_transaction(db, transaction_id, &e).await?;
+          return Err(e);
+        }
+      };
+
+      let token_price = fetch_token_price(&swap.mint).await.unwrap_or(0.0);
+      let transaction_hash = sale.attempt.transaction_hash.clone();
+      settle_reserved_transaction(
+        db,
+        transaction_id,
+        sale.attempt,
+        sale.sold_amount,
+        sale.sold_amount * token_price,
+      )
+      .await?;
+
+      format!(
+        "Copied a sell of {} by {}: sold {:.0}% of your position\nTransaction Hash: {}",
+        swap.mint,
+        follow.followed_address,
+        swap.sold_fraction * 100.0,
+        transaction_hash
+      )
+    }
+  };
+
+  Ok(Some(message))
+}
+
+async fn notify_follow_user(db: &DatabaseConnection, user_id: i32, message: &str) -> Result<()> {
+  let user = users::Entity::find_by_id(user_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve user")?;
+
+  if let Some(tg_id) = user.and_then(|user| user.tg_id.parse::<i64>().ok()) {
+    if let Err(e) = notify_user_by_telegram(tg_id, message).await {
+      eprintln!("Error notifying user {}: {}", tg_id, e);
+    }
+  }
+
+  Ok(())
+}
+
+async fn copy_new_swaps(
+  db: &DatabaseConnection,
+  follow: wallet_follows::Model,
+  sol_price: f64,
+) -> Result<()> {
+  let signatures =
+    fetch_signatures_since(&follow.followed_address, follow.last_signature.as_deref()).await?;
+
+  let newest_signature = match signatures.last() {
+    Some(newest_signature) => newest_signature,
+    None => return Ok(()),
+  };
+
+  // moved past before copying, a swap that fails to copy is reported rather than retried
+  advance_follow_cursor(db, follow.id, newest_signature).await?;
+
+  // a new follow starts from the followed wallet's latest transaction
+  if follow.last_signature.is_none() {
+    return Ok(());
+  }
+
+  let wallet = wallets::Entity::find_by_id(follow.wallet_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve wallet")?
+    .ok_or_else(|| anyhow!("No wallet found for follow {}", follow.id))?;
+
+  for signature in &signatures {
+    let swap = match fetch_wallet_swap(&follow.followed_address, signature).await {
+      Ok(Some(swap)) => swap,
+      Ok(None) => continue,
+      Err(e) => {
+        eprintln!("Failed to read transaction {}: {:?}", signature, e);
+        continue;
+      }
+    };
+
+    // re-read so the spending limit counts buys copied earlier in this run
+    let follow = wallet_follows::Entity::find_by_id(follow.id)
+      .one(db)
+      .await
+      .context("Failed to retrieve wallet follow")?
+      .ok_or_else(|| anyhow!("Follow {} was removed", follow.id))?;
+
+    let message = match copy_swap(db, &follow, &wallet, &swap, sol_price).await {
+      Ok(Some(message)) => message,
+      Ok(None) => continue,
+      Err(e) => {
+        eprintln!(
+          "Failed to copy {} for follow {}: {:?}",
+          signature, follow.id, e
+        );
+        format!(
+          "Could not copy a {} of {} by {}: {}",
+          if swap.side == WalletSwapSide::Buy {
+            "buy"
+          } else {
+            "sell"
+          },
+          swap.mint,
+          follow.followed_address,
+          e
+        )
+      }
+    };
+
+    notify_follow_user(db, follow.user_id, &message).await?;
+  }
+
+  Ok(())
+}
+
+async fn run_wallet_follow(
+  db: &DatabaseConnection,
+  follow: wallet_follows::Model,
+  sol_price: f64,
+) -> Result<()> {
+  let follow_id = follow.id;
+  let lock = match acquire_execution_lock(
+    db,
+    vec![
+      wallet_follow_lock_key(follow.id),
+      wallet_lock_key(follow.wallet_id),
+    ],
+  )
+  .await?
+  {
+    Some(lock) => lock,
+    // the cursor hasn't moved, so the next run picks the swaps up
+    None => return Ok(()),
+  };
+
+  let copied = copy_new_swaps(db, follow, sol_price).await;
+
+  if let Err(e) = release_execution_lock(db, lock).await {
+    eprintln!("Failed to release lock of follow {}: {:?}", follow_id, e);
+  }
+
+  copied
+}
+
+/// Copy the swaps made since the last run by every followed wallet that has copying on
+pub async fn run_enabled_wallet_follows(db: &DatabaseConnection) -> Result<()> {
+  let follows = wallet_follows::Entity::find()
+    .filter(wallet_follows::Column::Enabled.eq(true))
+    .all(db)
+    .await
+    .context("Failed to retrieve wallet follows")?;
+
+  if follows.is_empty() {
+    return Ok(());
+  }
+
+  // only used to value the recorded transactions
+  let sol_price = fetch_token_price("So11111111111111111111111111111111111111112")
+    .await
+    .unwrap_or(0.0);
+
+  let mut tasks = vec![];
+
+  for follow in follows {
+    let database = db.clone();
+    let task = tokio::spawn(async move {
+      let follow_id = follow.id;
+      if let Err(e) = run_wallet_follow(&database, follow, sol_price).await {
+        eprintln!("Failed to copy trades of follow {}: {:?}", follow_id, e);
+      }
+    });
+    tasks.push(task);
+  }
+
+  futures::future::join_all(tasks).await;
+
+  Ok(())
+}
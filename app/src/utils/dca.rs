@@ -0,0 +1,478 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use entity::{dca_plans, onchain_transactions, users, wallets};
+use sea_orm::{
+  sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+  QueryOrder, Set,
+};
+
+use crate::utils::{
+  notifications::notify_user_by_telegram,
+  price::solana::fetch_token_price,
+  swap::{
+    lock::{acquire_execution_lock, dca_plan_lock_key, release_execution_lock, wallet_lock_key},
+    slippage::SwapKind,
+    solana::{
+      execute_buy_trade, fail_reserved_transaction, reserve_transaction,
+      settle_reserved_transaction,
+    },
+  },
+};
+
+// below this much SOL left in the budget a plan is considered spent
+const MIN_DCA_BUY_SOL: f64 = 0.000_001;
+
+const DCA_IDEMPOTENCY_KEY_PREFIX: &str = "dca-";
+
+#[derive(Debug)]
+pub struct DcaPlanParams {
+  pub amount_per_buy: f64,
+  pub interval_minutes: i32,
+  pub total_budget: f64,
+  pub ends_at: NaiveDateTime,
+}
+
+/// Parse a plan end written as days (`7d`), a date (`2024-12-31`) or an RFC 3339 timestamp
+pub fn parse_dca_end(value: &str) -> Result<NaiveDateTime> {
+  let value = value.trim();
+
+  if let Some(days) = value.strip_suffix('d') {
+    let days = days
+      .parse::<i64>()
+      .map_err(|_| anyhow!("Invalid number of days {}", value))?;
+    return Ok(Utc::now().naive_utc() + Duration::days(days));
+  }
+
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    return date
+      .and_hms_opt(23, 59, 59)
+      .ok_or_else(|| anyhow!("Invalid end date {}", value));
+  }
+
+  chrono::DateTime::parse_from_rfc3339(value)
+    .map(|end| end.naive_utc())
+    .map_err(|_| anyhow!("End must be a number of days such as 7d, or a date such as 2024-12-31"))
+}
+
+fn validate_dca_params(params: &DcaPlanParams) -> Result<()> {
+  if params.amount_per_buy <= 0.0 {
+    return Err(anyhow!("Amount per buy must be greater than 0"));
+  }
+
+  if params.interval_minutes <= 0 {
+    return Err(anyhow!("Interval must be at least one minute"));
+  }
+
+  if params.total_budget < params.amount_per_buy {
+    return Err(anyhow!("Total budget must cover at least one buy"));
+  }
+
+  if params.ends_at <= Utc::now().naive_utc() {
+    return Err(anyhow!("End date must be in the future"));
+  }
+
+  Ok(())
+}
+
+/// Open a plan whose first buy runs on the next scheduler tick
+pub async fn create_dca_plan(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  token_id: i32,
+  contract_address: &str,
+  params: &DcaPlanParams,
+) -> Result<dca_plans::Model> {
+  validate_dca_params(params)?;
+
+  let new_plan = dca_plans::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    token_id: Set(token_id),
+    contract_address: Set(contract_address.to_string()),
+    amount_per_buy: Set(params.amount_per_buy as f32),
+    interval_minutes: Set(params.interval_minutes),
+    total_budget: Set(params.total_budget as f32),
+    next_buy_at: Set(Utc::now().naive_utc()),
+    ends_at: Set(params.ends_at),
+    ..Default::default()
+  };
+
+  new_plan
+    .insert(db)
+    .await
+    .context("Failed to create DCA plan")
+}
+
+pub async fn find_user_dca_plans(
+  db: &DatabaseConnection,
+  user_id: i32,
+  open_only: bool,
+) -> Result<Vec<dca_plans::Model>> {
+  let mut query = dca_plans::Entity::find()
+    .filter(dca_plans::Column::UserId.eq(user_id))
+    .order_by_desc(dca_plans::Column::CreatedAt);
+
+  if open_only {
+    query = query.filter(dca_plans::Column::Status.is_in(["active", "paused"]));
+  }
+
+  query.all(db).await.context("Failed to retrieve DCA plans")
+}
+
+/// Pause, resume or cancel one of the user's plans
+pub async fn update_dca_plan_status(
+  db: &DatabaseConnection,
+  user_id: i32,
+  plan_id: i32,
+  action: &str,
+) -> Result<dca_plans::Model> {
+  let plan = dca_plans::Entity::find_by_id(plan_id)
+    .filter(dca_plans::Column::UserId.eq(user_id))
+    .one(db)
+    .await
+    .context("Failed to retrieve DCA plan")?
+    .ok_or_else(|| anyhow!("DCA plan {} not found", plan_id))?;
+
+  let now = Utc::now().naive_utc();
+  let mut active_model: dca_plans::ActiveModel = plan.clone().into();
+
+  match (action, plan.status.as_str()) {
+    ("pause", "active") => active_model.status = Set("paused".to_string()),
+    ("resume", "paused") => {
+      active_model.status = Set("active".to_string());
+      // buys missed while paused are skipped rather than run back to back
+      if plan.next_buy_at < now {
+        active_model.next_buy_at = Set(now);
+      }
+    }
+    ("cancel", "active" | "paused") => active_model.status = Set("cancelled".to_string()),
+    ("pause" | "resume" | "cancel", status) => {
+      return Err(anyhow!("Cannot {} a plan that is {}", action, status))
+    }
+    _ => return Err(anyhow!("Unknown action {}", action)),
+  }
+
+  active_model.updated_at = Set(now);
+
+  active_model
+    .update(db)
+    .await
+    .context("Failed to update DCA plan")
+}
+
+// keyed on the slot that was claimed, so every interval gets its own key
+fn dca_idempotency_key(plan: &dca_plans::Model) -> String {
+  format!(
+    "{}{}-{}",
+    DCA_IDEMPOTENCY_KEY_PREFIX,
+    plan.id,
+    plan.next_buy_at.and_utc().timestamp()
+  )
+}
+
+fn dca_plan_of_idempotency_key(idempotency_key: &str) -> Option<i32> {
+  idempotency_key
+    .strip_prefix(DCA_IDEMPOTENCY_KEY_PREFIX)?
+    .split('-')
+    .next()?
+    .parse()
+    .ok()
+}
+
+// Move the plan's next buy forward before buying so overlapping runs can't buy twice
+async fn claim_dca_buy(db: &DatabaseConnection, plan: &dca_plans::Model) -> Result<bool> {
+  let next_buy_at = Utc::now().naive_utc() + Duration::minutes(plan.interval_minutes as i64);
+
+  let result = dca_plans::Entity::update_many()
+    .col_expr(dca_plans::Column::NextBuyAt, Expr::value(next_buy_at))
+    .filter(dca_plans::Column::Id.eq(plan.id))
+    .filter(dca_plans::Column::Status.eq("active"))
+    .filter(dca_plans::Column::NextBuyAt.eq(plan.next_buy_at))
+    .exec(db)
+    .await
+    .context("Failed to claim DCA buy")?;
+
+  Ok(result.rows_affected == 1)
+}
+
+// only an active plan completes, one paused or cancelled meanwhile keeps its status
+async fn complete_dca_plan(db: &DatabaseConnection, plan_id: i32) -> Result<()> {
+  dca_plans::Entity::update_many()
+    .col_expr(dca_plans::Column::Status, Expr::value("completed"))
+    .col_expr(
+      dca_plans::Column::UpdatedAt,
+      Expr::value(Utc::now().naive_utc()),
+    )
+    .filter(dca_plans::Column::Id.eq(plan_id))
+    .filter(dca_plans::Column::Status.eq("active"))
+    .exec(db)
+    .await
+    .context("Failed to complete DCA plan")?;
+
+  Ok(())
+}
+
+async fn run_dca_buy(
+  db: &DatabaseConnection,
+  plan: dca_plans::Model,
+  sol_price: f64,
+) -> Result<()> {
+  let now = Utc::now().naive_utc();
+  let remaining_budget = (plan.total_budget - plan.spent_sol) as f64;
+
+  if now >= plan.ends_at || remaining_budget < MIN_DCA_BUY_SOL {
+    return complete_dca_plan(db, plan.id).await;
+  }
+
+  let plan_id = plan.id;
+  let lock = match acquire_execution_lock(
+    db,
+    vec![dca_plan_lock_key(plan.id), wallet_lock_key(plan.wallet_id)],
+  )
+  .await?
+  {
+    Some(lock) => lock,
+    // the plan is still due, so the next run picks it up
+    None => return Ok(()),
+  };
+
+  let bought = buy_for_dca_plan(db, plan, sol_price).await;
+
+  if let Err(e) = release_execution_lock(db, lock).await {
+    eprintln!("Failed to release lock of DCA plan {}: {:?}", plan_id, e);
+  }
+
+  bought
+}
+
+async fn buy_for_dca_plan(
+  db: &DatabaseConnection,
+  plan: dca_plans::Model,
+  sol_price: f64,
+) -> Result<()> {
+  let now = Utc::now().naive_utc();
+  let remaining_budget = (plan.total_budget - plan.spent_sol) as f64;
+
+  if !claim_dca_buy(db, &plan).await? {
+    return Ok(());
+  }
+
+  let wallet = wallets::Entity::find_by_id(plan.wallet_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve wallet")?
+    .ok_or_else(|| anyhow!("No wallet found for DCA plan {}", plan.id))?;
+
+  let buy_size = (plan.amount_per_buy as f64).min(remaining_budget);
+
+  let idempotency_key = dca_idempotency_key(&plan);
+
+  let transaction_id = match reserve_transaction(
+    db,
+    &idempotency_key,
+    plan.user_id,
+    wallet.id,
+    "So11111111111111111111111111111111111111112",
+    &plan.contract_address,
+  )
+  .await?
+  {
+    Some(transaction_id) => transaction_id,
+    None => return Ok(()),
+  };
+
+  // a failed buy just waits for the next interval
+  let purchase = match execute_buy_trade(
+    plan.user_id,
+    wallet.id,
+    &plan.contract_address,
+    buy_size,
+    SwapKind::Buy,
+    db,
+  )
+  .await
+  {
+    Ok(purchase) => purchase,
+    Err(e) => {
+      fail_reserved_transaction(db, transaction_id, &e).await?;
+      return Err(e);
+    }
+  };
+
+  let transaction_hash = purchase.attempt.transaction_hash.clone();
+  // the guard may have downsized the buy
+  let buy_size = purchase.sol_amount;
+
+  settle_reserved_transaction(
+    db,
+    transaction_id,
+    purchase.attempt,
+    buy_size,
+    buy_size * sol_price,
+  )
+  .await?;
+
+  // counted onto the row as it is now, a plan paused or cancelled meanwhile is left alone
+  dca_plans::Entity::update_many()
+    .col_expr(
+      dca_plans::Column::SpentSol,
+      Expr::col(dca_plans::Column::SpentSol).add(buy_size as f32),
+    )
+    .col_expr(
+      dca_plans::Column::BuysCompleted,
+      Expr::col(dca_plans::Column::BuysCompleted).add(1),
+    )
+    .col_expr(dca_plans::Column::LastBuyAt, Expr::value(now))
+    .col_expr(dca_plans::Column::UpdatedAt, Expr::value(now))
+    .filter(dca_plans::Column::Id.eq(plan.id))
+    .filter(dca_plans::Column::Status.eq("active"))
+    .exec(db)
+    .await
+    .context("Failed to update DCA plan progress")?;
+
+  let plan = dca_plans::Entity::find_by_id(plan.id)
+    .one(db)
+    .await
+    .context("Failed to retrieve DCA plan")?
+    .ok_or_else(|| anyhow!("No DCA plan {}", plan.id))?;
+
+  let spent_sol = plan.spent_sol;
+  let next_buy_at = now + Duration::minutes(plan.interval_minutes as i64);
+  let remaining_budget = (plan.total_budget - spent_sol) as f64;
+
+  if remaining_budget < MIN_DCA_BUY_SOL || next_buy_at >= plan.ends_at {
+    complete_dca_plan(db, plan.id).await?;
+  }
+
+  let message = format!(
+    "DCA plan {}: bought {} SOL of {} ({} of {} SOL spent)\nTransaction Hash: {}",
+    plan.id, buy_size, plan.contract_address, spent_sol, plan.total_budget, transaction_hash
+  );
+  notify_plan_user(db, plan.user_id, &message).await?;
+
+  Ok(())
+}
+
+/// Give a plan back the budget and buy counted for a transaction that failed or expired on
+/// chain, None when the transaction wasn't a buy of a plan
+pub async fn refund_dca_buy(
+  db: &DatabaseConnection,
+  transaction: &onchain_transactions::Model,
+) -> Result<Option<dca_plans::Model>> {
+  let plan_id = match transaction
+    .idempotency_key
+    .as_deref()
+    .and_then(dca_plan_of_idempotency_key)
+  {
+    Some(plan_id) => plan_id,
+    None => return Ok(None),
+  };
+
+  let now = Utc::now().naive_utc();
+  let buy_size = transaction.value_native.unwrap_or(0.0);
+
+  dca_plans::Entity::update_many()
+    .col_expr(
+      dca_plans::Column::SpentSol,
+      Expr::col(dca_plans::Column::SpentSol).sub(buy_size),
+    )
+    .col_expr(
+      dca_plans::Column::BuysCompleted,
+      Expr::col(dca_plans::Column::BuysCompleted).sub(1),
+    )
+    .col_expr(dca_plans::Column::UpdatedAt, Expr::value(now))
+    .filter(dca_plans::Column::Id.eq(plan_id))
+    .filter(dca_plans::Column::BuysCompleted.gt(0))
+    .exec(db)
+    .await
+    .context("Failed to refund DCA buy")?;
+
+  // a plan completed by spending its budget has some of it back until it ends
+  dca_plans::Entity::update_many()
+    .col_expr(dca_plans::Column::Status, Expr::value("active"))
+    .filter(dca_plans::Column::Id.eq(plan_id))
+    .filter(dca_plans::Column::Status.eq("completed"))
+    .filter(dca_plans::Column::EndsAt.gt(now))
+    .exec(db)
+    .await
+    .context("Failed to reopen DCA plan")?;
+
+  dca_plans::Entity::find_by_id(plan_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve DCA plan")
+}
+
+async fn notify_plan_user(db: &DatabaseConnection, user_id: i32, message: &str) -> Result<()> {
+  let user = users::Entity::find_by_id(user_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve user")?;
+
+  if let Some(tg_id) = user.and_then(|user| user.tg_id.parse::<i64>().ok()) {
+    if let Err(e) = notify_user_by_telegram(tg_id, message).await {
+      eprintln!("Error notifying user {}: {}", tg_id, e);
+    }
+  }
+
+  Ok(())
+}
+
+/// Run a buy for every active plan that is due
+pub async fn run_due_dca_plans(db: &DatabaseConnection) -> Result<()> {
+  let due_plans = dca_plans::Entity::find()
+    .filter(dca_plans::Column::Status.eq("active"))
+    .filter(dca_plans::Column::NextBuyAt.lte(Utc::now().naive_utc()))
+    .all(db)
+    .await
+    .context("Failed to retrieve due DCA plans")?;
+
+  if due_plans.is_empty() {
+    return Ok(());
+  }
+
+  // only used to value the recorded transactions
+  let sol_price = fetch_token_price("So11111111111111111111111111111111111111112")
+    .await
+    .unwrap_or(0.0);
+
+  let mut tasks = vec![];
+
+  for plan in due_plans {
+    let database = db.clone();
+    let task = tokio::spawn(async move {
+      let plan_id = plan.id;
+      let user_id = plan.user_id;
+      if let Err(e) = run_dca_buy(&database, plan, sol_price).await {
+        eprintln!("Failed to run DCA plan {}: {:?}", plan_id, e);
+
+        let message = format!("DCA plan {}: buy was not made: {}", plan_id, e);
+        if let Err(e) = notify_plan_user(&database, user_id, &message).await {
+          eprintln!("Error notifying user {}: {:?}", user_id, e);
+        }
+      }
+    });
+    tasks.push(task);
+  }
+
+  futures::future::join_all(tasks).await;
+
+  Ok(())
+}
+
+pub fn format_dca_plan(plan: &dca_plans::Model) -> String {
+  format!(
+    "#{} {} [{}]\n{} SOL every {} min, {}/{} SOL spent over {} buys\nNext buy: {}\nEnds: {}",
+    plan.id,
+    plan.contract_address,
+    plan.status,
+    plan.amount_per_buy,
+    plan.interval_minutes,
+    plan.spent_sol,
+    plan.total_budget,
+    plan.buys_completed,
+    plan.next_buy_at,
+    plan.ends_at
+  )
+}
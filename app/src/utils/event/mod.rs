@@ -1,2 +1,3 @@
 pub mod price;
+pub mod pump_fun;
 pub mod raydium;
@@ -0,0 +1,95 @@
+use entity::raydium_token_launches;
+use sea_orm::{EntityTrait, Set};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  db,
+  integrations::pump_fun::{fetch_bonding_curve, PUMP_FUN_TOKEN_DECIMALS},
+  utils::price::solana::fetch_token_price,
+};
+
+use super::raydium::{buy_token_on_launch, notify_launch_class_users};
+
+// Tokens created on pump.fun are reported before they have a Raydium pool. While their bonding
+// curve is active they are recorded and bought as curve launches, swaps route through the
+// pump.fun program until the curve completes.
+
+pub const CURVE_LAUNCH_CLASS: &str = "curve_launch";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PumpFunTokenEvent {
+  mint: String,
+  creator: String,
+  timestamp: String,
+}
+
+pub async fn handle_curve_token_created_event(data: PumpFunTokenEvent) {
+  let db = db::connect_db()
+    .await
+    .expect("Failed to connect to the database");
+
+  let contract_address = &data.mint;
+
+  let curve = match fetch_bonding_curve(contract_address).await {
+    Ok(Some(curve)) if !curve.complete => curve,
+    Ok(Some(_)) => {
+      println!(
+        "pump.fun token {} already left its bonding curve.",
+        contract_address
+      );
+      return;
+    }
+    Ok(None) => {
+      println!("{} has no pump.fun bonding curve.", contract_address);
+      return;
+    }
+    Err(e) => {
+      eprintln!("An error occured: \n {:?}", e);
+      return;
+    }
+  };
+
+  let sol_price = match fetch_token_price("So11111111111111111111111111111111111111112").await {
+    Ok(price) => price,
+    Err(e) => {
+      eprintln!("Failed to fetch SOL price: {:?}", e);
+      return;
+    }
+  };
+
+  let curve_sol_liquidity = curve.sol_liquidity();
+  let curve_sol_liquidity_usd = curve_sol_liquidity * sol_price;
+  let launch_price_usd = curve.price_in_sol() * sol_price;
+
+  let curve_launch = raydium_token_launches::ActiveModel {
+    contract_address: Set(contract_address.clone()),
+    creator_address: Set(data.creator),
+    evaluation: Set(Some("track".to_string())),
+    launch_class: Set(Some(CURVE_LAUNCH_CLASS.to_string())),
+    launch_liquidity: Set(
+      (curve.real_token_reserves as f64 / 10f64.powi(PUMP_FUN_TOKEN_DECIMALS)) as f32,
+    ),
+    launch_liquidity_usd: Set(curve_sol_liquidity_usd as f32),
+    launch_price_usd: Set(Some(launch_price_usd as f32)),
+    ..Default::default()
+  };
+  let _ = raydium_token_launches::Entity::insert(curve_launch)
+    .exec(&db)
+    .await
+    .map_err(|e| e.to_string());
+
+  let notification_message = format!(
+    "pump.fun token {} launched on its bonding curve with {} liquidity (${}) at ${}",
+    contract_address, curve_sol_liquidity, curve_sol_liquidity_usd, launch_price_usd
+  );
+
+  match notify_launch_class_users(CURVE_LAUNCH_CLASS, notification_message, &db).await {
+    Ok(_) => println!("notified users of curve launch"),
+    Err(e) => eprintln!("An error occured: \n {:?}", e),
+  };
+
+  match buy_token_on_launch(contract_address, CURVE_LAUNCH_CLASS, db).await {
+    Ok(_) => println!("attempted buy of curve launch"),
+    Err(e) => eprintln!("An error occured: \n {:?}", e),
+  }
+}
@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Context, Result};
-use entity::{raydium_token_launches, users, wallets};
+use entity::{raydium_token_launches, user_settings, users, wallets};
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -12,9 +12,19 @@ use crate::{
   db,
   integrations::dexscreener::{self},
   utils::{
+    misc::is_pump_fun_token,
     notifications::{notify_user_by_telegram, notify_users},
     price::solana::fetch_token_price,
-    swap::solana::{create_stop_loss_order, execute_buy_trade, record_transaction, TradeParams},
+    settings::{find_or_create_user_settings, follows_launch_class, snipes_launch_class},
+    swap::{
+      lock::{acquire_execution_lock, release_execution_lock, wallet_lock_key},
+      paper::is_paper_wallet,
+      slippage::SwapKind,
+      solana::{
+        create_stop_loss_order, execute_buy_trade, fail_reserved_transaction, reserve_transaction,
+        settle_reserved_transaction, TradeParams,
+      },
+    },
     wallets::solana::{find_or_create_token, get_token_details, get_wallet_sol_balance},
   },
 };
@@ -33,35 +43,72 @@ pub struct RaydiumTokenEvent {
   quote_info: LPInfo,
 }
 
+/// Pool SOL liquidity bounds that sort a launch into a launch class
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidityTiers {
+  pub lower_limit: f64,
+  pub mid_limit: f64,
+  pub normal_limit: f64,
+  pub pro_limit: f64,
+}
+
+impl LiquidityTiers {
+  pub fn from_env() -> Self {
+    let lower_limit: f64 = env::var("LOWER_LIQUIDITY_LAUNCH_LIMIT")
+      .unwrap_or_else(|_| "30.0".to_string())
+      .parse()
+      .expect("LOWER_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
+
+    let mid_limit: f64 = env::var("MID_LIQUIDITY_LAUNCH_LIMIT")
+      .unwrap_or_else(|_| "70.0".to_string())
+      .parse()
+      .expect("MID_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
+
+    let normal_limit: f64 = env::var("NORMAL_LIQUIDITY_LAUNCH_LIMIT")
+      .unwrap_or_else(|_| "100.0".to_string())
+      .parse()
+      .expect("NORMAL_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
+
+    let pro_limit: f64 = env::var("PRO_LIQUIDITY_LAUNCH_LIMIT")
+      .unwrap_or_else(|_| "250.0".to_string())
+      .parse()
+      .expect("PRO_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
+
+    LiquidityTiers {
+      lower_limit,
+      mid_limit,
+      normal_limit,
+      pro_limit,
+    }
+  }
+
+  pub fn classify(&self, pool_sol_liquidity: f64) -> &'static str {
+    if pool_sol_liquidity <= self.lower_limit {
+      "below_limit"
+    } else if pool_sol_liquidity < self.mid_limit {
+      "lower_limit"
+    } else if pool_sol_liquidity < self.normal_limit {
+      "mid_launch"
+    } else if pool_sol_liquidity < self.pro_limit {
+      "pro_launch"
+    } else {
+      "crazy_launch"
+    }
+  }
+}
+
 pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
   let db = db::connect_db()
     .await
     .expect("Failed to connect to the database");
 
-  let lower_limit: f64 = env::var("LOWER_LIQUIDITY_LAUNCH_LIMIT")
-    .unwrap_or_else(|_| "30.0".to_string())
-    .parse()
-    .expect("LOWER_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
-
-  let mid_limit: f64 = env::var("MID_LIQUIDITY_LAUNCH_LIMIT")
-    .unwrap_or_else(|_| "70.0".to_string())
-    .parse()
-    .expect("MID_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
-
-  let normal_limit: f64 = env::var("NORMAL_LIQUIDITY_LAUNCH_LIMIT")
-    .unwrap_or_else(|_| "100.0".to_string())
-    .parse()
-    .expect("NORMAL_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
-
-  let pro_limit: f64 = env::var("PRO_LIQUIDITY_LAUNCH_LIMIT")
-    .unwrap_or_else(|_| "250.0".to_string())
-    .parse()
-    .expect("PRO_LIQUIDITY_LAUNCH_LIMIT must be a valid float");
+  let tiers = LiquidityTiers::from_env();
 
   let pool_sol_liquidity = data.quote_info.lp_amount;
   let sol_price = fetch_token_price(&data.quote_info.address).await.unwrap();
   let pool_sol_liquidity_usd = sol_price * pool_sol_liquidity;
   let contract_address = &data.base_info.address;
+  let launch_class = tiers.classify(pool_sol_liquidity);
 
   // Wait for 5 seconds before calling dexscreener becuse dexscreener may not have registered the launch
   // sleep(Duration::from_secs(5)).await;
@@ -72,7 +119,7 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
       Err(_e) => None,
     };
 
-  if pool_sol_liquidity <= lower_limit {
+  if launch_class == "below_limit" {
     println!("loser liquidity.");
 
     let mut loser_launch = raydium_token_launches::ActiveModel {
@@ -107,7 +154,7 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
       .exec(&db)
       .await
       .map_err(|e| e.to_string());
-  } else if pool_sol_liquidity > lower_limit && pool_sol_liquidity < mid_limit {
+  } else if launch_class == "lower_limit" {
     let mut lower_limit_launch = raydium_token_launches::ActiveModel {
       contract_address: Set(contract_address.clone()),
       creator_address: Set(data.creator),
@@ -140,7 +187,7 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
       .exec(&db)
       .await
       .map_err(|e| e.to_string());
-  } else if pool_sol_liquidity >= mid_limit && pool_sol_liquidity < normal_limit {
+  } else if launch_class == "mid_launch" {
     println!("processing mid launch.");
 
     let mut mid_limit_launch = raydium_token_launches::ActiveModel {
@@ -176,8 +223,25 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
       .await
       .map_err(|e| e.to_string());
 
-    //TODO:  Check if it's a pump.fun if yes buy
-  } else if pool_sol_liquidity >= normal_limit && pool_sol_liquidity < pro_limit {
+    // pump.fun tokens graduate to Raydium with about 79 SOL, which lands them in this class.
+    // Their curve stage comes in through the pump.fun token event as a curve launch.
+    if is_pump_fun_token(contract_address).await {
+      let notification_message = format!(
+        "pump.fun token {} graduated to Raydium with {} liquidity (${})",
+        contract_address, pool_sol_liquidity, pool_sol_liquidity_usd
+      );
+
+      match notify_launch_class_users("mid_launch", notification_message, &db).await {
+        Ok(_) => println!("notified users of pump.fun graduation"),
+        Err(e) => eprintln!("An error occured: \n {:?}", e),
+      };
+    }
+
+    match buy_token_on_launch(contract_address, "mid_launch", db).await {
+      Ok(_) => println!("attempted buy of mid launch"),
+      Err(e) => eprintln!("An error occured: \n {:?}", e),
+    }
+  } else if launch_class == "pro_launch" {
     println!("Liquidity is between the normal limit and pro limit.");
 
     let mut good_launch = raydium_token_launches::ActiveModel {
@@ -220,7 +284,12 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
       Ok(_) => println!("notified users of crazy launch"),
       Err(e) => eprintln!("An error occured: \n {:?}", e),
     };
-  } else if pool_sol_liquidity >= pro_limit {
+
+    match buy_token_on_launch(contract_address, "pro_launch", db).await {
+      Ok(_) => println!("attempted buy of pro launch"),
+      Err(e) => eprintln!("An error occured: \n {:?}", e),
+    }
+  } else if launch_class == "crazy_launch" {
     let mut crazy_launch = raydium_token_launches::ActiveModel {
       contract_address: Set(contract_address.clone()),
       creator_address: Set(data.creator),
@@ -263,7 +332,7 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
       Err(e) => eprintln!("An error occured: \n {:?}", e),
     };
 
-    match buy_token_on_launch(&contract_address, db).await {
+    match buy_token_on_launch(contract_address, "crazy_launch", db).await {
       Ok(_) => println!("attempted buy of crazy"),
       Err(e) => eprintln!("An error occured: \n {:?}", e),
     }
@@ -272,22 +341,71 @@ pub async fn handle_token_created_event(data: RaydiumTokenEvent) {
   // TODO: let is_boosted_token = /* Your logic to determine if the token is boosted */;
 }
 
-// at the moment we do not have a system for actually storing the settings of each user so all users with TG get coins as the launch 😬
-pub async fn buy_token_on_launch(ca: &str, db: DatabaseConnection) -> Result<()> {
+/// Notify the users following a launch class of a launch in it. Users who never saved their
+/// settings follow the default class only, which everyone is notified of anyway.
+pub async fn notify_launch_class_users(
+  launch_class: &str,
+  msg: String,
+  db: &DatabaseConnection,
+) -> Result<()> {
+  let users = users::Entity::find()
+    .find_also_related(user_settings::Entity)
+    .filter(users::Column::TgId.is_not_null())
+    .all(db)
+    .await
+    .context("Database error")?;
+
+  for (user, settings) in users {
+    if !settings.map_or(false, |settings| {
+      follows_launch_class(&settings, launch_class)
+    }) {
+      continue;
+    }
+
+    match user.tg_id.parse::<i64>() {
+      Ok(tg_id) => {
+        if let Err(e) = notify_user_by_telegram(tg_id, &msg).await {
+          eprintln!("Error notifying user {}: {}", tg_id, e);
+        }
+      }
+      Err(_) => eprintln!("Skipping user with invalid tg_id: {}", user.tg_id),
+    }
+  }
+
+  Ok(())
+}
+
+pub async fn buy_token_on_launch(
+  ca: &str,
+  launch_class: &str,
+  db: DatabaseConnection,
+) -> Result<()> {
   let users = users::Entity::find()
     .filter(users::Column::TgId.is_not_null())
     .all(&db) // Dereferencing Arc to get a reference to DatabaseConnection
     .await
     .context("Database error")?;
 
+  let mut snipers = vec![];
+  for user in users {
+    let settings = find_or_create_user_settings(&db, user.id).await?;
+    if snipes_launch_class(&settings, launch_class) {
+      snipers.push((user, settings));
+    }
+  }
+
+  if snipers.is_empty() {
+    return Ok(());
+  }
+
   let token = get_token_details(ca)
     .await
     .context("Failed to get token details:")?;
 
   let token_id = find_or_create_token(&db, &token, ca).await?;
   let mut tasks = vec![];
-  for user in users {
-    let trade_params = fetch_trade_parameters(ca).await?;
+  for (user, settings) in snipers {
+    let trade_params = fetch_trade_parameters(ca, &settings).await?;
     let contract_address = ca.to_string();
     let db = db.clone();
     let task = task::spawn(async move {
@@ -307,7 +425,7 @@ pub async fn buy_token_on_launch(ca: &str, db: DatabaseConnection) -> Result<()>
   Ok(())
 }
 
-async fn fetch_trade_parameters(ca: &str) -> Result<TradeParams> {
+async fn fetch_trade_parameters(ca: &str, settings: &user_settings::Model) -> Result<TradeParams> {
   let sol_price = fetch_token_price("So11111111111111111111111111111111111111112")
     .await
     .map_err(|e| anyhow!("Failed to fetch SOL price: {}", e))?;
@@ -316,27 +434,13 @@ async fn fetch_trade_parameters(ca: &str) -> Result<TradeParams> {
     .await
     .map_err(|e| anyhow!("Failed to fetch token price: {}", e))?;
 
-  let buy_size_percentage = env::var("BUY_SIZE")
-    .unwrap_or_else(|_| "10.0".to_string())
-    .parse()
-    .map_err(|e| anyhow!("Invalid BUY_SIZE: {}", e))?;
-
-  let launch_size_lower_limit = env::var("LAUNCH_BUY_SIZE_LOWER_LIMIT")
-    .unwrap_or_else(|_| "5.0".to_string())
-    .parse()
-    .map_err(|e| anyhow!("Invalid LAUNCH_BUY_SIZE_LOWER_LIMIT: {}", e))?;
-
-  let launch_stop_loss = env::var("LAUNCH_STOP_LOSS_PERCENTAGE")
-    .ok()
-    .and_then(|value| value.parse::<f32>().ok())
-    .unwrap_or(20.0);
-
   Ok(TradeParams {
     sol_price,
     token_price,
-    buy_size_percentage,
-    launch_size_lower_limit,
-    launch_stop_loss,
+    buy_size_percentage: settings.buy_size_percentage as f64,
+    min_sol_per_trade: settings.min_sol_per_trade as f64,
+    max_sol_per_trade: settings.max_sol_per_trade as f64,
+    launch_stop_loss: settings.launch_stop_loss_percentage,
   })
 }
 
@@ -350,33 +454,108 @@ async fn process_user_trade(
   // Get user's wallet
   let wallet = wallets::Entity::find()
     .filter(wallets::Column::UserId.eq(user.id))
+    .filter(wallets::Column::WatchOnly.eq(false))
     .one(db)
     .await?
     .ok_or_else(|| anyhow!("No wallet found for user {}", user.id))?;
 
-  let wallet_sol_balance = get_wallet_sol_balance(&wallet.address)
-    .await
-    .map_err(|e| anyhow!("Failed to get wallet balance: {}", e))?;
+  // the buy is sized on the wallet's SOL, which a swap already going out of it could spend
+  let lock = match acquire_execution_lock(db, vec![wallet_lock_key(wallet.id)]).await? {
+    Some(lock) => lock,
+    None => {
+      if let Ok(tg_id_parsed) = user.tg_id.parse::<i64>() {
+        let message = format!(
+          "Token {} was not bought at launch: another swap from your wallet is in progress",
+          ca
+        );
+
+        if let Err(e) = notify_user_by_telegram(tg_id_parsed, &message).await {
+          eprintln!("Error notifying user {}: {}", tg_id_parsed, e);
+        }
+      }
+
+      return Ok(());
+    }
+  };
 
-  let wallet_sol_value_usd = params.sol_price * wallet_sol_balance;
+  let bought = buy_launch_for_wallet(&user, &wallet, ca, token_id, db, params).await;
 
-  if wallet_sol_value_usd < params.launch_size_lower_limit {
-    return Ok(());
+  if let Err(e) = release_execution_lock(db, lock).await {
+    eprintln!("Failed to release lock of wallet {}: {:?}", wallet.id, e);
   }
 
-  let buy_size = wallet_sol_balance * (params.buy_size_percentage / 100.0);
-  let buy_size_usd = buy_size * params.sol_price;
+  bought
+}
+
+async fn buy_launch_for_wallet(
+  user: &users::Model,
+  wallet: &wallets::Model,
+  ca: &str,
+  token_id: i32,
+  db: &DatabaseConnection,
+  params: &TradeParams,
+) -> Result<()> {
+  // paper wallets hold no SOL, so they buy the largest size a real trade could have
+  let buy_size = if is_paper_wallet(db, wallet).await? {
+    params.max_sol_per_trade
+  } else {
+    let wallet_sol_balance = get_wallet_sol_balance(&wallet.address)
+      .await
+      .map_err(|e| anyhow!("Failed to get wallet balance: {}", e))?;
+
+    (wallet_sol_balance * (params.buy_size_percentage / 100.0)).min(params.max_sol_per_trade)
+  };
 
-  if buy_size < params.launch_size_lower_limit {
-    // create a stop loss with an arbitrary amount to track the sell price
+  if buy_size < params.min_sol_per_trade {
     return Ok(());
   }
 
-  let swap_result = execute_buy_trade(user.id, wallet.id, ca, buy_size, &wallet.address, db).await;
+  // a launch is bought once per wallet, a repeated launch event stops here
+  let transaction_id = match reserve_transaction(
+    db,
+    &format!("launch-{}-{}", wallet.id, ca),
+    user.id,
+    wallet.id,
+    "So11111111111111111111111111111111111111112",
+    ca,
+  )
+  .await?
+  {
+    Some(transaction_id) => transaction_id,
+    None => return Ok(()),
+  };
+
+  let swap_result =
+    execute_buy_trade(user.id, wallet.id, ca, buy_size, SwapKind::LaunchBuy, db).await;
+
+  let buy_size_usd = match swap_result {
+    Ok(purchase) => {
+      let buy_size_usd = purchase.sol_amount * params.sol_price;
+      settle_reserved_transaction(
+        db,
+        transaction_id,
+        purchase.attempt,
+        purchase.sol_amount,
+        buy_size_usd,
+      )
+      .await?;
+      buy_size_usd
+    }
+    Err(e) => {
+      eprintln!("Failed to buy {} for user {}: {:?}", ca, user.id, e);
+      fail_reserved_transaction(db, transaction_id, &e).await?;
 
-  if let Ok(attempt) = swap_result {
-    record_transaction(db, user.id, wallet.id, ca, attempt, buy_size, buy_size_usd).await?;
-  }
+      if let Ok(tg_id_parsed) = user.tg_id.parse::<i64>() {
+        let message = format!("Token {} was not bought at launch: {}", ca, e);
+
+        if let Err(e) = notify_user_by_telegram(tg_id_parsed, &message).await {
+          eprintln!("Error notifying user {}: {}", tg_id_parsed, e);
+        }
+      }
+
+      return Ok(());
+    }
+  };
 
   create_stop_loss_order(db, user.id, wallet.id, token_id, ca, params).await?;
 
@@ -2,11 +2,16 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::integrations::pump_fun::fetch_bonding_curve;
+
 pub fn generate_uuid() -> String {
   Uuid::new_v4().to_string()
 }
 
-// pub fn is_pump_fun_token(contract_address: String) -> bool {}
+/// Whether a token was launched on pump.fun, on its bonding curve or graduated from it
+pub async fn is_pump_fun_token(contract_address: &str) -> bool {
+  matches!(fetch_bonding_curve(contract_address).await, Ok(Some(_)))
+}
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub enum PriceTrend {
@@ -1,9 +1,15 @@
 pub mod auth;
+pub mod backtest;
 pub mod cache;
+pub mod copy_trade;
+pub mod dca;
 pub mod encryption;
 pub mod event;
 pub mod misc;
 pub mod notifications;
+pub mod orders;
+pub mod positions;
 pub mod price;
+pub mod settings;
 pub mod swap;
 pub mod wallets;
@@ -0,0 +1,308 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use entity::trade_orders;
+use sea_orm::{
+  sea_query::Expr, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+
+use super::trailing_stop::{high_water_mark, trailing_target_price};
+
+// Orders can have their target moved until the price jobs pick them up. The price and the
+// percentage of an order describe the same target from its reference price, so both are
+// rewritten together in the direction its strategy measures.
+
+// statuses an order can still be edited in
+const EDITABLE_ORDER_STATUSES: [&str; 2] = ["pending", "active"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderTarget {
+  Price(f64),
+  Percentage(f32),
+}
+
+impl OrderTarget {
+  /// A value ending in % is a percentage from the reference price, anything else a price
+  pub fn parse(value: &str) -> Result<Self> {
+    match value.strip_suffix('%') {
+      Some(percentage) => percentage
+        .parse::<f32>()
+        .ok()
+        .filter(|percentage| *percentage > 0.0)
+        .map(OrderTarget::Percentage)
+        .ok_or_else(|| anyhow!("Invalid percentage {}, use one such as 20%", value)),
+      None => value
+        .parse::<f64>()
+        .ok()
+        .filter(|price| *price > 0.0)
+        .map(OrderTarget::Price)
+        .ok_or_else(|| {
+          anyhow!(
+            "Invalid price {}, use a price or a percentage such as 20%",
+            value
+          )
+        }),
+    }
+  }
+}
+
+/// Orders of a user that are still waiting on their price, oldest first
+pub async fn find_user_editable_orders(
+  db: &DatabaseConnection,
+  user_id: i32,
+) -> Result<Vec<trade_orders::Model>> {
+  trade_orders::Entity::find()
+    .filter(trade_orders::Column::UserId.eq(user_id))
+    .filter(trade_orders::Column::Status.is_in(EDITABLE_ORDER_STATUSES))
+    .order_by_asc(trade_orders::Column::Id)
+    .all(db)
+    .await
+    .context("Failed to retrieve orders")
+}
+
+pub async fn find_user_order(
+  db: &DatabaseConnection,
+  user_id: i32,
+  order_id: i32,
+) -> Result<trade_orders::Model> {
+  trade_orders::Entity::find_by_id(order_id)
+    .filter(trade_orders::Column::UserId.eq(user_id))
+    .one(db)
+    .await
+    .context("Failed to retrieve order")?
+    .ok_or_else(|| anyhow!("No order {} found", order_id))
+}
+
+// target price and percentage of an order moved to `target`
+fn resolve_target(order: &trade_orders::Model, target: OrderTarget) -> Result<(f64, f32)> {
+  let reference_price = order.reference_price as f64;
+
+  match (order.strategy.as_str(), target) {
+    // a drop below the reference
+    ("stop_loss" | "launch_stop_loss" | "limit_buy", OrderTarget::Percentage(percentage))
+      if percentage < 100.0 =>
+    {
+      Ok((
+        reference_price * (1.0 - percentage as f64 / 100.0),
+        percentage,
+      ))
+    }
+    ("stop_loss" | "launch_stop_loss" | "limit_buy", OrderTarget::Price(price))
+      if reference_price > 0.0 =>
+    {
+      Ok((price, ((1.0 - price / reference_price) * 100.0) as f32))
+    }
+    // a rise above the reference
+    ("take_profit", OrderTarget::Percentage(percentage)) => Ok((
+      reference_price * (1.0 + percentage as f64 / 100.0),
+      percentage,
+    )),
+    ("take_profit", OrderTarget::Price(price)) if reference_price > 0.0 => {
+      Ok((price, ((price / reference_price - 1.0) * 100.0) as f32))
+    }
+    // the trail follows the peak, only its width can change
+    ("trailing_stop", OrderTarget::Percentage(percentage)) if percentage < 100.0 => Ok((
+      trailing_target_price(high_water_mark(order), percentage),
+      percentage,
+    )),
+    ("trailing_stop", OrderTarget::Price(_)) => Err(anyhow!(
+      "A trailing stop follows the price, set its trail as a percentage such as 15%"
+    )),
+    ("stop_loss" | "launch_stop_loss" | "limit_buy" | "take_profit" | "trailing_stop", _) => {
+      Err(anyhow!("Invalid target for a {}", order.strategy))
+    }
+    _ => Err(anyhow!(
+      "The target of a {} can't be edited",
+      order.strategy
+    )),
+  }
+}
+
+/// Move the target of an order that hasn't been picked up yet, returning the updated order
+pub async fn update_order_target(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  target: OrderTarget,
+) -> Result<trade_orders::Model> {
+  if !EDITABLE_ORDER_STATUSES.contains(&order.status.as_str()) {
+    return Err(anyhow!(
+      "Order {} is {} and can no longer be edited",
+      order.id,
+      order.status
+    ));
+  }
+
+  let (target_price, target_percentage) = resolve_target(order, target)?;
+
+  // only while the order is still in a status it can be edited in
+  let updated = trade_orders::Entity::update_many()
+    .col_expr(
+      trade_orders::Column::TargetPrice,
+      Expr::value(target_price as f32),
+    )
+    .col_expr(
+      trade_orders::Column::TargetPercentage,
+      Expr::value(target_percentage),
+    )
+    .col_expr(
+      trade_orders::Column::UpdatedAt,
+      Expr::value(Utc::now().naive_utc()),
+    )
+    .filter(trade_orders::Column::Id.eq(order.id))
+    .filter(trade_orders::Column::Status.is_in(EDITABLE_ORDER_STATUSES))
+    .exec(db)
+    .await
+    .context("Failed to update order target")?;
+
+  if updated.rows_affected == 0 {
+    return Err(anyhow!(
+      "Order {} was picked up before it could be edited",
+      order.id
+    ));
+  }
+
+  trade_orders::Entity::find_by_id(order.id)
+    .one(db)
+    .await
+    .context("Failed to retrieve order")?
+    .ok_or_else(|| anyhow!("No order {}", order.id))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn order(strategy: &str, reference_price: f32) -> trade_orders::Model {
+    let now = Utc::now().naive_utc();
+
+    trade_orders::Model {
+      id: 1,
+      user_id: 1,
+      wallet_id: 1,
+      token_id: 1,
+      reference_price,
+      target_price: 0.0,
+      target_percentage: 0.0,
+      contract_address: "token".to_string(),
+      strategy: strategy.to_string(),
+      status: "active".to_string(),
+      created_by: "user".to_string(),
+      metadata: None,
+      parent_id: None,
+      sell_fraction: None,
+      triggered_at: None,
+      filled_at: None,
+      failed_at: None,
+      cancelled_at: None,
+      expired_at: None,
+      expires_at: None,
+      failure_reason: None,
+      filled_transaction_id: None,
+      created_at: now,
+      updated_at: now,
+    }
+  }
+
+  fn assert_target(resolved: Result<(f64, f32)>, price: f64, percentage: f32) {
+    let (target_price, target_percentage) = resolved.unwrap();
+
+    assert!((target_price - price).abs() < 1e-6, "{}", target_price);
+    assert!(
+      (target_percentage - percentage).abs() < 1e-4,
+      "{}",
+      target_percentage
+    );
+  }
+
+  #[test]
+  fn targets_parse_as_prices_or_percentages() {
+    assert_eq!(
+      OrderTarget::parse("20%").unwrap(),
+      OrderTarget::Percentage(20.0)
+    );
+    assert_eq!(OrderTarget::parse("0.5").unwrap(), OrderTarget::Price(0.5));
+  }
+
+  #[test]
+  fn non_positive_and_malformed_targets_are_refused() {
+    for value in ["0", "-1", "0%", "-5%", "abc", "abc%", "%"] {
+      assert!(OrderTarget::parse(value).is_err(), "{}", value);
+    }
+  }
+
+  #[test]
+  fn stop_losses_move_below_the_reference() {
+    for strategy in ["stop_loss", "launch_stop_loss", "limit_buy"] {
+      let order = order(strategy, 2.0);
+
+      assert_target(
+        resolve_target(&order, OrderTarget::Percentage(25.0)),
+        1.5,
+        25.0,
+      );
+      assert_target(resolve_target(&order, OrderTarget::Price(1.5)), 1.5, 25.0);
+    }
+  }
+
+  #[test]
+  fn take_profits_move_above_the_reference() {
+    let order = order("take_profit", 2.0);
+
+    assert_target(
+      resolve_target(&order, OrderTarget::Percentage(50.0)),
+      3.0,
+      50.0,
+    );
+    assert_target(resolve_target(&order, OrderTarget::Price(3.0)), 3.0, 50.0);
+    // a take profit can be more than twice the reference
+    assert_target(
+      resolve_target(&order, OrderTarget::Percentage(150.0)),
+      5.0,
+      150.0,
+    );
+  }
+
+  #[test]
+  fn drops_of_100_percent_or_more_are_refused() {
+    for strategy in [
+      "stop_loss",
+      "launch_stop_loss",
+      "limit_buy",
+      "trailing_stop",
+    ] {
+      let order = order(strategy, 2.0);
+
+      assert!(resolve_target(&order, OrderTarget::Percentage(100.0)).is_err());
+      assert!(resolve_target(&order, OrderTarget::Percentage(150.0)).is_err());
+    }
+  }
+
+  #[test]
+  fn prices_need_a_reference_to_measure_from() {
+    for strategy in ["stop_loss", "launch_stop_loss", "limit_buy", "take_profit"] {
+      let order = order(strategy, 0.0);
+
+      assert!(resolve_target(&order, OrderTarget::Price(1.0)).is_err());
+    }
+  }
+
+  #[test]
+  fn trailing_stops_trail_from_their_peak() {
+    let mut order = order("trailing_stop", 2.0);
+    order.metadata = Some(json!({ "high_water_mark": 4.0 }));
+
+    assert_target(
+      resolve_target(&order, OrderTarget::Percentage(10.0)),
+      3.6,
+      10.0,
+    );
+    assert!(resolve_target(&order, OrderTarget::Price(3.0)).is_err());
+  }
+
+  #[test]
+  fn other_strategies_cant_be_edited() {
+    let order = order("ladder", 2.0);
+
+    assert!(resolve_target(&order, OrderTarget::Percentage(10.0)).is_err());
+  }
+}
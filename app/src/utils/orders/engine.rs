@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Context, Result};
+use entity::{trade_orders, users, wallets};
+use futures::future::join_all;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use super::{
+  lifecycle::cancel_order,
+  limit_buy::execute_limit_buy,
+  sell::execute_order_sell,
+  strategy::{find_strategy, strategy_names, PriceSnapshot, Signal},
+};
+use crate::utils::{
+  notifications::notify_user_by_telegram, price::solana::fetch_token_price,
+  swap::paper::wallet_token_balance,
+};
+
+async fn notify_order_user(user: &users::Model, message: &str) {
+  if let Ok(tg_id) = user.tg_id.parse::<i64>() {
+    if let Err(e) = notify_user_by_telegram(tg_id, message).await {
+      eprintln!("Error notifying user {}: {}", tg_id, e);
+    }
+  }
+}
+
+async fn execute_sell_signal(
+  db: &DatabaseConnection,
+  order: trade_orders::Model,
+  user: users::Model,
+  latest_price: f64,
+) -> Result<()> {
+  let wallet = wallets::Entity::find_by_id(order.wallet_id)
+    .one(db)
+    .await
+    .context("Failed to get wallet")?
+    .ok_or_else(|| anyhow!("No wallet found for order {}", order.id))?;
+
+  let balance = wallet_token_balance(db, &wallet, &order.contract_address).await?;
+
+  if balance.amount <= 0.0 {
+    // Cancel the order so the next evaluation doesn't report the missing balance again
+    if cancel_order(db, &order, Some("No balance left to sell")).await? {
+      let message = format!(
+        "{}: Token {} has no balance left to sell at {}; entry price {}",
+        order.strategy, order.contract_address, latest_price, order.reference_price
+      );
+      notify_order_user(&user, &message).await;
+    }
+    return Ok(());
+  }
+
+  let sale = match execute_order_sell(
+    db,
+    &order,
+    user.id,
+    &wallet,
+    balance.ui_amount,
+    latest_price,
+  )
+  .await?
+  {
+    Some(sale) => sale,
+    None => return Ok(()),
+  };
+
+  let message = format!(
+    "{}: Token {} was sold at {}, for ${:.2}. Entry Price: {}",
+    order.strategy,
+    order.contract_address,
+    latest_price,
+    latest_price * sale.sold_amount,
+    order.reference_price
+  );
+  notify_order_user(&user, &message).await;
+
+  Ok(())
+}
+
+/// Evaluate every active order on the snapshot's token against its strategy and act on the
+/// signals. Orders whose strategy isn't registered, like ladder parents, are left alone.
+pub async fn evaluate_orders(db: &DatabaseConnection, snapshot: &PriceSnapshot) -> Result<()> {
+  let orders = trade_orders::Entity::find()
+    .filter(trade_orders::Column::ContractAddress.eq(&snapshot.contract_address))
+    .filter(trade_orders::Column::Strategy.is_in(strategy_names()))
+    .filter(trade_orders::Column::Status.eq("active"))
+    .find_with_related(users::Entity)
+    .all(db)
+    .await
+    .context("Failed to retrieve active orders")?;
+
+  let mut sol_price = None;
+  let mut tasks = vec![];
+
+  for (order, related_users) in orders {
+    let signal = match find_strategy(&order.strategy) {
+      Some(strategy) => strategy.evaluate(&order, snapshot),
+      None => Signal::Hold,
+    };
+
+    if signal == Signal::Hold {
+      continue;
+    }
+
+    // Get the first (and should be only) related user
+    let user = match related_users.into_iter().next() {
+      Some(user) => user,
+      None => {
+        eprintln!("No user found for order {}", order.id);
+        continue;
+      }
+    };
+
+    let database = db.clone();
+    let latest_price = snapshot.price;
+
+    let task = match signal {
+      Signal::Sell => tokio::spawn(async move {
+        let order_id = order.id;
+        let message_prefix = format!("Selling {} for order {}", order.contract_address, order_id);
+        if let Err(e) = execute_sell_signal(&database, order, user.clone(), latest_price).await {
+          eprintln!("Error selling for order {}: {:?}", order_id, e);
+          notify_order_user(&user, &format!("{} failed: {}", message_prefix, e)).await;
+        }
+      }),
+      Signal::Buy => {
+        // only used to value the recorded transaction, so a failed lookup doesn't hold up the buy
+        if sol_price.is_none() {
+          sol_price = Some(
+            fetch_token_price("So11111111111111111111111111111111111111112")
+              .await
+              .unwrap_or(0.0),
+          );
+        }
+        let sol_price = sol_price.unwrap_or(0.0);
+
+        tokio::spawn(async move {
+          if let Err(e) = execute_limit_buy(&database, &order, sol_price, latest_price).await {
+            eprintln!("Failed to fill limit buy {}: {:?}", order.id, e);
+
+            let message = format!(
+              "Limit buy of {} for order {} failed: {}",
+              order.contract_address, order.id, e
+            );
+            notify_order_user(&user, &message).await;
+          }
+        })
+      }
+      Signal::Hold => continue,
+    };
+
+    tasks.push(task);
+  }
+
+  join_all(tasks).await;
+
+  Ok(())
+}
@@ -0,0 +1,337 @@
+use anyhow::{anyhow, Context, Result};
+use entity::trade_orders;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+  Set, TransactionTrait,
+};
+use serde_json::json;
+
+use super::{
+  lifecycle::{transition_order, OrderStatus, TransitionDetails, OPEN_ORDER_STATUSES},
+  trailing_stop::{trailing_target_price, with_high_water_mark},
+};
+
+// metadata key on the parent order holding the token amount the ladder was opened on
+pub const POSITION_AMOUNT_KEY: &str = "position_amount";
+
+#[derive(Debug)]
+pub struct LadderRung {
+  pub target_multiple: f64,
+  // fraction of the position at ladder creation, between 0 and 1
+  pub sell_fraction: f64,
+}
+
+#[derive(Debug)]
+pub struct LadderPlan {
+  pub reference_price: f64,
+  pub position_amount: f64,
+  pub rungs: Vec<LadderRung>,
+  // the rest of the position rides a trailing stop when set
+  pub trail_percentage: Option<f32>,
+}
+
+/// Parse rungs written as `multiple:percentage` pairs, e.g. `2:25,3:25`
+pub fn parse_ladder_rungs(value: &str) -> Result<Vec<LadderRung>> {
+  value
+    .split(',')
+    .filter(|rung| !rung.trim().is_empty())
+    .map(|rung| {
+      let (multiple, percentage) = rung
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Rung {} must be written as multiple:percentage", rung))?;
+
+      let target_multiple = multiple
+        .trim()
+        .trim_end_matches('x')
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid multiple in rung {}", rung))?;
+
+      let percentage = percentage
+        .trim()
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid percentage in rung {}", rung))?;
+
+      Ok(LadderRung {
+        target_multiple,
+        sell_fraction: percentage / 100.0,
+      })
+    })
+    .collect()
+}
+
+fn validate_ladder_plan(plan: &LadderPlan) -> Result<()> {
+  if plan.rungs.is_empty() && plan.trail_percentage.is_none() {
+    return Err(anyhow!(
+      "A ladder needs at least one rung or a trailing stop"
+    ));
+  }
+
+  if plan.position_amount <= 0.0 {
+    return Err(anyhow!("No balance to ladder out of"));
+  }
+
+  for rung in &plan.rungs {
+    if rung.target_multiple <= 1.0 {
+      return Err(anyhow!("Rung multiples must be greater than 1"));
+    }
+    if rung.sell_fraction <= 0.0 || rung.sell_fraction > 1.0 {
+      return Err(anyhow!("Rung percentages must be between 0 and 100"));
+    }
+  }
+
+  let total_fraction = rungs_fraction(&plan.rungs);
+  if total_fraction > 1.0 + f64::EPSILON {
+    return Err(anyhow!("Rung percentages add up to more than 100"));
+  }
+
+  if plan.trail_percentage.is_some() && trail_sell_fraction(&plan.rungs) <= 0.0 {
+    return Err(anyhow!(
+      "The rungs sell the whole position, leaving nothing for the trailing stop"
+    ));
+  }
+
+  Ok(())
+}
+
+fn rungs_fraction(rungs: &[LadderRung]) -> f64 {
+  rungs.iter().map(|rung| rung.sell_fraction).sum()
+}
+
+/// Fraction of the position the rungs leave over, which is all the trailing stop sells
+pub fn trail_sell_fraction(rungs: &[LadderRung]) -> f64 {
+  let fraction = 1.0 - rungs_fraction(rungs);
+
+  // rungs adding up to 100% leave float noise rather than zero
+  if fraction <= f64::EPSILON {
+    0.0
+  } else {
+    fraction
+  }
+}
+
+/// Create a parent `ladder` order with one `take_profit` child per rung and an optional
+/// `trailing_stop` child for the share of the position the rungs leave over
+pub async fn create_ladder_order(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  token_id: i32,
+  contract_address: &str,
+  plan: &LadderPlan,
+) -> Result<trade_orders::Model> {
+  validate_ladder_plan(plan)?;
+
+  let txn = db.begin().await.context("Failed to start transaction")?;
+
+  let parent_order = trade_orders::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    token_id: Set(token_id),
+    contract_address: Set(contract_address.to_string()),
+    reference_price: Set(plan.reference_price as f32),
+    target_price: Set(plan.reference_price as f32),
+    strategy: Set("ladder".to_string()),
+    created_by: Set("user".to_string()),
+    metadata: Set(Some(json!({ POSITION_AMOUNT_KEY: plan.position_amount }))),
+    ..Default::default()
+  }
+  .insert(&txn)
+  .await
+  .context("Failed to create ladder order")?;
+
+  for rung in &plan.rungs {
+    trade_orders::ActiveModel {
+      user_id: Set(user_id),
+      wallet_id: Set(wallet_id),
+      token_id: Set(token_id),
+      contract_address: Set(contract_address.to_string()),
+      reference_price: Set(plan.reference_price as f32),
+      target_percentage: Set(((rung.target_multiple - 1.0) * 100.0) as f32),
+      target_price: Set((plan.reference_price * rung.target_multiple) as f32),
+      strategy: Set("take_profit".to_string()),
+      created_by: Set("user".to_string()),
+      parent_id: Set(Some(parent_order.id)),
+      sell_fraction: Set(Some(rung.sell_fraction as f32)),
+      ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .context("Failed to create ladder rung")?;
+  }
+
+  if let Some(trail_percentage) = plan.trail_percentage {
+    trade_orders::ActiveModel {
+      user_id: Set(user_id),
+      wallet_id: Set(wallet_id),
+      token_id: Set(token_id),
+      contract_address: Set(contract_address.to_string()),
+      reference_price: Set(plan.reference_price as f32),
+      target_percentage: Set(trail_percentage),
+      target_price: Set(trailing_target_price(plan.reference_price, trail_percentage) as f32),
+      strategy: Set("trailing_stop".to_string()),
+      created_by: Set("user".to_string()),
+      metadata: Set(Some(with_high_water_mark(None, plan.reference_price))),
+      parent_id: Set(Some(parent_order.id)),
+      sell_fraction: Set(Some(trail_sell_fraction(&plan.rungs) as f32)),
+      ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .context("Failed to create ladder trailing stop")?;
+  }
+
+  txn
+    .commit()
+    .await
+    .context("Failed to commit ladder order")?;
+
+  Ok(parent_order)
+}
+
+/// Fraction of the current balance an order should sell. Rungs are sized against the
+/// position the ladder was opened on, so earlier fills don't shrink later rungs.
+pub async fn order_sell_fraction(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  balance: f64,
+) -> Result<f64> {
+  let (parent_id, rung_fraction) = match (order.parent_id, order.sell_fraction) {
+    (Some(parent_id), Some(rung_fraction)) => (parent_id, rung_fraction as f64),
+    (_, sell_fraction) => return Ok(sell_fraction.map(|f| f as f64).unwrap_or(1.0)),
+  };
+
+  if balance <= 0.0 {
+    return Ok(0.0);
+  }
+
+  let parent = trade_orders::Entity::find_by_id(parent_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve ladder order")?
+    .ok_or_else(|| anyhow!("Ladder order {} not found", parent_id))?;
+
+  let position_amount = parent
+    .metadata
+    .as_ref()
+    .and_then(|metadata| metadata.get(POSITION_AMOUNT_KEY))
+    .and_then(|amount| amount.as_f64())
+    .unwrap_or(balance);
+
+  Ok((rung_fraction * position_amount / balance).min(1.0))
+}
+
+/// Close the parent ladder once none of its rungs are left open, as filled when any of them
+/// sold and as cancelled when they were all called off
+pub async fn complete_ladder<C: ConnectionTrait>(db: &C, parent_id: i32) -> Result<()> {
+  let rungs = trade_orders::Entity::find()
+    .filter(trade_orders::Column::ParentId.eq(parent_id))
+    .all(db)
+    .await
+    .context("Failed to retrieve ladder rungs")?;
+
+  if rungs
+    .iter()
+    .any(|rung| OPEN_ORDER_STATUSES.contains(&rung.status.as_str()))
+  {
+    return Ok(());
+  }
+
+  let closed_status = if rungs
+    .iter()
+    .any(|rung| rung.status == OrderStatus::Filled.as_str())
+  {
+    OrderStatus::Filled
+  } else {
+    OrderStatus::Cancelled
+  };
+
+  transition_order(
+    db,
+    parent_id,
+    OrderStatus::Active,
+    closed_status,
+    TransitionDetails::default(),
+  )
+  .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn plan(rungs: &str, trail_percentage: Option<f32>) -> LadderPlan {
+    LadderPlan {
+      reference_price: 1.0,
+      position_amount: 1_000.0,
+      rungs: parse_ladder_rungs(rungs).unwrap(),
+      trail_percentage,
+    }
+  }
+
+  #[test]
+  fn rungs_parse_as_multiples_and_fractions() {
+    let rungs = parse_ladder_rungs("2x:25%, 3:50,").unwrap();
+
+    assert_eq!(rungs.len(), 2);
+    assert_eq!(rungs[0].target_multiple, 2.0);
+    assert_eq!(rungs[0].sell_fraction, 0.25);
+    assert_eq!(rungs[1].target_multiple, 3.0);
+    assert_eq!(rungs[1].sell_fraction, 0.5);
+  }
+
+  #[test]
+  fn malformed_rungs_are_refused() {
+    assert!(parse_ladder_rungs("2").is_err());
+    assert!(parse_ladder_rungs("two:25").is_err());
+    assert!(parse_ladder_rungs("2:a quarter").is_err());
+  }
+
+  #[test]
+  fn trailing_stop_sells_the_leftover() {
+    let rungs = parse_ladder_rungs("2:25,3:25").unwrap();
+    assert!((trail_sell_fraction(&rungs) - 0.5).abs() < 1e-9);
+
+    assert_eq!(trail_sell_fraction(&[]), 1.0);
+  }
+
+  #[test]
+  fn rungs_adding_up_to_100_leave_nothing() {
+    let rungs = parse_ladder_rungs("2:10,3:20,4:70").unwrap();
+    assert_eq!(trail_sell_fraction(&rungs), 0.0);
+
+    let rungs = parse_ladder_rungs("2:60,3:60").unwrap();
+    assert_eq!(trail_sell_fraction(&rungs), 0.0);
+  }
+
+  #[test]
+  fn valid_plans_pass() {
+    assert!(validate_ladder_plan(&plan("2:25,3:25", Some(15.0))).is_ok());
+    assert!(validate_ladder_plan(&plan("2:50,3:50", None)).is_ok());
+    assert!(validate_ladder_plan(&plan("", Some(15.0))).is_ok());
+  }
+
+  #[test]
+  fn invalid_plans_are_refused() {
+    // nothing to sell with
+    assert!(validate_ladder_plan(&plan("", None)).is_err());
+    // a rung at or below the reference
+    assert!(validate_ladder_plan(&plan("1:25", None)).is_err());
+    // rung percentages out of range
+    assert!(validate_ladder_plan(&plan("2:0", None)).is_err());
+    assert!(validate_ladder_plan(&plan("2:150", None)).is_err());
+    assert!(validate_ladder_plan(&plan("2:60,3:60", None)).is_err());
+    // nothing left over for the trailing stop
+    assert!(validate_ladder_plan(&plan("2:50,3:50", Some(15.0))).is_err());
+  }
+
+  #[test]
+  fn an_empty_position_cant_be_laddered() {
+    let mut empty = plan("2:25", None);
+    empty.position_amount = 0.0;
+
+    assert!(validate_ladder_plan(&empty).is_err());
+  }
+}
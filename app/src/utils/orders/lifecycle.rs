@@ -0,0 +1,392 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use entity::trade_orders;
+use sea_orm::{sea_query::Expr, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+
+use super::ladder::complete_ladder;
+
+// Every status change of a trade order goes through this module so illegal moves are rejected
+// in one place. Transitions are compare-and-set on the current status, so two runs racing on
+// the same order can't both move it.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+  // created but not armed yet
+  Pending,
+  // watched by the price jobs
+  Active,
+  // the price condition was met and a run claimed the order
+  Triggered,
+  // the swap is being built and sent
+  Executing,
+  Filled,
+  Failed,
+  Cancelled,
+  Expired,
+}
+
+// statuses the price jobs still have to look at or finish
+pub const OPEN_ORDER_STATUSES: [&str; 4] = ["pending", "active", "triggered", "executing"];
+
+impl OrderStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      OrderStatus::Pending => "pending",
+      OrderStatus::Active => "active",
+      OrderStatus::Triggered => "triggered",
+      OrderStatus::Executing => "executing",
+      OrderStatus::Filled => "filled",
+      OrderStatus::Failed => "failed",
+      OrderStatus::Cancelled => "cancelled",
+      OrderStatus::Expired => "expired",
+    }
+  }
+
+  pub fn parse(value: &str) -> Result<Self> {
+    match value {
+      "pending" => Ok(OrderStatus::Pending),
+      "active" => Ok(OrderStatus::Active),
+      "triggered" => Ok(OrderStatus::Triggered),
+      "executing" => Ok(OrderStatus::Executing),
+      "filled" => Ok(OrderStatus::Filled),
+      "failed" => Ok(OrderStatus::Failed),
+      "cancelled" => Ok(OrderStatus::Cancelled),
+      "expired" => Ok(OrderStatus::Expired),
+      _ => Err(anyhow!("Unknown order status {}", value)),
+    }
+  }
+
+  pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+    use OrderStatus::*;
+
+    matches!(
+      (self, next),
+      (Pending, Active | Cancelled | Expired)
+        | (Active, Triggered | Cancelled | Expired)
+        // a ladder closes straight from active once its rungs are done
+        | (Active, Filled)
+        // the trigger is dropped when there is nothing left for the order to sell
+        | (Triggered, Executing | Cancelled)
+        | (Executing, Filled | Failed)
+        | (Failed, Active | Cancelled)
+    )
+  }
+}
+
+/// Idempotency key of the swap an order sends when it executes. A failed order that is
+/// reactivated has a new `failed_at`, so its next attempt gets a new key.
+pub fn order_idempotency_key(order: &trade_orders::Model) -> String {
+  format!(
+    "order-{}-{}",
+    order.id,
+    order
+      .failed_at
+      .map(|failed_at| failed_at.and_utc().timestamp())
+      .unwrap_or(0)
+  )
+}
+
+#[derive(Debug, Default)]
+pub struct TransitionDetails {
+  pub failure_reason: Option<String>,
+  pub filled_transaction_id: Option<i32>,
+}
+
+/// Move an order from `from` to `to`. Errors on an illegal move, returns false when the order
+/// was no longer in `from` because another run got to it first.
+pub async fn transition_order<C: ConnectionTrait>(
+  db: &C,
+  order_id: i32,
+  from: OrderStatus,
+  to: OrderStatus,
+  details: TransitionDetails,
+) -> Result<bool> {
+  if !from.can_transition_to(to) {
+    return Err(anyhow!(
+      "Order {} cannot move from {} to {}",
+      order_id,
+      from.as_str(),
+      to.as_str()
+    ));
+  }
+
+  let now = Utc::now().naive_utc();
+
+  let mut update = trade_orders::Entity::update_many()
+    .col_expr(trade_orders::Column::Status, Expr::value(to.as_str()))
+    .col_expr(trade_orders::Column::UpdatedAt, Expr::value(now));
+
+  let timestamp_column = match to {
+    OrderStatus::Triggered => Some(trade_orders::Column::TriggeredAt),
+    OrderStatus::Filled => Some(trade_orders::Column::FilledAt),
+    OrderStatus::Failed => Some(trade_orders::Column::FailedAt),
+    OrderStatus::Cancelled => Some(trade_orders::Column::CancelledAt),
+    OrderStatus::Expired => Some(trade_orders::Column::ExpiredAt),
+    _ => None,
+  };
+
+  if let Some(column) = timestamp_column {
+    update = update.col_expr(column, Expr::value(now));
+  }
+
+  if let Some(failure_reason) = details.failure_reason {
+    update = update.col_expr(
+      trade_orders::Column::FailureReason,
+      Expr::value(failure_reason),
+    );
+  }
+
+  if let Some(transaction_id) = details.filled_transaction_id {
+    update = update.col_expr(
+      trade_orders::Column::FilledTransactionId,
+      Expr::value(transaction_id),
+    );
+  }
+
+  let result = update
+    .filter(trade_orders::Column::Id.eq(order_id))
+    .filter(trade_orders::Column::Status.eq(from.as_str()))
+    .exec(db)
+    .await
+    .context("Failed to update order status")?;
+
+  Ok(result.rows_affected == 1)
+}
+
+/// Claim an active order whose price condition was met
+pub async fn trigger_order<C: ConnectionTrait>(db: &C, order_id: i32) -> Result<bool> {
+  transition_order(
+    db,
+    order_id,
+    OrderStatus::Active,
+    OrderStatus::Triggered,
+    TransitionDetails::default(),
+  )
+  .await
+}
+
+pub async fn start_order_execution<C: ConnectionTrait>(db: &C, order_id: i32) -> Result<bool> {
+  transition_order(
+    db,
+    order_id,
+    OrderStatus::Triggered,
+    OrderStatus::Executing,
+    TransitionDetails::default(),
+  )
+  .await
+}
+
+pub async fn fill_order<C: ConnectionTrait>(
+  db: &C,
+  order_id: i32,
+  transaction_id: i32,
+) -> Result<bool> {
+  transition_order(
+    db,
+    order_id,
+    OrderStatus::Executing,
+    OrderStatus::Filled,
+    TransitionDetails {
+      filled_transaction_id: Some(transaction_id),
+      ..Default::default()
+    },
+  )
+  .await
+}
+
+pub async fn fail_order<C: ConnectionTrait>(db: &C, order_id: i32, reason: &str) -> Result<bool> {
+  transition_order(
+    db,
+    order_id,
+    OrderStatus::Executing,
+    OrderStatus::Failed,
+    TransitionDetails {
+      failure_reason: Some(reason.to_string()),
+      ..Default::default()
+    },
+  )
+  .await
+}
+
+/// Link an executing order to the swap it sent. The order stays executing until the
+/// confirmation job finds the swap landed or failed on chain.
+pub async fn submit_order<C: ConnectionTrait>(
+  db: &C,
+  order_id: i32,
+  transaction_id: i32,
+) -> Result<bool> {
+  let result = trade_orders::Entity::update_many()
+    .col_expr(
+      trade_orders::Column::FilledTransactionId,
+      Expr::value(transaction_id),
+    )
+    .col_expr(
+      trade_orders::Column::UpdatedAt,
+      Expr::value(Utc::now().naive_utc()),
+    )
+    .filter(trade_orders::Column::Id.eq(order_id))
+    .filter(trade_orders::Column::Status.eq(OrderStatus::Executing.as_str()))
+    .exec(db)
+    .await
+    .context("Failed to link order to its transaction")?;
+
+  Ok(result.rows_affected == 1)
+}
+
+// moves an order to cancelled or expired along with its open child orders, and closes the
+// ladder it is a rung of when it was the last one open
+async fn close_order<C: ConnectionTrait>(
+  db: &C,
+  order: &trade_orders::Model,
+  to: OrderStatus,
+  reason: Option<&str>,
+) -> Result<bool> {
+  let closed = transition_order(
+    db,
+    order.id,
+    OrderStatus::parse(&order.status)?,
+    to,
+    TransitionDetails {
+      failure_reason: reason.map(|reason| reason.to_string()),
+      ..Default::default()
+    },
+  )
+  .await?;
+
+  if closed {
+    let children = trade_orders::Entity::find()
+      .filter(trade_orders::Column::ParentId.eq(order.id))
+      .filter(trade_orders::Column::Status.is_in(["pending", "active"]))
+      .all(db)
+      .await
+      .context("Failed to retrieve child orders")?;
+
+    for child in children {
+      transition_order(
+        db,
+        child.id,
+        OrderStatus::parse(&child.status)?,
+        OrderStatus::Cancelled,
+        TransitionDetails::default(),
+      )
+      .await?;
+    }
+
+    // a ladder whose last open rung was called off is done too
+    if let Some(parent_id) = order.parent_id {
+      complete_ladder(db, parent_id).await?;
+    }
+  }
+
+  Ok(closed)
+}
+
+/// Cancel an order from whatever status it is in now, along with its open child orders
+pub async fn cancel_order<C: ConnectionTrait>(
+  db: &C,
+  order: &trade_orders::Model,
+  reason: Option<&str>,
+) -> Result<bool> {
+  close_order(db, order, OrderStatus::Cancelled, reason).await
+}
+
+/// Put a failed order back in front of the price jobs
+pub async fn reactivate_order<C: ConnectionTrait>(db: &C, order_id: i32) -> Result<bool> {
+  transition_order(
+    db,
+    order_id,
+    OrderStatus::Failed,
+    OrderStatus::Active,
+    TransitionDetails::default(),
+  )
+  .await
+}
+
+/// Expire every pending or active order whose `expires_at` has passed, cancelling the rungs of
+/// an expired ladder
+pub async fn expire_orders<C: ConnectionTrait>(db: &C) -> Result<u64> {
+  let orders = trade_orders::Entity::find()
+    .filter(
+      trade_orders::Column::Status
+        .is_in([OrderStatus::Pending.as_str(), OrderStatus::Active.as_str()]),
+    )
+    .filter(trade_orders::Column::ExpiresAt.lte(Utc::now().naive_utc()))
+    .all(db)
+    .await
+    .context("Failed to retrieve expired orders")?;
+
+  let mut expired = 0;
+
+  for order in orders {
+    if close_order(db, &order, OrderStatus::Expired, None).await? {
+      expired += 1;
+    }
+  }
+
+  Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ALL_STATUSES: [OrderStatus; 8] = [
+    OrderStatus::Pending,
+    OrderStatus::Active,
+    OrderStatus::Triggered,
+    OrderStatus::Executing,
+    OrderStatus::Filled,
+    OrderStatus::Failed,
+    OrderStatus::Cancelled,
+    OrderStatus::Expired,
+  ];
+
+  #[test]
+  fn statuses_round_trip_through_their_names() {
+    for status in ALL_STATUSES {
+      assert_eq!(OrderStatus::parse(status.as_str()).unwrap(), status);
+    }
+    assert!(OrderStatus::parse("open").is_err());
+  }
+
+  #[test]
+  fn transition_table() {
+    use OrderStatus::*;
+
+    let allowed = [
+      (Pending, Active),
+      (Pending, Cancelled),
+      (Pending, Expired),
+      (Active, Triggered),
+      (Active, Cancelled),
+      (Active, Expired),
+      (Active, Filled),
+      (Triggered, Executing),
+      (Triggered, Cancelled),
+      (Executing, Filled),
+      (Executing, Failed),
+      (Failed, Active),
+      (Failed, Cancelled),
+    ];
+
+    for from in ALL_STATUSES {
+      for to in ALL_STATUSES {
+        assert_eq!(
+          from.can_transition_to(to),
+          allowed.contains(&(from, to)),
+          "{} -> {}",
+          from.as_str(),
+          to.as_str()
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn cancelled_and_expired_orders_are_final() {
+    for to in ALL_STATUSES {
+      assert!(!OrderStatus::Cancelled.can_transition_to(to));
+      assert!(!OrderStatus::Expired.can_transition_to(to));
+    }
+  }
+}
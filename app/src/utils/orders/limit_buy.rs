@@ -0,0 +1,231 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDateTime;
+use entity::{trade_orders, wallets};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde_json::json;
+
+use super::{
+  lifecycle::{fail_order, order_idempotency_key, start_order_execution, trigger_order},
+  submitted::order_swap_sent,
+};
+use crate::utils::swap::{
+  lock::{acquire_execution_lock, order_lock_key, release_execution_lock, wallet_lock_key},
+  slippage::SwapKind,
+  solana::{
+    execute_buy_trade, fail_reserved_transaction, reserve_transaction, settle_reserved_transaction,
+    SwapTxResult,
+  },
+};
+
+// metadata keys on a limit buy for the SOL to spend and the stop loss to attach once filled
+pub const SOL_AMOUNT_KEY: &str = "sol_amount";
+pub const STOP_LOSS_PERCENTAGE_KEY: &str = "stop_loss_percentage";
+
+pub fn limit_buy_sol_amount(order: &trade_orders::Model) -> f64 {
+  order
+    .metadata
+    .as_ref()
+    .and_then(|metadata| metadata.get(SOL_AMOUNT_KEY))
+    .and_then(|amount| amount.as_f64())
+    .unwrap_or(0.0)
+}
+
+pub fn limit_buy_stop_loss_percentage(order: &trade_orders::Model) -> Option<f32> {
+  order
+    .metadata
+    .as_ref()
+    .and_then(|metadata| metadata.get(STOP_LOSS_PERCENTAGE_KEY))
+    .and_then(|percentage| percentage.as_f64())
+    .map(|percentage| percentage as f32)
+}
+
+/// Queue a buy of `sol_amount` SOL for when the token trades at or below `target_price`
+#[allow(clippy::too_many_arguments)]
+pub async fn create_limit_buy_order(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  token_id: i32,
+  contract_address: &str,
+  reference_price: f64,
+  target_price: f64,
+  sol_amount: f64,
+  stop_loss_percentage: Option<f32>,
+  expires_at: Option<NaiveDateTime>,
+) -> Result<trade_orders::Model> {
+  if target_price <= 0.0 {
+    return Err(anyhow!("Limit price must be greater than 0"));
+  }
+
+  if sol_amount <= 0.0 {
+    return Err(anyhow!("SOL amount must be greater than 0"));
+  }
+
+  if let Some(percentage) = stop_loss_percentage {
+    if percentage <= 0.0 || percentage >= 100.0 {
+      return Err(anyhow!("Stop loss percentage must be between 0 and 100"));
+    }
+  }
+
+  let target_percentage = if reference_price > 0.0 {
+    ((1.0 - target_price / reference_price) * 100.0) as f32
+  } else {
+    0.0
+  };
+
+  let new_trade_order = trade_orders::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    token_id: Set(token_id),
+    contract_address: Set(contract_address.to_string()),
+    reference_price: Set(reference_price as f32),
+    target_percentage: Set(target_percentage),
+    target_price: Set(target_price as f32),
+    strategy: Set("limit_buy".to_string()),
+    created_by: Set("user".to_string()),
+    metadata: Set(Some(json!({
+      SOL_AMOUNT_KEY: sol_amount,
+      STOP_LOSS_PERCENTAGE_KEY: stop_loss_percentage,
+    }))),
+    expires_at: Set(expires_at),
+    ..Default::default()
+  };
+
+  new_trade_order
+    .insert(db)
+    .await
+    .context("Failed to create limit buy order")
+}
+
+/// Attach the stop loss a filled limit buy asked for, measured from `entry_price`
+pub async fn attach_limit_buy_stop_loss(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  entry_price: f64,
+) -> Result<()> {
+  let stop_loss_percentage = match limit_buy_stop_loss_percentage(order) {
+    Some(stop_loss_percentage) => stop_loss_percentage,
+    None => return Ok(()),
+  };
+
+  let stop_loss_order = trade_orders::ActiveModel {
+    user_id: Set(order.user_id),
+    wallet_id: Set(order.wallet_id),
+    token_id: Set(order.token_id),
+    contract_address: Set(order.contract_address.clone()),
+    reference_price: Set(entry_price as f32),
+    target_percentage: Set(stop_loss_percentage),
+    target_price: Set((entry_price * (1.0 - stop_loss_percentage as f64 / 100.0)) as f32),
+    strategy: Set("stop_loss".to_string()),
+    created_by: Set("user".to_string()),
+    ..Default::default()
+  };
+
+  trade_orders::Entity::insert(stop_loss_order)
+    .exec(db)
+    .await
+    .context("Failed to attach stop loss")?;
+
+  Ok(())
+}
+
+/// Buy for a limit order whose target was reached. Returns None when another run already
+/// claimed the order or its wallet.
+pub async fn execute_limit_buy(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  sol_price: f64,
+  latest_price: f64,
+) -> Result<Option<SwapTxResult>> {
+  let lock = match acquire_execution_lock(
+    db,
+    vec![order_lock_key(order.id), wallet_lock_key(order.wallet_id)],
+  )
+  .await?
+  {
+    Some(lock) => lock,
+    None => return Ok(None),
+  };
+
+  let attempt = buy_limit_order(db, order, sol_price, latest_price).await;
+
+  if let Err(e) = release_execution_lock(db, lock).await {
+    eprintln!("Failed to release lock of order {}: {:?}", order.id, e);
+  }
+
+  attempt
+}
+
+async fn buy_limit_order(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  sol_price: f64,
+  latest_price: f64,
+) -> Result<Option<SwapTxResult>> {
+  let wallet = wallets::Entity::find_by_id(order.wallet_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve wallet")?
+    .ok_or_else(|| anyhow!("No wallet found for order {}", order.id))?;
+
+  let sol_amount = limit_buy_sol_amount(order);
+
+  if !trigger_order(db, order.id).await? || !start_order_execution(db, order.id).await? {
+    return Ok(None);
+  }
+
+  let transaction_id = match reserve_transaction(
+    db,
+    &order_idempotency_key(order),
+    order.user_id,
+    wallet.id,
+    "So11111111111111111111111111111111111111112",
+    &order.contract_address,
+  )
+  .await?
+  {
+    Some(transaction_id) => transaction_id,
+    None => {
+      fail_order(db, order.id, "A swap was already sent for this trigger").await?;
+      return Ok(None);
+    }
+  };
+
+  let purchase = match execute_buy_trade(
+    order.user_id,
+    wallet.id,
+    &order.contract_address,
+    sol_amount,
+    SwapKind::Buy,
+    db,
+  )
+  .await
+  {
+    Ok(purchase) => purchase,
+    Err(e) => {
+      fail_reserved_transaction(db, transaction_id, &e).await?;
+      fail_order(db, order.id, &e.to_string()).await?;
+      return Err(e);
+    }
+  };
+
+  settle_reserved_transaction(
+    db,
+    transaction_id,
+    purchase.attempt.clone(),
+    purchase.sol_amount,
+    purchase.sol_amount * sol_price,
+  )
+  .await?;
+
+  order_swap_sent(
+    db,
+    order,
+    transaction_id,
+    purchase.attempt.success,
+    latest_price,
+  )
+  .await?;
+
+  Ok(Some(purchase.attempt))
+}
@@ -0,0 +1,23 @@
+pub mod edit;
+pub mod engine;
+pub mod ladder;
+pub mod lifecycle;
+pub mod limit_buy;
+pub mod sell;
+pub mod strategy;
+pub mod submitted;
+pub mod trailing_stop;
+
+use entity::trade_orders;
+
+pub fn format_trade_order(order: &trade_orders::Model) -> String {
+  let sell = match order.sell_fraction {
+    Some(sell_fraction) => format!(", sells {}%", sell_fraction * 100.0),
+    None => String::new(),
+  };
+
+  format!(
+    "#{} {} at ${} ({}%){} [{}]",
+    order.id, order.strategy, order.target_price, order.target_percentage, sell, order.status
+  )
+}
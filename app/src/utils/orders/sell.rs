@@ -0,0 +1,135 @@
+use anyhow::Result;
+use entity::{trade_orders, wallets};
+use sea_orm::DatabaseConnection;
+
+use super::{
+  ladder::order_sell_fraction,
+  lifecycle::{
+    cancel_order, fail_order, order_idempotency_key, start_order_execution, trigger_order,
+    OrderStatus,
+  },
+  submitted::order_swap_sent,
+};
+use crate::utils::swap::{
+  lock::{acquire_execution_lock, order_lock_key, release_execution_lock, wallet_lock_key},
+  slippage::SwapKind,
+  solana::{
+    execute_sell_trade, fail_reserved_transaction, reserve_transaction,
+    settle_reserved_transaction, SellTradeResult,
+  },
+};
+
+/// Sell the share of the position a triggered order is responsible for and walk the order
+/// through triggered and executing, on to filled or failed once its swap is settled. Returns None when another run already
+/// claimed the order or its wallet, or there was nothing left for it to sell.
+pub async fn execute_order_sell(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  user_id: i32,
+  wallet: &wallets::Model,
+  balance: f64,
+  latest_price: f64,
+) -> Result<Option<SellTradeResult>> {
+  let lock = match acquire_execution_lock(
+    db,
+    vec![order_lock_key(order.id), wallet_lock_key(wallet.id)],
+  )
+  .await?
+  {
+    Some(lock) => lock,
+    // the order stays active and is looked at again on the next price update
+    None => return Ok(None),
+  };
+
+  let sale = sell_order(db, order, user_id, wallet, balance, latest_price).await;
+
+  if let Err(e) = release_execution_lock(db, lock).await {
+    eprintln!("Failed to release lock of order {}: {:?}", order.id, e);
+  }
+
+  sale
+}
+
+async fn sell_order(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  user_id: i32,
+  wallet: &wallets::Model,
+  balance: f64,
+  latest_price: f64,
+) -> Result<Option<SellTradeResult>> {
+  if !trigger_order(db, order.id).await? {
+    return Ok(None);
+  }
+
+  let sell_fraction = order_sell_fraction(db, order, balance).await?;
+
+  if sell_fraction <= 0.0 {
+    // cancelled as triggered, closing the ladder when this was its last open rung
+    let triggered_order = trade_orders::Model {
+      status: OrderStatus::Triggered.as_str().to_string(),
+      ..order.clone()
+    };
+    cancel_order(db, &triggered_order, Some("No balance left to sell")).await?;
+    return Ok(None);
+  }
+
+  if !start_order_execution(db, order.id).await? {
+    return Ok(None);
+  }
+
+  let transaction_id = match reserve_transaction(
+    db,
+    &order_idempotency_key(order),
+    user_id,
+    wallet.id,
+    &order.contract_address,
+    "So11111111111111111111111111111111111111112",
+  )
+  .await?
+  {
+    Some(transaction_id) => transaction_id,
+    None => {
+      fail_order(db, order.id, "A swap was already sent for this trigger").await?;
+      return Ok(None);
+    }
+  };
+
+  let sale = match execute_sell_trade(
+    user_id,
+    wallet,
+    &order.contract_address,
+    sell_fraction,
+    SwapKind::for_sell_strategy(&order.strategy),
+    db,
+  )
+  .await
+  {
+    Ok(sale) => sale,
+    Err(e) => {
+      fail_reserved_transaction(db, transaction_id, &e).await?;
+      fail_order(db, order.id, &e.to_string()).await?;
+      return Err(e);
+    }
+  };
+
+  settle_reserved_transaction(
+    db,
+    transaction_id,
+    sale.attempt.clone(),
+    sale.sold_amount,
+    latest_price * sale.sold_amount,
+  )
+  .await?;
+
+  order_swap_sent(
+    db,
+    order,
+    transaction_id,
+    sale.attempt.success,
+    latest_price,
+  )
+  .await?;
+
+  Ok(Some(sale))
+}
@@ -0,0 +1,142 @@
+use entity::trade_orders;
+
+use super::trailing_stop::{high_water_mark, trailing_target_price};
+use crate::utils::misc::{PriceAnalyzer, PriceTrend};
+
+/// Prices of a token at the moment its orders are evaluated
+#[derive(Debug, Clone)]
+pub struct PriceSnapshot {
+  pub contract_address: String,
+  pub price: f64,
+  // prices of the last few minutes, oldest first
+  pub recent_prices: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+  Hold,
+  Sell,
+  Buy,
+}
+
+/// An automated order type, keyed by the `trade_orders.strategy` it handles
+pub trait Strategy: Send + Sync {
+  fn name(&self) -> &'static str;
+
+  fn evaluate(&self, order: &trade_orders::Model, snapshot: &PriceSnapshot) -> Signal;
+}
+
+pub struct StopLoss;
+
+impl Strategy for StopLoss {
+  fn name(&self) -> &'static str {
+    "stop_loss"
+  }
+
+  fn evaluate(&self, order: &trade_orders::Model, snapshot: &PriceSnapshot) -> Signal {
+    if snapshot.price <= order.target_price as f64 {
+      Signal::Sell
+    } else {
+      Signal::Hold
+    }
+  }
+}
+
+pub struct TakeProfit;
+
+impl Strategy for TakeProfit {
+  fn name(&self) -> &'static str {
+    "take_profit"
+  }
+
+  fn evaluate(&self, order: &trade_orders::Model, snapshot: &PriceSnapshot) -> Signal {
+    if snapshot.price >= order.target_price as f64 {
+      Signal::Sell
+    } else {
+      Signal::Hold
+    }
+  }
+}
+
+pub struct TrailingStop;
+
+impl Strategy for TrailingStop {
+  fn name(&self) -> &'static str {
+    "trailing_stop"
+  }
+
+  fn evaluate(&self, order: &trade_orders::Model, snapshot: &PriceSnapshot) -> Signal {
+    // the snapshot price can be a new high the stored target hasn't ratcheted to yet
+    let peak = high_water_mark(order).max(snapshot.price);
+
+    if snapshot.price <= trailing_target_price(peak, order.target_percentage) {
+      Signal::Sell
+    } else {
+      Signal::Hold
+    }
+  }
+}
+
+/// Stop loss placed on launch buys. Sells once the price falls to its target and, when there is
+/// enough history, on a falling trend before that.
+pub struct LaunchStopLoss;
+
+impl Strategy for LaunchStopLoss {
+  fn name(&self) -> &'static str {
+    "launch_stop_loss"
+  }
+
+  fn evaluate(&self, order: &trade_orders::Model, snapshot: &PriceSnapshot) -> Signal {
+    if snapshot.price <= order.target_price as f64 {
+      return Signal::Sell;
+    }
+
+    if snapshot.recent_prices.len() > 5 {
+      let reference_price = snapshot.recent_prices.first().cloned().unwrap_or(0.0);
+      let analyzer = PriceAnalyzer::new(3, 20.0);
+
+      if reference_price != 0.0
+        && analyzer.analyze_trend(&snapshot.recent_prices) == PriceTrend::Decreasing
+      {
+        return Signal::Sell;
+      }
+    }
+
+    Signal::Hold
+  }
+}
+
+pub struct LimitBuy;
+
+impl Strategy for LimitBuy {
+  fn name(&self) -> &'static str {
+    "limit_buy"
+  }
+
+  fn evaluate(&self, order: &trade_orders::Model, snapshot: &PriceSnapshot) -> Signal {
+    if snapshot.price <= order.target_price as f64 {
+      Signal::Buy
+    } else {
+      Signal::Hold
+    }
+  }
+}
+
+static STRATEGIES: [&dyn Strategy; 5] = [
+  &StopLoss,
+  &TakeProfit,
+  &TrailingStop,
+  &LaunchStopLoss,
+  &LimitBuy,
+];
+
+pub fn find_strategy(name: &str) -> Option<&'static dyn Strategy> {
+  STRATEGIES
+    .iter()
+    .copied()
+    .find(|strategy| strategy.name() == name)
+}
+
+pub fn strategy_names() -> Vec<&'static str> {
+  STRATEGIES.iter().map(|strategy| strategy.name()).collect()
+}
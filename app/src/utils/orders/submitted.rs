@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use entity::trade_orders;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use super::{
+  ladder::complete_ladder,
+  lifecycle::{fail_order, fill_order, reactivate_order, submit_order, OrderStatus},
+  limit_buy::attach_limit_buy_stop_loss,
+};
+
+// An order whose swap was sent stays executing, linked to the swap's transaction, until the
+// confirmation job finds that swap on chain. A confirmed swap fills the order, a failed or
+// expired one fails it. Sells are then put back in front of the price jobs since the tokens they
+// protect are still held, limit buys stay failed like one whose swap failed to send. Paper fills
+// can't fail on chain and fill their order as they are made.
+
+#[derive(Debug)]
+pub struct FailedOrder {
+  pub order: trade_orders::Model,
+  pub reactivated: bool,
+}
+
+fn retries_on_chain_failure(order: &trade_orders::Model) -> bool {
+  order.strategy != "limit_buy"
+}
+
+/// Settle an executing order once its swap went out. A paper fill fills it right away, a sent
+/// swap is left to the confirmation job. `entry_price` is what the stop loss of a limit buy is
+/// measured from.
+pub async fn order_swap_sent(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  transaction_id: i32,
+  filled: bool,
+  entry_price: f64,
+) -> Result<()> {
+  if filled {
+    fill_executing_order(db, order, transaction_id, entry_price).await?;
+  } else {
+    submit_order(db, order.id, transaction_id).await?;
+  }
+
+  Ok(())
+}
+
+// fills an order and does what filling it entails, false when another run settled it first
+async fn fill_executing_order(
+  db: &DatabaseConnection,
+  order: &trade_orders::Model,
+  transaction_id: i32,
+  entry_price: f64,
+) -> Result<bool> {
+  if !fill_order(db, order.id, transaction_id).await? {
+    return Ok(false);
+  }
+
+  if let Some(parent_id) = order.parent_id {
+    complete_ladder(db, parent_id).await?;
+  }
+
+  if order.strategy == "limit_buy" {
+    attach_limit_buy_stop_loss(db, order, entry_price).await?;
+  }
+
+  Ok(true)
+}
+
+async fn find_submitted_order(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+) -> Result<Option<trade_orders::Model>> {
+  trade_orders::Entity::find()
+    .filter(trade_orders::Column::FilledTransactionId.eq(transaction_id))
+    .filter(trade_orders::Column::Status.eq(OrderStatus::Executing.as_str()))
+    .one(db)
+    .await
+    .context("Failed to retrieve submitted order")
+}
+
+/// Fill the order that sent a transaction which landed, None when no order is waiting on it
+pub async fn fill_submitted_order(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+) -> Result<Option<trade_orders::Model>> {
+  let order = match find_submitted_order(db, transaction_id).await? {
+    Some(order) => order,
+    None => return Ok(None),
+  };
+
+  // a limit buy fills at or below its target, the stop loss it attaches is anchored to the
+  // position's entry once the fill is applied to it
+  let entry_price = order.target_price as f64;
+
+  if !fill_executing_order(db, &order, transaction_id, entry_price).await? {
+    return Ok(None);
+  }
+
+  Ok(Some(order))
+}
+
+/// Fail the order that sent a transaction which never landed, None when no order is waiting on
+/// it
+pub async fn fail_submitted_order(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+  reason: &str,
+) -> Result<Option<FailedOrder>> {
+  let order = match find_submitted_order(db, transaction_id).await? {
+    Some(order) => order,
+    None => return Ok(None),
+  };
+
+  if !fail_order(db, order.id, reason).await? {
+    return Ok(None);
+  }
+
+  let reactivated = retries_on_chain_failure(&order) && reactivate_order(db, order.id).await?;
+
+  Ok(Some(FailedOrder { order, reactivated }))
+}
@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use entity::trade_orders;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+
+// metadata key holding the highest price seen since the order was opened
+pub const HIGH_WATER_MARK_KEY: &str = "high_water_mark";
+
+pub fn high_water_mark(order: &trade_orders::Model) -> f64 {
+  order
+    .metadata
+    .as_ref()
+    .and_then(|metadata| metadata.get(HIGH_WATER_MARK_KEY))
+    .and_then(|peak| peak.as_f64())
+    .unwrap_or(order.reference_price as f64)
+}
+
+pub fn trailing_target_price(peak: f64, trail_percentage: f32) -> f64 {
+  peak * (1.0 - (trail_percentage as f64 / 100.0))
+}
+
+pub fn with_high_water_mark(metadata: Option<Value>, peak: f64) -> Value {
+  match metadata {
+    Some(Value::Object(mut map)) => {
+      map.insert(HIGH_WATER_MARK_KEY.to_string(), json!(peak));
+      Value::Object(map)
+    }
+    _ => json!({ HIGH_WATER_MARK_KEY: peak }),
+  }
+}
+
+/// Move the target of every active trailing stop on a token up when the price makes a new high
+pub async fn ratchet_trailing_stops(db: &DatabaseConnection, ca: &str, price: f64) -> Result<()> {
+  let trailing_stops = trade_orders::Entity::find()
+    .filter(trade_orders::Column::ContractAddress.eq(ca))
+    .filter(trade_orders::Column::Strategy.eq("trailing_stop"))
+    .filter(trade_orders::Column::Status.eq("active"))
+    .all(db)
+    .await
+    .context("Failed to retrieve trailing stop orders")?;
+
+  for order in trailing_stops {
+    if price <= high_water_mark(&order) {
+      continue;
+    }
+
+    let ratchet_update_model = trade_orders::ActiveModel {
+      id: Set(order.id),
+      target_price: Set(trailing_target_price(price, order.target_percentage) as f32),
+      metadata: Set(Some(with_high_water_mark(order.metadata, price))),
+      updated_at: Set(Utc::now().naive_utc()),
+      ..Default::default()
+    };
+
+    trade_orders::Entity::update(ratchet_update_model)
+      .exec(db)
+      .await
+      .context("Failed to ratchet trailing stop")?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,376 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use entity::{onchain_transactions, positions, token_prices, trade_orders};
+use sea_orm::{
+  sea_query::{Expr, OnConflict},
+  ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+  TransactionTrait,
+};
+
+use crate::utils::wallets::solana::get_token_details;
+
+// A position is what a wallet holds of a token as its confirmed fills add up. Buys add to its
+// quantity and cost basis, sells take their share of the cost basis out and realize the
+// difference with what they were sold for. Network fees are counted apart from PnL. Tokens sold
+// beyond what the ledger knows of, bought before it existed, realize nothing. Paper fills add up
+// to a simulated position of their own, apart from what the wallet holds on chain.
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+// strategies whose target is measured from the entry price
+const ENTRY_ANCHORED_STRATEGIES: [&str; 2] = ["stop_loss", "launch_stop_loss"];
+
+/// A confirmed swap between SOL and a token, amounts in the smallest unit of each mint
+#[derive(Debug, Clone, Copy)]
+struct PositionFill {
+  is_buy: bool,
+  token_amount: u64,
+  lamports: u64,
+  value_usd: f64,
+  network_fee_lamports: u64,
+}
+
+fn ui_quantity(quantity: i64, decimals: i32) -> f64 {
+  quantity as f64 / 10f64.powi(decimals)
+}
+
+/// Quantity held, in whole tokens
+pub fn position_quantity(position: &positions::Model) -> f64 {
+  ui_quantity(position.quantity, position.decimals)
+}
+
+/// PnL of what is still held at a price in SOL per token
+pub fn unrealized_pnl_sol(position: &positions::Model, price_native: f64) -> f64 {
+  position_quantity(position) * price_native - position.cost_basis_sol as f64
+}
+
+/// PnL of what is still held at a price in USD per token
+pub fn unrealized_pnl_usd(position: &positions::Model, price_usd: f64) -> f64 {
+  position_quantity(position) * price_usd - position.cost_basis_usd as f64
+}
+
+fn apply_fill(position: &mut positions::Model, fill: &PositionFill) {
+  let mut cost_basis_sol = position.cost_basis_sol as f64;
+  let mut cost_basis_usd = position.cost_basis_usd as f64;
+  let sol_amount = fill.lamports as f64 / 1_000_000_000.0;
+
+  if fill.is_buy {
+    position.quantity += fill.token_amount as i64;
+    cost_basis_sol += sol_amount;
+    cost_basis_usd += fill.value_usd;
+  } else {
+    let held = position.quantity.max(0) as u64;
+    let matched = fill.token_amount.min(held);
+
+    let held_fraction = if held > 0 {
+      matched as f64 / held as f64
+    } else {
+      0.0
+    };
+    let sold_fraction = if fill.token_amount > 0 {
+      matched as f64 / fill.token_amount as f64
+    } else {
+      0.0
+    };
+
+    let sold_cost_sol = cost_basis_sol * held_fraction;
+
+    position.realized_pnl_sol += (sol_amount * sold_fraction - sold_cost_sol) as f32;
+    position.quantity -= matched as i64;
+    cost_basis_sol -= sold_cost_sol;
+    cost_basis_usd -= cost_basis_usd * held_fraction;
+  }
+
+  // a closed position keeps the entry it was closed with
+  if position.quantity > 0 {
+    let quantity = position_quantity(position);
+    position.average_entry_price = (cost_basis_sol / quantity) as f32;
+    position.average_entry_price_usd = (cost_basis_usd / quantity) as f32;
+  } else {
+    cost_basis_sol = 0.0;
+    cost_basis_usd = 0.0;
+  }
+
+  position.cost_basis_sol = cost_basis_sol as f32;
+  position.cost_basis_usd = cost_basis_usd as f32;
+  position.fees_lamports += fill.network_fee_lamports as i64;
+}
+
+fn transaction_fill(transaction: &onchain_transactions::Model) -> Option<(String, PositionFill)> {
+  let input_amount = transaction.input_amount? as u64;
+  let output_amount = transaction.output_amount? as u64;
+
+  let (contract_address, is_buy) = match (
+    transaction.from_token.as_deref()?,
+    transaction.to_token.as_deref()?,
+  ) {
+    (SOL_MINT, SOL_MINT) => return None,
+    (SOL_MINT, to_token) => (to_token, true),
+    (from_token, SOL_MINT) => (from_token, false),
+    _ => return None,
+  };
+
+  let (token_amount, lamports) = if is_buy {
+    (output_amount, input_amount)
+  } else {
+    (input_amount, output_amount)
+  };
+
+  Some((
+    contract_address.to_string(),
+    PositionFill {
+      is_buy,
+      token_amount,
+      lamports,
+      value_usd: transaction.value_usd.unwrap_or(0.0) as f64,
+      network_fee_lamports: transaction.network_fee_lamports.unwrap_or(0) as u64,
+    },
+  ))
+}
+
+async fn find_or_create_position(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  contract_address: &str,
+  simulated: bool,
+) -> Result<positions::Model> {
+  let existing_position = positions::Entity::find()
+    .filter(positions::Column::WalletId.eq(wallet_id))
+    .filter(positions::Column::ContractAddress.eq(contract_address))
+    .filter(positions::Column::Simulated.eq(simulated))
+    .one(db)
+    .await
+    .context("Failed to retrieve position")?;
+
+  if let Some(position) = existing_position {
+    return Ok(position);
+  }
+
+  let token = get_token_details(contract_address).await?;
+
+  let new_position = positions::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    contract_address: Set(contract_address.to_string()),
+    decimals: Set(token.decimals as i32),
+    simulated: Set(simulated),
+    ..Default::default()
+  };
+
+  // another fill may have opened it in the meantime
+  let inserted = positions::Entity::insert(new_position)
+    .on_conflict(
+      OnConflict::columns([
+        positions::Column::WalletId,
+        positions::Column::ContractAddress,
+        positions::Column::Simulated,
+      ])
+      .do_nothing()
+      .to_owned(),
+    )
+    .exec(db)
+    .await;
+
+  match inserted {
+    Ok(_) | Err(DbErr::RecordNotInserted) => {}
+    Err(e) => return Err(e).context("Failed to create position"),
+  }
+
+  positions::Entity::find()
+    .filter(positions::Column::WalletId.eq(wallet_id))
+    .filter(positions::Column::ContractAddress.eq(contract_address))
+    .filter(positions::Column::Simulated.eq(simulated))
+    .one(db)
+    .await
+    .context("Failed to retrieve position")?
+    .ok_or_else(|| {
+      anyhow!(
+        "No position in {} for wallet {}",
+        contract_address,
+        wallet_id
+      )
+    })
+}
+
+/// Apply the fill of a confirmed or paper swap to the wallet's position in its token. A fill is
+/// only ever applied once, None when there was nothing left to apply.
+pub async fn apply_fill_to_position(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+) -> Result<Option<positions::Model>> {
+  let transaction = onchain_transactions::Entity::find_by_id(transaction_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve transaction")?
+    .ok_or_else(|| anyhow!("No transaction {}", transaction_id))?;
+
+  if transaction.position_id.is_some() || transaction.status.as_deref() != Some("confirmed") {
+    return Ok(None);
+  }
+
+  let (contract_address, fill) = match transaction_fill(&transaction) {
+    Some(fill) => fill,
+    None => return Ok(None),
+  };
+
+  let position = find_or_create_position(
+    db,
+    transaction.user_id,
+    transaction.wallet_id,
+    &contract_address,
+    transaction.simulated,
+  )
+  .await?;
+
+  let txn = db.begin().await.context("Failed to start transaction")?;
+
+  // claims the fill, a run that got to it first already applied it
+  let claimed = onchain_transactions::Entity::update_many()
+    .col_expr(
+      onchain_transactions::Column::PositionId,
+      Expr::value(position.id),
+    )
+    .filter(onchain_transactions::Column::Id.eq(transaction.id))
+    .filter(onchain_transactions::Column::PositionId.is_null())
+    .exec(&txn)
+    .await
+    .context("Failed to claim fill")?;
+
+  if claimed.rows_affected == 0 {
+    return Ok(None);
+  }
+
+  // fills of the same position are applied one after the other
+  let mut position = positions::Entity::find_by_id(position.id)
+    .lock_exclusive()
+    .one(&txn)
+    .await
+    .context("Failed to retrieve position")?
+    .ok_or_else(|| anyhow!("No position {}", position.id))?;
+
+  apply_fill(&mut position, &fill);
+
+  let position_update_model = positions::ActiveModel {
+    id: Set(position.id),
+    quantity: Set(position.quantity),
+    cost_basis_sol: Set(position.cost_basis_sol),
+    cost_basis_usd: Set(position.cost_basis_usd),
+    average_entry_price: Set(position.average_entry_price),
+    average_entry_price_usd: Set(position.average_entry_price_usd),
+    realized_pnl_sol: Set(position.realized_pnl_sol),
+    fees_lamports: Set(position.fees_lamports),
+    updated_at: Set(Utc::now().naive_utc()),
+    ..Default::default()
+  };
+
+  let position = positions::Entity::update(position_update_model)
+    .exec(&txn)
+    .await
+    .context("Failed to update position")?;
+
+  txn.commit().await.context("Failed to commit transaction")?;
+
+  if fill.is_buy {
+    anchor_orders_to_entry(db, &position).await?;
+  }
+
+  Ok(Some(position))
+}
+
+/// Apply a fill without failing the swap it came from, the position just misses it
+pub async fn try_apply_fill_to_position(db: &DatabaseConnection, transaction_id: i32) {
+  if let Err(e) = apply_fill_to_position(db, transaction_id).await {
+    eprintln!(
+      "Failed to apply transaction {} to its position: {:?}",
+      transaction_id, e
+    );
+  }
+}
+
+// stop losses on a position are measured from its average entry rather than the price seen when
+// the order was made
+async fn anchor_orders_to_entry(
+  db: &DatabaseConnection,
+  position: &positions::Model,
+) -> Result<()> {
+  let entry_price = position.average_entry_price_usd as f64;
+
+  if position.quantity <= 0 || entry_price <= 0.0 {
+    return Ok(());
+  }
+
+  let orders = trade_orders::Entity::find()
+    .filter(trade_orders::Column::WalletId.eq(position.wallet_id))
+    .filter(trade_orders::Column::ContractAddress.eq(&position.contract_address))
+    .filter(trade_orders::Column::Strategy.is_in(ENTRY_ANCHORED_STRATEGIES))
+    .filter(trade_orders::Column::Status.eq("active"))
+    .all(db)
+    .await
+    .context("Failed to retrieve stop loss orders")?;
+
+  for order in orders {
+    let order_update_model = trade_orders::ActiveModel {
+      id: Set(order.id),
+      reference_price: Set(entry_price as f32),
+      target_price: Set((entry_price * (1.0 - order.target_percentage as f64 / 100.0)) as f32),
+      updated_at: Set(Utc::now().naive_utc()),
+      ..Default::default()
+    };
+
+    trade_orders::Entity::update(order_update_model)
+      .exec(db)
+      .await
+      .context("Failed to update stop loss order")?;
+  }
+
+  Ok(())
+}
+
+/// Latest recorded price of a token
+pub async fn latest_token_price(
+  db: &DatabaseConnection,
+  contract_address: &str,
+) -> Result<Option<token_prices::Model>> {
+  token_prices::Entity::find()
+    .filter(token_prices::Column::ContractAddress.eq(contract_address))
+    .order_by_desc(token_prices::Column::CreatedAt)
+    .one(db)
+    .await
+    .context("Failed to retrieve token price")
+}
+
+/// Unrealized PnL of a position at the latest recorded price, None without one
+pub async fn position_unrealized_pnl(
+  db: &DatabaseConnection,
+  position: &positions::Model,
+) -> Result<Option<f64>> {
+  let price_native = latest_token_price(db, &position.contract_address)
+    .await?
+    .and_then(|price| price.price_native);
+
+  Ok(price_native.map(|price_native| unrealized_pnl_sol(position, price_native as f64)))
+}
+
+/// Positions of a user, open ones first, only those still held with open_only
+pub async fn find_user_positions(
+  db: &DatabaseConnection,
+  user_id: i32,
+  open_only: bool,
+) -> Result<Vec<positions::Model>> {
+  let mut query = positions::Entity::find().filter(positions::Column::UserId.eq(user_id));
+
+  if open_only {
+    query = query.filter(positions::Column::Quantity.gt(0));
+  }
+
+  let mut positions = query
+    .order_by_desc(positions::Column::UpdatedAt)
+    .all(db)
+    .await
+    .context("Failed to retrieve positions")?;
+
+  positions.sort_by_key(|position| position.quantity <= 0);
+
+  Ok(positions)
+}
@@ -1,7 +1,19 @@
 #![allow(dead_code)]
 use anyhow::Result;
 
-use crate::{integrations::raydium::RaydiumPriceFetcher, utils::cache::get_memcache_hash};
+use crate::{
+  integrations::{pump_fun::fetch_bonding_curve, raydium::RaydiumPriceFetcher},
+  utils::cache::get_memcache_hash,
+};
+
+// Raydium doesn't price tokens still on their pump.fun bonding curve, those are priced off the
+// curve instead
+async fn bonding_curve_price_in_sol(ticker: &str) -> Option<f64> {
+  match fetch_bonding_curve(ticker).await {
+    Ok(Some(curve)) if !curve.complete => Some(curve.price_in_sol()),
+    _ => None,
+  }
+}
 
 pub async fn fetch_token_price(ticker: &str) -> Result<f64> {
   // Attempt to get the cached price
@@ -27,6 +39,13 @@ pub async fn fetch_token_price(ticker: &str) -> Result<f64> {
   match token_price_in_usd {
     Ok(price) => Ok(price),
     Err(err) => {
+      if let Some(price_in_sol) = bonding_curve_price_in_sol(ticker).await {
+        let sol_price = raydium_client
+          .get_token_price_in_usd("So11111111111111111111111111111111111111112")
+          .await?;
+        return Ok(price_in_sol * sol_price);
+      }
+
       eprintln!("Error fetching token price: {:?}", err);
       Err(err.into())
     }
@@ -53,6 +72,10 @@ pub async fn fetch_token_sol_price(ticker: &str) -> Result<f64> {
   match token_price_in_usd {
     Ok(price) => Ok(price),
     Err(err) => {
+      if let Some(price_in_sol) = bonding_curve_price_in_sol(ticker).await {
+        return Ok(price_in_sol);
+      }
+
       eprintln!("Error fetching token price: {:?}", err);
       Err(err.into())
     }
@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Context, Result};
+use entity::user_settings;
+use sea_orm::{
+  sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+  QueryFilter, Set,
+};
+
+// launch classes from the raydium and pump.fun launch events that a user can opt into sniping
+pub const SNIPEABLE_LAUNCH_CLASSES: [&str; 4] =
+  ["curve_launch", "mid_launch", "pro_launch", "crazy_launch"];
+
+async fn find_user_settings(
+  db: &DatabaseConnection,
+  user_id: i32,
+) -> Result<Option<user_settings::Model>> {
+  user_settings::Entity::find()
+    .filter(user_settings::Column::UserId.eq(user_id))
+    .one(db)
+    .await
+    .context("Failed to retrieve user settings")
+}
+
+/// Fetch the settings of a user, creating a row with the column defaults if none exists yet
+pub async fn find_or_create_user_settings(
+  db: &DatabaseConnection,
+  user_id: i32,
+) -> Result<user_settings::Model> {
+  if let Some(settings) = find_user_settings(db, user_id).await? {
+    return Ok(settings);
+  }
+
+  let new_settings = user_settings::ActiveModel {
+    user_id: Set(user_id),
+    ..Default::default()
+  };
+
+  // a concurrent call may have created the row since, in which case its row is the one read
+  let inserted = user_settings::Entity::insert(new_settings)
+    .on_conflict(
+      OnConflict::column(user_settings::Column::UserId)
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec(db)
+    .await;
+
+  match inserted {
+    Ok(_) | Err(DbErr::RecordNotInserted) => {}
+    Err(e) => return Err(e).context("Failed to create user settings"),
+  }
+
+  find_user_settings(db, user_id)
+    .await?
+    .ok_or_else(|| anyhow!("No settings found for user {}", user_id))
+}
+
+pub fn follows_launch_class(settings: &user_settings::Model, launch_class: &str) -> bool {
+  settings
+    .launch_classes
+    .split(',')
+    .any(|class| class.trim() == launch_class)
+}
+
+pub fn snipes_launch_class(settings: &user_settings::Model, launch_class: &str) -> bool {
+  settings.auto_buy && follows_launch_class(settings, launch_class)
+}
+
+pub fn parse_launch_classes(value: &str) -> Result<String> {
+  let classes: Vec<&str> = value
+    .split(',')
+    .map(|class| class.trim())
+    .filter(|class| !class.is_empty())
+    .collect();
+
+  for class in &classes {
+    if !SNIPEABLE_LAUNCH_CLASSES.contains(class) {
+      return Err(anyhow!(
+        "Unknown launch class {}. Valid classes are: {}",
+        class,
+        SNIPEABLE_LAUNCH_CLASSES.join(", ")
+      ));
+    }
+  }
+
+  Ok(classes.join(","))
+}
+
+fn parse_percentage(key: &str, value: &str) -> Result<f32> {
+  let percentage = value
+    .parse::<f32>()
+    .map_err(|_| anyhow!("{} must be a number", key))?;
+
+  if !(0.0..=100.0).contains(&percentage) {
+    return Err(anyhow!("{} must be between 0 and 100", key));
+  }
+
+  Ok(percentage)
+}
+
+fn parse_sol_amount(key: &str, value: &str) -> Result<f32> {
+  let amount = value
+    .parse::<f32>()
+    .map_err(|_| anyhow!("{} must be a number", key))?;
+
+  if amount < 0.0 {
+    return Err(anyhow!("{} cannot be negative", key));
+  }
+
+  Ok(amount)
+}
+
+fn parse_toggle(key: &str, value: &str) -> Result<bool> {
+  match value {
+    "on" | "true" | "yes" => Ok(true),
+    "off" | "false" | "no" => Ok(false),
+    _ => Err(anyhow!("{} must be on or off", key)),
+  }
+}
+
+fn parse_bps(key: &str, value: &str) -> Result<i32> {
+  value
+    .parse::<i32>()
+    .map_err(|_| anyhow!("{} must be a whole number", key))
+}
+
+pub fn validate_user_settings(settings: &user_settings::Model) -> Result<()> {
+  if settings.min_sol_per_trade > settings.max_sol_per_trade {
+    return Err(anyhow!(
+      "min_sol ({}) cannot be greater than max_sol ({})",
+      settings.min_sol_per_trade,
+      settings.max_sol_per_trade
+    ));
+  }
+
+  for (key, bps) in [
+    ("slippage_bps", settings.slippage_bps),
+    ("sell_slippage_bps", settings.sell_slippage_bps),
+    ("max_buy_slippage_bps", settings.max_buy_slippage_bps),
+    ("max_sell_slippage_bps", settings.max_sell_slippage_bps),
+    (
+      "max_stop_loss_slippage_bps",
+      settings.max_stop_loss_slippage_bps,
+    ),
+  ] {
+    if bps <= 0 || bps > 10_000 {
+      return Err(anyhow!("{} must be between 1 and 10000", key));
+    }
+  }
+
+  // escalation starts from the initial slippage, so a cap below it would never be used
+  if settings.max_buy_slippage_bps < settings.slippage_bps {
+    return Err(anyhow!(
+      "max_buy_slippage_bps ({}) cannot be lower than slippage_bps ({})",
+      settings.max_buy_slippage_bps,
+      settings.slippage_bps
+    ));
+  }
+
+  for (key, cap) in [
+    ("max_sell_slippage_bps", settings.max_sell_slippage_bps),
+    (
+      "max_stop_loss_slippage_bps",
+      settings.max_stop_loss_slippage_bps,
+    ),
+  ] {
+    if cap < settings.sell_slippage_bps {
+      return Err(anyhow!(
+        "{} ({}) cannot be lower than sell_slippage_bps ({})",
+        key,
+        cap,
+        settings.sell_slippage_bps
+      ));
+    }
+  }
+
+  Ok(())
+}
+
+/// Apply a single `key value` pair, as sent through the bot, to the user's settings
+pub async fn update_user_setting(
+  db: &DatabaseConnection,
+  settings: user_settings::Model,
+  key: &str,
+  value: &str,
+) -> Result<user_settings::Model> {
+  let mut updated = settings.clone();
+
+  match key {
+    "auto_buy" => updated.auto_buy = parse_toggle(key, value)?,
+    "paper_trading" => updated.paper_trading = parse_toggle(key, value)?,
+    "max_price_impact" => updated.max_price_impact_percentage = parse_percentage(key, value)?,
+    "buy_size" => updated.buy_size_percentage = parse_percentage(key, value)?,
+    "min_sol" => updated.min_sol_per_trade = parse_sol_amount(key, value)?,
+    "max_sol" => updated.max_sol_per_trade = parse_sol_amount(key, value)?,
+    "launch_stop_loss" => updated.launch_stop_loss_percentage = parse_percentage(key, value)?,
+    "default_stop_loss" => updated.default_stop_loss_percentage = parse_percentage(key, value)?,
+    "min_watchlist_usd" => updated.min_watchlist_usd = parse_sol_amount(key, value)?,
+    "slippage_bps" => updated.slippage_bps = parse_bps(key, value)?,
+    "sell_slippage_bps" => updated.sell_slippage_bps = parse_bps(key, value)?,
+    "max_buy_slippage_bps" => updated.max_buy_slippage_bps = parse_bps(key, value)?,
+    "max_sell_slippage_bps" => updated.max_sell_slippage_bps = parse_bps(key, value)?,
+    "max_stop_loss_slippage_bps" => updated.max_stop_loss_slippage_bps = parse_bps(key, value)?,
+    "launch_classes" => updated.launch_classes = parse_launch_classes(value)?,
+    "jito_tip" => updated.jito_tip_sol = parse_sol_amount(key, value)?,
+    "jito_launch_buys" => updated.jito_launch_buys = parse_toggle(key, value)?,
+    "jito_stop_losses" => updated.jito_stop_losses = parse_toggle(key, value)?,
+    _ => return Err(anyhow!("Unknown setting {}", key)),
+  }
+
+  save_user_settings(db, updated).await
+}
+
+pub async fn save_user_settings(
+  db: &DatabaseConnection,
+  settings: user_settings::Model,
+) -> Result<user_settings::Model> {
+  validate_user_settings(&settings)?;
+
+  let mut active_model: user_settings::ActiveModel = settings.into();
+  // Model -> ActiveModel conversion marks every column unchanged, so flag them for update
+  active_model = active_model.reset_all();
+  active_model.updated_at = Set(chrono::Utc::now().naive_utc());
+
+  active_model
+    .update(db)
+    .await
+    .context("Failed to update user settings")
+}
+
+pub fn format_user_settings(settings: &user_settings::Model) -> String {
+  format!(
+    "auto_buy: {}\nbuy_size: {}%\nmin_sol: {} SOL\nmax_sol: {} SOL\nlaunch_stop_loss: {}%\ndefault_stop_loss: {}%\nmin_watchlist_usd: ${}\nslippage_bps: {}\nsell_slippage_bps: {}\nmax_buy_slippage_bps: {}\nmax_sell_slippage_bps: {}\nmax_stop_loss_slippage_bps: {}\nlaunch_classes: {}\npaper_trading: {}\nmax_price_impact: {}%\njito_tip: {} SOL\njito_launch_buys: {}\njito_stop_losses: {}",
+    if settings.auto_buy { "on" } else { "off" },
+    settings.buy_size_percentage,
+    settings.min_sol_per_trade,
+    settings.max_sol_per_trade,
+    settings.launch_stop_loss_percentage,
+    settings.default_stop_loss_percentage,
+    settings.min_watchlist_usd,
+    settings.slippage_bps,
+    settings.sell_slippage_bps,
+    settings.max_buy_slippage_bps,
+    settings.max_sell_slippage_bps,
+    settings.max_stop_loss_slippage_bps,
+    settings.launch_classes,
+    if settings.paper_trading { "on" } else { "off" },
+    settings.max_price_impact_percentage,
+    settings.jito_tip_sol,
+    if settings.jito_launch_buys { "on" } else { "off" },
+    if settings.jito_stop_losses { "on" } else { "off" }
+  )
+}
@@ -0,0 +1,276 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Utc};
+use entity::{onchain_transactions, users};
+use sea_orm::{
+  sea_query::Expr, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncClient;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::TransactionStatus;
+use std::{env, str::FromStr};
+
+use crate::utils::{
+  dca::refund_dca_buy,
+  notifications::notify_user_by_telegram,
+  orders::submitted::{fail_submitted_order, fill_submitted_order, FailedOrder},
+  positions::try_apply_fill_to_position,
+  swap::{
+    fill::{record_swap_fill, SwapFill},
+    preflight::failure_reason,
+  },
+};
+
+// Swaps are sent without waiting for them to land. Their rows stay "submitted" until this job
+// finds their signature confirmed or failed on chain, or gives up on them once their blockhash
+// is long expired, and the user is told how it ended.
+
+// a transaction's blockhash expires after about a minute, one unseen for longer never lands
+const EXPIRE_AFTER_SECONDS: i64 = 180;
+
+// the most signatures the RPC takes in one status request
+const MAX_SIGNATURES_PER_REQUEST: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+  Confirmed,
+  Failed(String),
+  Expired,
+}
+
+impl Outcome {
+  fn status(&self) -> &'static str {
+    match self {
+      Outcome::Confirmed => "confirmed",
+      Outcome::Failed(_) => "failed",
+      Outcome::Expired => "expired",
+    }
+  }
+
+  // what an order or plan counting on the swap is told when it didn't land
+  fn failure_reason(&self) -> Option<String> {
+    match self {
+      Outcome::Confirmed => None,
+      Outcome::Failed(reason) => Some(format!("Swap failed on chain: {}", reason)),
+      Outcome::Expired => Some("Swap expired without landing".to_string()),
+    }
+  }
+}
+
+fn status_outcome(status: Option<&TransactionStatus>) -> Option<Outcome> {
+  let status = status?;
+
+  // outcomes are only taken once the cluster can't roll them back
+  if !status.satisfies_commitment(CommitmentConfig::confirmed()) {
+    return None;
+  }
+
+  match &status.err {
+    Some(error) => Some(Outcome::Failed(failure_reason(error, &[]))),
+    None => Some(Outcome::Confirmed),
+  }
+}
+
+fn outcome_message(
+  transaction: &onchain_transactions::Model,
+  outcome: &Outcome,
+  fill: Option<&SwapFill>,
+) -> String {
+  let transaction_hash = transaction.transaction_hash.as_deref().unwrap_or_default();
+
+  match outcome {
+    Outcome::Confirmed => match fill {
+      Some(fill) => format!(
+        "Swap confirmed ✅\n{} tokens for {:.4} SOL, at {} SOL each\nTransaction Hash: {}",
+        fill.token_amount, fill.sol_amount, fill.effective_price, transaction_hash
+      ),
+      None => format!("Swap confirmed ✅\nTransaction Hash: {}", transaction_hash),
+    },
+    Outcome::Failed(reason) => format!(
+      "Swap failed on chain: {}\nTransaction Hash: {}",
+      reason, transaction_hash
+    ),
+    Outcome::Expired => format!(
+      "Swap expired without landing\nTransaction Hash: {}",
+      transaction_hash
+    ),
+  }
+}
+
+// moves the row out of "submitted" only if no other run did first, false when one did
+async fn settle_submitted_transaction(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+  outcome: &Outcome,
+) -> Result<bool> {
+  let updated = onchain_transactions::Entity::update_many()
+    .col_expr(
+      onchain_transactions::Column::Status,
+      Expr::value(outcome.status()),
+    )
+    .col_expr(
+      onchain_transactions::Column::UpdatedAt,
+      Expr::value(Utc::now().naive_utc()),
+    )
+    .filter(onchain_transactions::Column::Id.eq(transaction_id))
+    .filter(onchain_transactions::Column::Status.eq("submitted"))
+    .exec(db)
+    .await
+    .context("Failed to update transaction status")?;
+
+  Ok(updated.rows_affected > 0)
+}
+
+// takes back what the order or DCA plan that sent a swap counted on it, returning what the
+// user is told about it
+async fn unwind_failed_swap(
+  db: &DatabaseConnection,
+  transaction: &onchain_transactions::Model,
+  reason: &str,
+) -> String {
+  match fail_submitted_order(db, transaction.id, reason).await {
+    Ok(Some(FailedOrder { order, reactivated })) => {
+      return if reactivated {
+        format!("\nOrder #{} is active again and retries", order.id)
+      } else {
+        format!("\nOrder #{} failed", order.id)
+      };
+    }
+    Ok(None) => {}
+    Err(e) => eprintln!(
+      "Failed to fail the order of transaction {}: {:?}",
+      transaction.id, e
+    ),
+  }
+
+  match refund_dca_buy(db, transaction).await {
+    Ok(Some(plan)) => format!(
+      "\nDCA plan {}: buy not counted ({} of {} SOL spent)",
+      plan.id, plan.spent_sol, plan.total_budget
+    ),
+    Ok(None) => String::new(),
+    Err(e) => {
+      eprintln!(
+        "Failed to refund the DCA buy of transaction {}: {:?}",
+        transaction.id, e
+      );
+      String::new()
+    }
+  }
+}
+
+async fn notify_transaction_user(
+  db: &DatabaseConnection,
+  transaction: &onchain_transactions::Model,
+  message: &str,
+) -> Result<()> {
+  let user = users::Entity::find_by_id(transaction.user_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve user")?;
+
+  if let Some(tg_id) = user.and_then(|user| user.tg_id.parse::<i64>().ok()) {
+    if let Err(e) = notify_user_by_telegram(tg_id, message).await {
+      eprintln!("Error notifying user {}: {}", tg_id, e);
+    }
+  }
+
+  Ok(())
+}
+
+/// Settle every submitted transaction whose outcome is known and tell its user
+pub async fn track_submitted_transactions(db: &DatabaseConnection) -> Result<()> {
+  let submitted = onchain_transactions::Entity::find()
+    .filter(onchain_transactions::Column::Status.eq("submitted"))
+    .filter(onchain_transactions::Column::TransactionHash.is_not_null())
+    .order_by_asc(onchain_transactions::Column::UpdatedAt)
+    .all(db)
+    .await
+    .context("Failed to retrieve submitted transactions")?;
+
+  if submitted.is_empty() {
+    return Ok(());
+  }
+
+  let rpc_url =
+    env::var("SOLANA_RPC_URL").context("Failed to retrieve SOLANA_RPC_URL from environment")?;
+  let client = AsyncClient::new(rpc_url);
+
+  let expire_before = Utc::now().naive_utc() - Duration::seconds(EXPIRE_AFTER_SECONDS);
+
+  for transactions in submitted.chunks(MAX_SIGNATURES_PER_REQUEST) {
+    let mut tracked = vec![];
+
+    for transaction in transactions {
+      let transaction_hash = transaction.transaction_hash.as_deref().unwrap_or_default();
+
+      match Signature::from_str(transaction_hash) {
+        Ok(signature) => tracked.push((transaction, signature)),
+        Err(_) => eprintln!(
+          "Transaction {} has an invalid signature {}",
+          transaction.id, transaction_hash
+        ),
+      }
+    }
+
+    let signatures: Vec<Signature> = tracked.iter().map(|(_, signature)| *signature).collect();
+
+    // the history is searched too, a run that fell behind still finds transactions that landed
+    let statuses = client
+      .get_signature_statuses_with_history(&signatures)
+      .await
+      .map_err(|e| anyhow!("Failed to fetch signature statuses: {}", e))?
+      .value;
+
+    for ((transaction, _), status) in tracked.into_iter().zip(statuses) {
+      let outcome = match status_outcome(status.as_ref()) {
+        Some(outcome) => outcome,
+        None if status.is_none() && transaction.updated_at < expire_before => Outcome::Expired,
+        None => continue,
+      };
+
+      if !settle_submitted_transaction(db, transaction.id, &outcome).await? {
+        continue;
+      }
+
+      // the order that sent the swap is filled before the fill reaches its position, so a stop
+      // loss it attaches is anchored to the position's entry
+      if outcome == Outcome::Confirmed {
+        if let Err(e) = fill_submitted_order(db, transaction.id).await {
+          eprintln!(
+            "Failed to fill the order of transaction {}: {:?}",
+            transaction.id, e
+          );
+        }
+      }
+
+      // the requested size stays on the row when the fill can't be read
+      let fill = match outcome {
+        Outcome::Confirmed => match record_swap_fill(db, transaction).await {
+          Ok(Some(fill)) => {
+            try_apply_fill_to_position(db, transaction.id).await;
+            Some(fill)
+          }
+          Ok(None) => None,
+          Err(e) => {
+            eprintln!(
+              "Failed to record fill of transaction {}: {:?}",
+              transaction.id, e
+            );
+            None
+          }
+        },
+        _ => None,
+      };
+
+      let mut message = outcome_message(transaction, &outcome, fill.as_ref());
+
+      if let Some(reason) = outcome.failure_reason() {
+        message.push_str(&unwind_failed_swap(db, transaction, &reason).await);
+      }
+
+      notify_transaction_user(db, transaction, &message).await?;
+    }
+  }
+
+  Ok(())
+}
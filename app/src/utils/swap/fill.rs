@@ -0,0 +1,192 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use entity::{onchain_transactions, wallets};
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
+
+use crate::utils::{
+  price::solana::fetch_token_price,
+  wallets::activity::{fetch_balance_changes, WalletBalanceChanges},
+};
+
+// A sent swap's row holds the size that was asked for until its transaction confirms. What
+// actually moved is then read back from the wallet's balance changes in it, so PnL is worked out
+// from real fills. The rent of a token account the swap opened or closed counts as part of it.
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// What a confirmed swap actually filled at, amounts in the smallest unit of each mint
+#[derive(Debug, Clone, Copy)]
+pub struct SwapFill {
+  pub input_amount: u64,
+  pub output_amount: u64,
+  pub sol_amount: f64,
+  pub token_amount: f64,
+  // SOL per token
+  pub effective_price: f64,
+  pub network_fee_lamports: u64,
+  pub priority_fee_lamports: u64,
+}
+
+/// The fill of a swap between SOL and a token, from the balance changes of its transaction
+pub fn swap_fill(
+  from_token: &str,
+  to_token: &str,
+  changes: &WalletBalanceChanges,
+) -> Result<SwapFill> {
+  let (mint, is_buy) = match (from_token, to_token) {
+    (SOL_MINT, mint) => (mint, true),
+    (mint, SOL_MINT) => (mint, false),
+    _ => return Err(anyhow!("Only swaps between SOL and a token have fills")),
+  };
+
+  let token_change = changes
+    .token_changes
+    .iter()
+    .find(|change| change.mint == mint)
+    .ok_or_else(|| anyhow!("The transaction moved no {}", mint))?;
+
+  let lamports = changes.sol_delta.unsigned_abs() as u64;
+  let token_units = token_change.delta.unsigned_abs() as u64;
+
+  if is_buy != (token_change.delta > 0) || is_buy == (changes.sol_delta > 0) {
+    return Err(anyhow!(
+      "The transaction didn't swap between SOL and {}",
+      mint
+    ));
+  }
+
+  let sol_amount = changes.sol_amount();
+  let token_amount = token_change.ui_amount();
+
+  let (input_amount, output_amount) = if is_buy {
+    (lamports, token_units)
+  } else {
+    (token_units, lamports)
+  };
+
+  Ok(SwapFill {
+    input_amount,
+    output_amount,
+    sol_amount,
+    token_amount,
+    effective_price: if token_amount > 0.0 {
+      sol_amount / token_amount
+    } else {
+      0.0
+    },
+    network_fee_lamports: changes.network_fee_lamports,
+    priority_fee_lamports: changes.priority_fee_lamports,
+  })
+}
+
+/// Replace the requested size of a confirmed swap's row with what it filled at. None when its
+/// transaction made no balance changes to read.
+pub async fn record_swap_fill(
+  db: &DatabaseConnection,
+  transaction: &onchain_transactions::Model,
+) -> Result<Option<SwapFill>> {
+  let transaction_hash = transaction
+    .transaction_hash
+    .as_deref()
+    .ok_or_else(|| anyhow!("Transaction {} has no hash", transaction.id))?;
+
+  let (from_token, to_token) = match (&transaction.from_token, &transaction.to_token) {
+    (Some(from_token), Some(to_token)) => (from_token, to_token),
+    _ => return Err(anyhow!("Transaction {} has no tokens", transaction.id)),
+  };
+
+  let wallet = wallets::Entity::find_by_id(transaction.wallet_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve wallet")?
+    .ok_or_else(|| anyhow!("No wallet found for transaction {}", transaction.id))?;
+
+  let changes = match fetch_balance_changes(&wallet.address, transaction_hash).await? {
+    Some(changes) => changes,
+    None => return Ok(None),
+  };
+
+  let fill = swap_fill(from_token, to_token, &changes)?;
+
+  let sol_price = fetch_token_price(SOL_MINT).await.ok();
+
+  let fill_update_model = onchain_transactions::ActiveModel {
+    id: Set(transaction.id),
+    input_amount: Set(Some(fill.input_amount as i64)),
+    output_amount: Set(Some(fill.output_amount as i64)),
+    value_native: Set(Some(fill.sol_amount as f32)),
+    value_usd: Set(
+      sol_price
+        .map(|sol_price| (fill.sol_amount * sol_price) as f32)
+        .or(transaction.value_usd),
+    ),
+    effective_price: Set(Some(fill.effective_price as f32)),
+    network_fee_lamports: Set(Some(fill.network_fee_lamports as i64)),
+    priority_fee_lamports: Set(Some(fill.priority_fee_lamports as i64)),
+    updated_at: Set(Utc::now().naive_utc()),
+    ..Default::default()
+  };
+
+  onchain_transactions::Entity::update(fill_update_model)
+    .exec(db)
+    .await
+    .context("Failed to record swap fill")?;
+
+  Ok(Some(fill))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::wallets::activity::TokenBalanceChange;
+
+  fn changes(sol_delta: i128, token_delta: i128) -> WalletBalanceChanges {
+    WalletBalanceChanges {
+      sol_delta,
+      token_changes: vec![TokenBalanceChange {
+        mint: "token".to_string(),
+        delta: token_delta,
+        pre_amount: 10_000_000,
+        decimals: 6,
+      }],
+      network_fee_lamports: 5_000,
+      priority_fee_lamports: 1_000,
+    }
+  }
+
+  #[test]
+  fn a_buy_fills_sol_in_and_tokens_out() {
+    let fill = swap_fill(SOL_MINT, "token", &changes(-500_000_000, 2_000_000)).unwrap();
+
+    assert_eq!(fill.input_amount, 500_000_000);
+    assert_eq!(fill.output_amount, 2_000_000);
+    assert_eq!(fill.sol_amount, 0.5);
+    assert_eq!(fill.token_amount, 2.0);
+    assert_eq!(fill.effective_price, 0.25);
+    assert_eq!(fill.network_fee_lamports, 5_000);
+    assert_eq!(fill.priority_fee_lamports, 1_000);
+  }
+
+  #[test]
+  fn a_sell_fills_tokens_in_and_sol_out() {
+    let fill = swap_fill("token", SOL_MINT, &changes(300_000_000, -1_000_000)).unwrap();
+
+    assert_eq!(fill.input_amount, 1_000_000);
+    assert_eq!(fill.output_amount, 300_000_000);
+    assert_eq!(fill.effective_price, 0.3);
+  }
+
+  #[test]
+  fn balances_moving_the_wrong_way_are_not_a_fill() {
+    // the buy took tokens out of the wallet
+    assert!(swap_fill(SOL_MINT, "token", &changes(-500_000_000, -2_000_000)).is_err());
+    // the sell cost SOL
+    assert!(swap_fill("token", SOL_MINT, &changes(-5_000, -1_000_000)).is_err());
+  }
+
+  #[test]
+  fn only_swaps_between_sol_and_the_token_fill() {
+    assert!(swap_fill("other", SOL_MINT, &changes(300_000_000, -1_000_000)).is_err());
+    assert!(swap_fill("token", "other", &changes(0, -1_000_000)).is_err());
+  }
+}
@@ -0,0 +1,205 @@
+use anyhow::{anyhow, Result};
+
+use crate::utils::swap::{provider::SwapQuote, slippage::SwapKind};
+
+// A user's max price impact caps what a swap can lose to the pool: the quoted price impact plus
+// the slippage its minimum output still allows. Thin launch pools blow through it at sizes that
+// look harmless. Slippage raised by escalation, and all of a stop loss's, is bounded by the
+// kind's own slippage cap instead, so only the price impact is held to the limit there.
+
+// buys are downsized to no less than this share of the requested size before being rejected
+pub const MIN_DOWNSIZE_FRACTION: f64 = 0.25;
+
+/// What of a quote is held to the price impact limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteCheck {
+  // the price impact plus the slippage the minimum output allows
+  Full,
+  // the price impact alone
+  PriceImpactOnly,
+}
+
+impl QuoteCheck {
+  /// Check for an attempt of a swap of `kind`, `escalated` once its slippage was raised
+  pub fn for_attempt(kind: SwapKind, escalated: bool) -> QuoteCheck {
+    if escalated || kind == SwapKind::StopLossSell {
+      QuoteCheck::PriceImpactOnly
+    } else {
+      QuoteCheck::Full
+    }
+  }
+}
+
+/// Share of the quoted output the minimum output lets go, in percent
+pub fn slippage_allowance_percentage(quote: &SwapQuote) -> Result<f64> {
+  let output_amount = quote.output_amount;
+  let minimum_output = quote.minimum_output_amount;
+
+  if output_amount == 0 {
+    return Err(anyhow!("Quote has no output"));
+  }
+
+  if minimum_output > output_amount {
+    return Err(anyhow!(
+      "Quote minimum output {} is above its output {}",
+      minimum_output,
+      output_amount
+    ));
+  }
+
+  Ok((output_amount - minimum_output) as f64 / output_amount as f64 * 100.0)
+}
+
+/// Reject a quote that is malformed or would lose more than `max_price_impact_percentage`
+pub fn check_swap_quote(
+  quote: &SwapQuote,
+  max_price_impact_percentage: f32,
+  check: QuoteCheck,
+) -> Result<()> {
+  let limit = max_price_impact_percentage as f64;
+  let price_impact = quote.price_impact_pct;
+  let slippage_allowance = slippage_allowance_percentage(quote)?;
+
+  if price_impact > limit {
+    return Err(anyhow!(
+      "Price impact of {:.2}% is above your limit of {}%",
+      price_impact,
+      limit
+    ));
+  }
+
+  if check == QuoteCheck::Full && price_impact + slippage_allowance > limit {
+    return Err(anyhow!(
+      "Minimum output is {:.2}% below the market price, above your limit of {}%",
+      price_impact + slippage_allowance,
+      limit
+    ));
+  }
+
+  Ok(())
+}
+
+/// Share of the quoted size a buy can keep and stay within the limit, assuming price impact
+/// grows with size. None when even the smallest downsized buy would be past it.
+pub fn downsize_fraction(
+  quote: &SwapQuote,
+  max_price_impact_percentage: f32,
+) -> Result<Option<f64>> {
+  let slippage_allowance = slippage_allowance_percentage(quote)?;
+  let impact_budget = max_price_impact_percentage as f64 - slippage_allowance;
+
+  if impact_budget <= 0.0 || quote.price_impact_pct <= 0.0 {
+    return Ok(None);
+  }
+
+  // a little under the budget, impact doesn't grow exactly linearly
+  let fraction = (impact_budget / quote.price_impact_pct * 0.9).min(1.0);
+
+  Ok(Some(fraction).filter(|fraction| *fraction >= MIN_DOWNSIZE_FRACTION))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::swap::{provider::SwapRoute, slippage::SlippagePolicy};
+  use serde_json::Value;
+
+  fn quote(price_impact_pct: f64, slippage_bps: u32) -> SwapQuote {
+    let output_amount = 1_000_000;
+
+    SwapQuote {
+      route: SwapRoute::Raydium,
+      input_mint: "in".to_string(),
+      output_mint: "out".to_string(),
+      input_amount: 1_000,
+      output_amount,
+      minimum_output_amount: output_amount * (10_000 - slippage_bps as u64) / 10_000,
+      slippage_bps,
+      price_impact_pct,
+      raw: Value::Null,
+    }
+  }
+
+  #[test]
+  fn full_check_counts_slippage_allowance() {
+    assert!(check_swap_quote(&quote(5.0, 400), 10.0, QuoteCheck::Full).is_ok());
+    assert!(check_swap_quote(&quote(5.0, 600), 10.0, QuoteCheck::Full).is_err());
+  }
+
+  #[test]
+  fn price_impact_above_limit_is_always_refused() {
+    for check in [QuoteCheck::Full, QuoteCheck::PriceImpactOnly] {
+      assert!(check_swap_quote(&quote(12.0, 50), 10.0, check).is_err());
+    }
+  }
+
+  #[test]
+  fn malformed_quotes_are_refused() {
+    let mut no_output = quote(1.0, 50);
+    no_output.output_amount = 0;
+    no_output.minimum_output_amount = 0;
+    assert!(check_swap_quote(&no_output, 10.0, QuoteCheck::PriceImpactOnly).is_err());
+
+    let mut minimum_above_output = quote(1.0, 50);
+    minimum_above_output.minimum_output_amount = minimum_above_output.output_amount + 1;
+    assert!(check_swap_quote(&minimum_above_output, 10.0, QuoteCheck::PriceImpactOnly).is_err());
+  }
+
+  #[test]
+  fn only_first_attempts_of_non_stop_loss_swaps_get_the_full_check() {
+    assert_eq!(
+      QuoteCheck::for_attempt(SwapKind::Buy, false),
+      QuoteCheck::Full
+    );
+    assert_eq!(
+      QuoteCheck::for_attempt(SwapKind::Sell, false),
+      QuoteCheck::Full
+    );
+    assert_eq!(
+      QuoteCheck::for_attempt(SwapKind::Sell, true),
+      QuoteCheck::PriceImpactOnly
+    );
+    assert_eq!(
+      QuoteCheck::for_attempt(SwapKind::StopLossSell, false),
+      QuoteCheck::PriceImpactOnly
+    );
+  }
+
+  #[test]
+  fn stop_loss_escalates_to_its_cap() {
+    // defaults of the user settings: 100 bps sells, 2500 bps stop loss cap, 10% price impact
+    let policy = SlippagePolicy {
+      initial_bps: 100,
+      max_bps: 2_500,
+    };
+    let mut slippage_bps = policy.initial_bps;
+    let mut attempts = vec![];
+
+    loop {
+      let check =
+        QuoteCheck::for_attempt(SwapKind::StopLossSell, slippage_bps > policy.initial_bps);
+      assert!(check_swap_quote(&quote(6.0, slippage_bps), 10.0, check).is_ok());
+      attempts.push(slippage_bps);
+
+      // every attempt fails on slippage
+      match policy.escalate(slippage_bps) {
+        Some(next_slippage_bps) => slippage_bps = next_slippage_bps,
+        None => break,
+      }
+    }
+
+    assert_eq!(attempts, vec![100, 200, 400, 800, 1_600, 2_500]);
+  }
+
+  #[test]
+  fn downsizes_buys_into_the_limit() {
+    // 4% of the 10% limit goes to slippage, leaving 6% for a quote at 12% impact
+    let fraction = downsize_fraction(&quote(12.0, 400), 10.0).unwrap().unwrap();
+    assert!((fraction - 0.45).abs() < 1e-9);
+
+    // too small a buy to be worth making
+    assert_eq!(downsize_fraction(&quote(40.0, 400), 10.0).unwrap(), None);
+    // slippage alone uses the whole limit
+    assert_eq!(downsize_fraction(&quote(12.0, 1_000), 10.0).unwrap(), None);
+  }
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use entity::onchain_transactions;
+use sea_orm::{
+  ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+pub const HISTORY_PAGE_SIZE: u64 = 10;
+
+/// A page of a user's transactions, newest first, along with how many pages there are.
+/// Pages count from 0.
+pub async fn find_user_transactions_page(
+  db: &DatabaseConnection,
+  user_id: i32,
+  page: u64,
+) -> Result<(Vec<onchain_transactions::Model>, u64)> {
+  let paginator = onchain_transactions::Entity::find()
+    .filter(onchain_transactions::Column::UserId.eq(user_id))
+    .order_by_desc(onchain_transactions::Column::CreatedAt)
+    .order_by_desc(onchain_transactions::Column::Id)
+    .paginate(db, HISTORY_PAGE_SIZE);
+
+  let pages = paginator
+    .num_pages()
+    .await
+    .context("Failed to count transactions")?;
+
+  let transactions = paginator
+    .fetch_page(page)
+    .await
+    .context("Failed to retrieve transactions")?;
+
+  Ok((transactions, pages))
+}
+
+pub fn solscan_transaction_url(transaction_hash: &str) -> String {
+  format!("https://solscan.io/tx/{}", transaction_hash)
+}
+
+pub fn format_transaction(transaction: &onchain_transactions::Model) -> String {
+  let from_token = transaction.from_token.as_deref().unwrap_or_default();
+  let to_token = transaction.to_token.as_deref().unwrap_or_default();
+
+  let (side, token) = match (from_token, to_token) {
+    (SOL_MINT, token) => ("Buy", token),
+    (token, SOL_MINT) => ("Sell", token),
+    (_, token) => ("Swap", token),
+  };
+
+  let mut lines = vec![format!(
+    "#{} {} {} [{}{}]",
+    transaction.id,
+    side,
+    token,
+    transaction.status.as_deref().unwrap_or("unknown"),
+    if transaction.simulated { ", paper" } else { "" }
+  )];
+
+  if let Some(value_native) = transaction.value_native {
+    let fill = match transaction.effective_price {
+      Some(effective_price) if effective_price > 0.0 => format!(
+        " for {} tokens at {} SOL",
+        value_native / effective_price,
+        effective_price
+      ),
+      _ => String::new(),
+    };
+
+    lines.push(format!(
+      "{:.4} SOL (${:.2}){}",
+      value_native,
+      transaction.value_usd.unwrap_or(0.0),
+      fill
+    ));
+  }
+
+  lines.push(
+    transaction
+      .created_at
+      .format("%Y-%m-%d %H:%M UTC")
+      .to_string(),
+  );
+
+  // paper trades never went on chain
+  if let Some(transaction_hash) = transaction
+    .transaction_hash
+    .as_deref()
+    .filter(|_| !transaction.simulated)
+  {
+    lines.push(solscan_transaction_url(transaction_hash));
+  }
+
+  lines.join("\n")
+}
@@ -0,0 +1,95 @@
+use anyhow::{anyhow, Context, Result};
+use entity::user_settings;
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncClient;
+use solana_sdk::{
+  signature::{Keypair, Signature},
+  signer::Signer,
+  system_instruction,
+  transaction::{Transaction, VersionedTransaction},
+};
+use std::env;
+
+use crate::{
+  integrations::jito::{JitoClient, MAX_BUNDLE_TXS, MIN_TIP_LAMPORTS},
+  utils::swap::{
+    preflight::{simulate_swap_tx, PreflightResult},
+    slippage::SwapKind,
+    solana::sign_swap_txs,
+  },
+};
+
+// Launch buys and stop-loss sells race everyone else for the same block. Users can have them
+// sent as a Jito bundle instead of through the RPC, paying a tip to land first and never paying
+// it, nor the network fee, when the swap would fail.
+
+/// The tip to send a swap of this kind with as a Jito bundle, None when the user sends it
+/// through the RPC
+pub fn jito_tip_lamports(settings: &user_settings::Model, kind: SwapKind) -> Option<u64> {
+  let opted_in = match kind {
+    SwapKind::LaunchBuy => settings.jito_launch_buys,
+    SwapKind::StopLossSell => settings.jito_stop_losses,
+    SwapKind::Buy | SwapKind::Sell => false,
+  };
+
+  if !opted_in {
+    return None;
+  }
+
+  let tip_lamports = (settings.jito_tip_sol as f64 * 1_000_000_000.0).floor() as u64;
+
+  Some(tip_lamports.max(MIN_TIP_LAMPORTS))
+}
+
+/// Sign the swap transactions and send them in one bundle with a tip transfer after them, each
+/// swap simulated before the bundle goes out. Returns the signatures of the swap transactions as
+/// soon as the block engine took the bundle, with the simulation of the last one; whether it
+/// landed is left to the confirmation job.
+pub async fn execute_jito_bundle_txs(
+  unsigned_txs: Vec<VersionedTransaction>,
+  keypair: &Keypair,
+  tip_lamports: u64,
+) -> Result<(Vec<Signature>, Option<PreflightResult>)> {
+  if unsigned_txs.len() >= MAX_BUNDLE_TXS {
+    return Err(anyhow!(
+      "{} swap transactions don't fit in a bundle with the tip",
+      unsigned_txs.len()
+    ));
+  }
+
+  let rpc_url =
+    env::var("SOLANA_RPC_URL").context("Failed to retrieve SOLANA_RPC_URL from environment")?;
+  let client = AsyncClient::new(rpc_url);
+
+  let recent_blockhash = client.get_latest_blockhash().await?;
+
+  let mut bundle = sign_swap_txs(unsigned_txs, keypair, recent_blockhash)?;
+  let signatures: Vec<Signature> = bundle.iter().map(|tx| tx.signatures[0]).collect();
+
+  // a bundle that would fail isn't sent, the same as a swap sent through the RPC
+  let mut preflight = None;
+  for tx in &bundle {
+    preflight = Some(simulate_swap_tx(&client, tx).await?);
+  }
+
+  let tip_instruction =
+    system_instruction::transfer(&keypair.pubkey(), &JitoClient::tip_account()?, tip_lamports);
+
+  bundle.push(
+    Transaction::new_signed_with_payer(
+      &[tip_instruction],
+      Some(&keypair.pubkey()),
+      &[keypair],
+      recent_blockhash,
+    )
+    .into(),
+  );
+
+  let bundle_id = JitoClient::new().send_bundle(&bundle).await?;
+
+  println!(
+    "Bundle {} sent with a tip of {} lamports",
+    bundle_id, tip_lamports
+  );
+
+  Ok((signatures, preflight))
+}
@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use entity::execution_locks;
+use sea_orm::{
+  sea_query::OnConflict, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+};
+use uuid::Uuid;
+
+// Swaps started by the price jobs take a lock on the order and on the wallet they trade from,
+// so the cron run and the price update path can't both send one. Locks are rows in
+// execution_locks so they hold across processes, and they expire in case a run dies holding one.
+
+// long enough for a swap to be sent and confirmed
+pub const EXECUTION_LOCK_TTL_SECONDS: i64 = 300;
+
+pub fn wallet_lock_key(wallet_id: i32) -> String {
+  format!("wallet:{}", wallet_id)
+}
+
+pub fn order_lock_key(order_id: i32) -> String {
+  format!("order:{}", order_id)
+}
+
+pub fn dca_plan_lock_key(plan_id: i32) -> String {
+  format!("dca_plan:{}", plan_id)
+}
+
+pub fn wallet_follow_lock_key(follow_id: i32) -> String {
+  format!("wallet_follow:{}", follow_id)
+}
+
+#[derive(Debug)]
+pub struct ExecutionLock {
+  owner: String,
+}
+
+async fn delete_locks(db: &DatabaseConnection, owner: &str) -> Result<()> {
+  execution_locks::Entity::delete_many()
+    .filter(execution_locks::Column::Owner.eq(owner))
+    .exec(db)
+    .await
+    .context("Failed to release execution lock")?;
+
+  Ok(())
+}
+
+/// Take every key or none of them. Returns None when another run holds one of the keys.
+pub async fn acquire_execution_lock(
+  db: &DatabaseConnection,
+  keys: Vec<String>,
+) -> Result<Option<ExecutionLock>> {
+  let now = Utc::now().naive_utc();
+
+  // left behind by a run that died before releasing them
+  execution_locks::Entity::delete_many()
+    .filter(execution_locks::Column::LockKey.is_in(keys.clone()))
+    .filter(execution_locks::Column::ExpiresAt.lte(now))
+    .exec(db)
+    .await
+    .context("Failed to clear expired execution locks")?;
+
+  let owner = Uuid::new_v4().to_string();
+
+  for key in keys {
+    let lock = execution_locks::ActiveModel {
+      lock_key: Set(key),
+      owner: Set(owner.clone()),
+      expires_at: Set(now + Duration::seconds(EXECUTION_LOCK_TTL_SECONDS)),
+      ..Default::default()
+    };
+
+    let inserted = execution_locks::Entity::insert(lock)
+      .on_conflict(
+        OnConflict::column(execution_locks::Column::LockKey)
+          .do_nothing()
+          .to_owned(),
+      )
+      .exec(db)
+      .await;
+
+    match inserted {
+      Ok(_) => {}
+      Err(DbErr::RecordNotInserted) => {
+        delete_locks(db, &owner).await?;
+        return Ok(None);
+      }
+      Err(e) => {
+        delete_locks(db, &owner).await?;
+        return Err(e).context("Failed to take execution lock");
+      }
+    }
+  }
+
+  Ok(Some(ExecutionLock { owner }))
+}
+
+pub async fn release_execution_lock(db: &DatabaseConnection, lock: ExecutionLock) -> Result<()> {
+  delete_locks(db, &lock.owner).await
+}
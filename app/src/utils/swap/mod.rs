@@ -1 +1,11 @@
+pub mod confirmation;
+pub mod fill;
+pub mod guard;
+pub mod history;
+pub mod jito;
+pub mod lock;
+pub mod paper;
+pub mod preflight;
+pub mod provider;
+pub mod slippage;
 pub mod solana;
@@ -0,0 +1,172 @@
+use anyhow::{anyhow, Context, Result};
+use entity::{onchain_transactions, wallets};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::utils::{
+  settings::find_or_create_user_settings,
+  swap::provider::SwapQuote,
+  wallets::solana::{get_token_balance, get_token_details, SplTokenBalance},
+};
+
+// Paper trading fills swaps from their quote instead of signing and sending them. Fills
+// are recorded in onchain_transactions with `simulated` set, and the token balances of a paper
+// wallet are what those fills add up to.
+
+// base fee of a single signature plus a typical priority fee
+pub const PAPER_NETWORK_FEE_LAMPORTS: u64 = 105_000;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Amounts of a simulated swap, in the smallest unit of each mint
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedFill {
+  pub input_amount: u64,
+  pub output_amount: u64,
+  pub network_fee_lamports: u64,
+}
+
+/// Whether swaps from a wallet are simulated. The wallet's own flag wins over the user setting.
+pub async fn is_paper_wallet(db: &DatabaseConnection, wallet: &wallets::Model) -> Result<bool> {
+  if let Some(paper_trading) = wallet.paper_trading {
+    return Ok(paper_trading);
+  }
+
+  let settings = find_or_create_user_settings(db, wallet.user_id).await?;
+
+  Ok(settings.paper_trading)
+}
+
+/// Fill a swap at the quoted output less the full slippage tolerance, so paper results don't
+/// look better than a real fill could. The network fee is taken from the SOL side.
+pub fn simulate_swap_fill(quote: &SwapQuote) -> SimulatedFill {
+  let slippage_bps = (quote.slippage_bps as u128).min(10_000);
+
+  // in u128, as the output of a token with many decimals times the bps overflows u64
+  let mut output_amount = (quote.output_amount as u128 * (10_000 - slippage_bps) / 10_000) as u64;
+
+  if quote.output_mint == SOL_MINT {
+    output_amount = output_amount.saturating_sub(PAPER_NETWORK_FEE_LAMPORTS);
+  }
+
+  SimulatedFill {
+    input_amount: quote.input_amount,
+    output_amount,
+    network_fee_lamports: PAPER_NETWORK_FEE_LAMPORTS,
+  }
+}
+
+/// Balance of a token in a paper wallet, from its simulated buys less its simulated sells
+pub async fn paper_token_balance(
+  db: &DatabaseConnection,
+  wallet_id: i32,
+  ca: &str,
+) -> Result<SplTokenBalance> {
+  let fills = onchain_transactions::Entity::find()
+    .filter(onchain_transactions::Column::WalletId.eq(wallet_id))
+    .filter(onchain_transactions::Column::Simulated.eq(true))
+    .filter(
+      onchain_transactions::Column::FromToken
+        .eq(ca)
+        .or(onchain_transactions::Column::ToToken.eq(ca)),
+    )
+    .all(db)
+    .await
+    .context("Failed to retrieve simulated transactions")?;
+
+  let amount = fills.iter().fold(0i64, |amount, fill| {
+    if fill.to_token.as_deref() == Some(ca) {
+      amount + fill.output_amount.unwrap_or(0)
+    } else {
+      amount - fill.input_amount.unwrap_or(0)
+    }
+  });
+
+  if amount <= 0 {
+    return Ok(SplTokenBalance {
+      amount: 0.0,
+      ui_amount: 0.0,
+    });
+  }
+
+  let token = get_token_details(ca)
+    .await
+    .map_err(|e| anyhow!("Failed to get token details: {}", e))?;
+
+  Ok(SplTokenBalance {
+    amount: amount as f64,
+    ui_amount: amount as f64 / 10f64.powi(token.decimals as i32),
+  })
+}
+
+/// Balance of a token in a wallet, simulated for paper wallets and on chain otherwise
+pub async fn wallet_token_balance(
+  db: &DatabaseConnection,
+  wallet: &wallets::Model,
+  ca: &str,
+) -> Result<SplTokenBalance> {
+  if is_paper_wallet(db, wallet).await? {
+    return paper_token_balance(db, wallet.id, ca).await;
+  }
+
+  get_token_balance(&wallet.address, ca)
+    .await
+    .map_err(|e| anyhow!("Failed to get balance: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::swap::provider::SwapRoute;
+  use serde_json::Value;
+
+  fn quote(output_mint: &str, output_amount: u64, slippage_bps: u32) -> SwapQuote {
+    SwapQuote {
+      route: SwapRoute::Raydium,
+      input_mint: "in".to_string(),
+      output_mint: output_mint.to_string(),
+      input_amount: 1_000_000,
+      output_amount,
+      minimum_output_amount: output_amount,
+      slippage_bps,
+      price_impact_pct: 0.5,
+      raw: Value::Null,
+    }
+  }
+
+  #[test]
+  fn buys_fill_at_the_quote_less_the_full_slippage() {
+    let fill = simulate_swap_fill(&quote("token", 2_000_000, 250));
+
+    assert_eq!(fill.input_amount, 1_000_000);
+    assert_eq!(fill.output_amount, 1_950_000);
+    assert_eq!(fill.network_fee_lamports, PAPER_NETWORK_FEE_LAMPORTS);
+  }
+
+  #[test]
+  fn sells_pay_the_network_fee_from_their_sol() {
+    let fill = simulate_swap_fill(&quote(SOL_MINT, 500_000_000, 100));
+
+    assert_eq!(fill.output_amount, 495_000_000 - PAPER_NETWORK_FEE_LAMPORTS);
+  }
+
+  #[test]
+  fn a_sell_worth_less_than_the_fee_fills_nothing() {
+    let fill = simulate_swap_fill(&quote(SOL_MINT, 50_000, 100));
+
+    assert_eq!(fill.output_amount, 0);
+  }
+
+  #[test]
+  fn slippage_past_100_percent_fills_nothing() {
+    let fill = simulate_swap_fill(&quote("token", 2_000_000, 15_000));
+
+    assert_eq!(fill.output_amount, 0);
+  }
+
+  #[test]
+  fn outputs_too_large_to_multiply_in_u64_still_fill() {
+    let fill = simulate_swap_fill(&quote("token", u64::MAX, 100));
+
+    assert_eq!(fill.output_amount, (u64::MAX as u128 * 99 / 100) as u64);
+  }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value as Json;
+use solana_client::{
+  nonblocking::rpc_client::RpcClient as AsyncClient, rpc_config::RpcSimulateTransactionConfig,
+};
+use solana_sdk::{
+  commitment_config::CommitmentConfig,
+  instruction::InstructionError,
+  transaction::{TransactionError, VersionedTransaction},
+};
+use std::fmt;
+
+use crate::utils::swap::slippage::is_slippage_message;
+
+// Swap transactions are simulated before they are sent, through the RPC or in a Jito bundle. One
+// that would fail stops the swap there, with the reason read from the simulation's error and
+// logs, instead of paying the network fee for a failed transaction.
+
+/// Outcome of simulating a swap transaction, kept on its onchain_transactions row
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightResult {
+  pub success: bool,
+  // why the simulation failed, in words
+  pub reason: Option<String>,
+  pub error: Option<String>,
+  pub units_consumed: Option<u64>,
+  pub logs: Vec<String>,
+}
+
+impl PreflightResult {
+  pub fn to_json(&self) -> Json {
+    serde_json::to_value(self).unwrap_or(Json::Null)
+  }
+}
+
+/// A swap stopped because its simulation failed
+#[derive(Debug, Clone)]
+pub struct PreflightError(pub PreflightResult);
+
+impl fmt::Display for PreflightError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Simulation failed: {}",
+      self.0.reason.as_deref().unwrap_or("unknown reason")
+    )
+  }
+}
+
+impl std::error::Error for PreflightError {}
+
+/// The simulation result behind a failed swap, if a simulation is what stopped it
+pub fn preflight_of_error(error: &anyhow::Error) -> Option<&PreflightResult> {
+  error
+    .downcast_ref::<PreflightError>()
+    .map(|preflight_error| &preflight_error.0)
+}
+
+/// Simulate a signed swap transaction, failing with a PreflightError when it would fail
+pub async fn simulate_swap_tx(
+  client: &AsyncClient,
+  tx: &VersionedTransaction,
+) -> Result<PreflightResult> {
+  let simulation = client
+    .simulate_transaction_with_config(
+      tx,
+      RpcSimulateTransactionConfig {
+        sig_verify: false,
+        commitment: Some(CommitmentConfig::processed()),
+        ..Default::default()
+      },
+    )
+    .await
+    .context("Failed to simulate transaction")?
+    .value;
+
+  let logs = simulation.logs.unwrap_or_default();
+
+  let error = match simulation.err {
+    Some(error) => error,
+    None => {
+      return Ok(PreflightResult {
+        success: true,
+        reason: None,
+        error: None,
+        units_consumed: simulation.units_consumed,
+        logs,
+      })
+    }
+  };
+
+  let result = PreflightResult {
+    success: false,
+    reason: Some(failure_reason(&error, &logs)),
+    error: Some(error.to_string()),
+    units_consumed: simulation.units_consumed,
+    logs,
+  };
+
+  Err(PreflightError(result).into())
+}
+
+/// Why a transaction failed, from its error and the logs of its programs
+pub fn failure_reason(error: &TransactionError, logs: &[String]) -> String {
+  let logs = logs.join("\n").to_lowercase();
+
+  match error {
+    TransactionError::AccountNotFound => "the wallet has no SOL".to_string(),
+    TransactionError::InsufficientFundsForFee => "insufficient SOL for the network fee".to_string(),
+    TransactionError::InsufficientFundsForRent { .. } => "insufficient SOL for rent".to_string(),
+    TransactionError::BlockhashNotFound => "the blockhash expired".to_string(),
+    TransactionError::InstructionError(index, instruction_error) => {
+      // the system program logs "insufficient lamports", the token program "insufficient funds"
+      if *instruction_error == InstructionError::InsufficientFunds
+        || logs.contains("insufficient lamports")
+        || logs.contains("insufficient funds")
+      {
+        return "insufficient funds".to_string();
+      }
+
+      if let InstructionError::Custom(code) = instruction_error {
+        if is_slippage_message(&format!("custom program error: {:#x}", code)) {
+          return "slippage exceeded".to_string();
+        }
+      }
+
+      if is_slippage_message(&logs) {
+        return "slippage exceeded".to_string();
+      }
+
+      if *instruction_error == InstructionError::UninitializedAccount
+        || logs.contains("accountnotinitialized")
+        || logs.contains("account not initialized")
+      {
+        return "account not initialized".to_string();
+      }
+
+      match instruction_error {
+        InstructionError::Custom(code) => {
+          format!("program error {} in instruction {}", code, index)
+        }
+        _ => format!("instruction {} failed: {}", index, instruction_error),
+      }
+    }
+    _ => error.to_string(),
+  }
+}
@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_sdk::transaction::VersionedTransaction;
+
+use crate::integrations::{
+  jupiter::JupiterSwapClient, pump_fun::PumpFunSwapClient, raydium::RaydiumPriceFetcher,
+};
+
+// Swaps are quoted on every route and made on the one giving the most output. A route that
+// fails to quote is left out, one that fails to build the transaction hands the swap to the
+// others, so a token whose liquidity left Raydium, or that is still on its pump.fun bonding
+// curve, can still be traded.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapRoute {
+  Raydium,
+  Jupiter,
+  PumpFun,
+}
+
+impl SwapRoute {
+  pub const ALL: [SwapRoute; 3] = [SwapRoute::Raydium, SwapRoute::Jupiter, SwapRoute::PumpFun];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      SwapRoute::Raydium => "raydium",
+      SwapRoute::Jupiter => "jupiter",
+      SwapRoute::PumpFun => "pump_fun",
+    }
+  }
+
+  pub fn provider(&self) -> Box<dyn SwapProvider> {
+    match self {
+      SwapRoute::Raydium => Box::new(RaydiumPriceFetcher::new()),
+      SwapRoute::Jupiter => Box::new(JupiterSwapClient::new()),
+      SwapRoute::PumpFun => Box::new(PumpFunSwapClient::new()),
+    }
+  }
+}
+
+/// A quote in the same shape whatever route it came from, amounts in the smallest unit of
+/// each mint
+#[derive(Debug, Clone)]
+pub struct SwapQuote {
+  pub route: SwapRoute,
+  pub input_mint: String,
+  pub output_mint: String,
+  pub input_amount: u64,
+  pub output_amount: u64,
+  // the least output the swap takes before failing on slippage
+  pub minimum_output_amount: u64,
+  pub slippage_bps: u32,
+  // in percent
+  pub price_impact_pct: f64,
+  // the route's own quote, handed back to it to build the transaction
+  pub raw: Value,
+}
+
+#[async_trait]
+pub trait SwapProvider: Send + Sync {
+  /// Quote swapping `amount` of the input mint, in its smallest unit
+  async fn quote_swap(
+    &self,
+    input_mint: &str,
+    output_mint: &str,
+    amount: &str,
+    slippage_bps: u32,
+  ) -> Result<SwapQuote>;
+
+  /// Unsigned transactions making a quoted swap from `taker_address`
+  async fn build_swap_txs(
+    &self,
+    taker_address: &str,
+    quote: &SwapQuote,
+  ) -> Result<Vec<VersionedTransaction>>;
+}
+
+pub fn parse_quote_amount(value: &str, name: &str) -> Result<u64> {
+  value
+    .parse::<u64>()
+    .map_err(|_| anyhow!("Quote {} {} is not a number", name, value))
+}
+
+async fn quote_best_swap_on(
+  routes: &[SwapRoute],
+  input_mint: &str,
+  output_mint: &str,
+  amount: &str,
+  slippage_bps: u32,
+) -> Result<SwapQuote> {
+  let quotes = futures::future::join_all(routes.iter().map(|route| async move {
+    let quote = route
+      .provider()
+      .quote_swap(input_mint, output_mint, amount, slippage_bps)
+      .await;
+    (route, quote)
+  }))
+  .await;
+
+  let mut best_quote: Option<SwapQuote> = None;
+  let mut errors = vec![];
+
+  for (route, quote) in quotes {
+    match quote {
+      Ok(quote) => {
+        if best_quote.as_ref().map_or(true, |best_quote| {
+          quote.output_amount > best_quote.output_amount
+        }) {
+          best_quote = Some(quote);
+        }
+      }
+      Err(e) => {
+        eprintln!("Failed to quote on {}: {:#}", route.name(), e);
+        errors.push(format!("{}: {:#}", route.name(), e));
+      }
+    }
+  }
+
+  best_quote.ok_or_else(|| anyhow!("No route could quote the swap ({})", errors.join(", ")))
+}
+
+/// Quote a swap on every route and keep the one with the most output
+pub async fn quote_best_swap(
+  input_mint: &str,
+  output_mint: &str,
+  amount: &str,
+  slippage_bps: u32,
+) -> Result<SwapQuote> {
+  quote_best_swap_on(
+    &SwapRoute::ALL,
+    input_mint,
+    output_mint,
+    amount,
+    slippage_bps,
+  )
+  .await
+}
+
+/// Build the transactions of a quoted swap on its route. When that route can't, the swap is
+/// quoted again on the others and the best of those, once it passes `check`, is built instead.
+/// Returns the quote the transactions were built from.
+pub async fn build_swap_txs_with_fallback<F>(
+  taker_address: &str,
+  quote: SwapQuote,
+  check: F,
+) -> Result<(SwapQuote, Vec<VersionedTransaction>)>
+where
+  F: Fn(&SwapQuote) -> Result<()>,
+{
+  let error = match quote
+    .route
+    .provider()
+    .build_swap_txs(taker_address, &quote)
+    .await
+  {
+    Ok(txs) => return Ok((quote, txs)),
+    Err(e) => e,
+  };
+
+  eprintln!(
+    "Failed to build swap on {}, trying other routes: {:#}",
+    quote.route.name(),
+    error
+  );
+
+  let other_routes: Vec<SwapRoute> = SwapRoute::ALL
+    .into_iter()
+    .filter(|route| *route != quote.route)
+    .collect();
+
+  let fallback_quote = quote_best_swap_on(
+    &other_routes,
+    &quote.input_mint,
+    &quote.output_mint,
+    &quote.input_amount.to_string(),
+    quote.slippage_bps,
+  )
+  .await
+  .map_err(|e| {
+    anyhow!(
+      "{:#}, and no other route could take the swap: {:#}",
+      error,
+      e
+    )
+  })?;
+
+  check(&fallback_quote)?;
+
+  let txs = fallback_quote
+    .route
+    .provider()
+    .build_swap_txs(taker_address, &fallback_quote)
+    .await?;
+
+  Ok((fallback_quote, txs))
+}
@@ -0,0 +1,149 @@
+use entity::user_settings;
+
+// Swaps start at the user's slippage for their kind and, when one fails because the price moved
+// past it, are re-quoted with more slippage until the kind's cap. Stop-loss sells get their own,
+// wider cap: getting out of a falling token matters more than the price it goes at.
+
+// markers of a swap that failed on its minimum output, as they appear in RPC errors and logs:
+// AMM v4 ExceededSlippage (30), CLMM TooLittleOutputReceived (6022), Jupiter
+// SlippageToleranceExceeded (6001) and pump.fun TooMuchSolRequired (6002) and
+// TooLittleSolReceived (6003)
+const SLIPPAGE_ERROR_MARKERS: [&str; 6] = [
+  "slippage",
+  "custom program error: 0x1e",
+  "custom program error: 0x1786",
+  "custom program error: 0x1771",
+  "custom program error: 0x1772",
+  "custom program error: 0x1773",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapKind {
+  Buy,
+  // a buy made as a token launches
+  LaunchBuy,
+  Sell,
+  StopLossSell,
+}
+
+impl SwapKind {
+  /// Kind of the sell made by an order with the given strategy
+  pub fn for_sell_strategy(strategy: &str) -> SwapKind {
+    match strategy {
+      // a trailing stop is a stop loss that follows the price up
+      "stop_loss" | "launch_stop_loss" | "trailing_stop" => SwapKind::StopLossSell,
+      _ => SwapKind::Sell,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlippagePolicy {
+  pub initial_bps: u32,
+  pub max_bps: u32,
+}
+
+impl SlippagePolicy {
+  pub fn for_kind(settings: &user_settings::Model, kind: SwapKind) -> SlippagePolicy {
+    let (initial_bps, max_bps) = match kind {
+      SwapKind::Buy | SwapKind::LaunchBuy => (settings.slippage_bps, settings.max_buy_slippage_bps),
+      SwapKind::Sell => (settings.sell_slippage_bps, settings.max_sell_slippage_bps),
+      SwapKind::StopLossSell => (
+        settings.sell_slippage_bps,
+        settings.max_stop_loss_slippage_bps,
+      ),
+    };
+
+    SlippagePolicy {
+      initial_bps: initial_bps.max(1) as u32,
+      max_bps: max_bps.max(initial_bps).max(1) as u32,
+    }
+  }
+
+  /// Slippage of the next attempt after one at `slippage_bps` failed, doubling up to the cap.
+  /// None once the cap was already tried.
+  pub fn escalate(&self, slippage_bps: u32) -> Option<u32> {
+    if slippage_bps >= self.max_bps {
+      return None;
+    }
+
+    Some((slippage_bps * 2).min(self.max_bps))
+  }
+}
+
+/// Whether a failed swap failed because the price moved past its slippage
+pub fn is_slippage_error(error: &anyhow::Error) -> bool {
+  is_slippage_message(&format!("{:#}", error))
+}
+
+/// Whether an error message or transaction logs tell of a swap failing on its slippage
+pub fn is_slippage_message(message: &str) -> bool {
+  let message = message.to_lowercase();
+
+  SLIPPAGE_ERROR_MARKERS.iter().any(|marker| {
+    message.match_indices(marker).any(|(index, _)| {
+      // 0x1e must not match the start of a longer code such as 0x1e5
+      !message[index + marker.len()..].starts_with(|c: char| c.is_ascii_hexdigit())
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::anyhow;
+
+  const POLICY: SlippagePolicy = SlippagePolicy {
+    initial_bps: 100,
+    max_bps: 500,
+  };
+
+  #[test]
+  fn escalation_doubles_up_to_the_cap() {
+    assert_eq!(POLICY.escalate(100), Some(200));
+    assert_eq!(POLICY.escalate(200), Some(400));
+    assert_eq!(POLICY.escalate(400), Some(500));
+  }
+
+  #[test]
+  fn escalation_stops_once_the_cap_was_tried() {
+    assert_eq!(POLICY.escalate(500), None);
+    assert_eq!(POLICY.escalate(800), None);
+  }
+
+  #[test]
+  fn slippage_failures_are_recognized() {
+    assert!(is_slippage_error(&anyhow!(
+      "Error: Slippage tolerance exceeded"
+    )));
+    assert!(is_slippage_error(&anyhow!(
+      "Program log: custom program error: 0x1E"
+    )));
+    assert!(is_slippage_error(
+      &anyhow!("custom program error: 0x1786").context("Transaction execution failed")
+    ));
+  }
+
+  #[test]
+  fn other_program_errors_are_not_slippage() {
+    assert!(!is_slippage_error(&anyhow!("custom program error: 0x1e5")));
+    assert!(!is_slippage_error(&anyhow!("insufficient funds for rent")));
+  }
+
+  #[test]
+  fn stop_losses_sell_as_stop_loss_sells() {
+    assert_eq!(
+      SwapKind::for_sell_strategy("stop_loss"),
+      SwapKind::StopLossSell
+    );
+    assert_eq!(
+      SwapKind::for_sell_strategy("launch_stop_loss"),
+      SwapKind::StopLossSell
+    );
+    assert_eq!(
+      SwapKind::for_sell_strategy("trailing_stop"),
+      SwapKind::StopLossSell
+    );
+    assert_eq!(SwapKind::for_sell_strategy("take_profit"), SwapKind::Sell);
+  }
+}
@@ -1,23 +1,35 @@
 #![allow(dead_code)]
 use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
 use entity::{onchain_transactions, trade_orders, wallets};
-use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use sea_orm::{
+  sea_query::OnConflict, ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, Set,
+};
 use solana_client::nonblocking::rpc_client::RpcClient as AsyncClient;
 
 use solana_sdk::{
-  commitment_config::CommitmentConfig,
+  hash::Hash,
   signature::{Keypair, Signature},
-  transaction::{Transaction, VersionedTransaction},
+  transaction::{TransactionError, VersionedTransaction},
 };
 use std::env;
 use std::time::Duration;
-
-use crate::{
-  integrations::raydium::RaydiumPriceFetcher,
-  utils::{
-    encryption::{decrypt_private_key, EncryptPKDetails},
-    wallets::solana::keypair_from_private_key,
+use uuid::Uuid;
+
+use crate::utils::{
+  encryption::{decrypt_private_key, EncryptPKDetails},
+  orders::trailing_stop::{trailing_target_price, with_high_water_mark},
+  positions::try_apply_fill_to_position,
+  settings::find_or_create_user_settings,
+  swap::{
+    guard::{check_swap_quote, downsize_fraction, QuoteCheck},
+    jito::{execute_jito_bundle_txs, jito_tip_lamports},
+    paper::{is_paper_wallet, simulate_swap_fill, wallet_token_balance, SimulatedFill},
+    preflight::{preflight_of_error, simulate_swap_tx, PreflightError, PreflightResult},
+    provider::{build_swap_txs_with_fallback, quote_best_swap, SwapQuote, SwapRoute},
+    slippage::{is_slippage_error, SlippagePolicy, SwapKind},
   },
+  wallets::solana::keypair_from_private_key,
 };
 
 const MAX_RETRIES: u32 = 3;
@@ -34,78 +46,7 @@ pub async fn execute_raydium_swap_tx(
 
   let recent_blockhash = client.get_latest_blockhash().await?;
 
-  // let message = match &unsigned_tx.message {
-  //   VersionedMessage::V0(message) => {
-  //     let instructions: Vec<Instruction> = message
-  //       .instructions
-  //       .iter()
-  //       .map(|compiled_ix| {
-  //         // Ensure the program_id_index is within bounds
-  //         let program_id_index = compiled_ix.program_id_index as usize;
-  //         if program_id_index >= message.account_keys.len() {
-  //           eprintln!(
-  //             "Error: program_id_index {} out of bounds for account_keys of length {}",
-  //             program_id_index,
-  //             message.account_keys.len()
-  //           );
-  //           return Err(anyhow!("Program ID index out of bounds"));
-  //         }
-
-  //         let program_id = message.account_keys[program_id_index];
-
-  //         let accounts: Vec<AccountMeta> = compiled_ix
-  //           .accounts
-  //           .iter()
-  //           .map(|&index| {
-  //             let index_usize = index as usize;
-  //             // Check index bounds for account_keys
-  //             if index_usize >= message.account_keys.len() {
-  //               eprintln!(
-  //                 "Error: Account index {} out of bounds for account_keys of length {}",
-  //                 index_usize,
-  //                 message.account_keys.len()
-  //               );
-  //               return AccountMeta::new(Pubkey::default(), false); // Handle this appropriately
-  //             }
-
-  //             let pubkey = message.account_keys[index_usize];
-  //             // Check if the account is writable and/or signer
-  //             let is_signer = message.header.num_required_signatures as usize > index_usize;
-  //             let is_writable = message.is_maybe_writable(index_usize);
-
-  //             if is_writable {
-  //               AccountMeta::new(pubkey, is_signer)
-  //             } else {
-  //               AccountMeta::new_readonly(pubkey, is_signer)
-  //             }
-  //           })
-  //           .collect();
-
-  //         Ok(Instruction {
-  //           program_id,
-  //           accounts,
-  //           data: compiled_ix.data.clone(),
-  //         })
-  //       })
-  //       .collect::<Result<Vec<Instruction>, _>>()?; // Collect errors if any
-
-  //     Message::new(&instructions, Some(&keypair.pubkey()))
-  //   }
-  //   _ => return Err(anyhow!("Unsupported message version")),
-  // };
-
-  let message = match unsigned_tx.message {
-    solana_sdk::message::VersionedMessage::Legacy(message) => message,
-    e => {
-      eprintln!("Error: Unsupported message version in transaction: {:?}", e);
-      return Err(anyhow!("Unsupported message version"));
-    }
-  };
-
-  // Create a new Transaction from the Message
-  let mut tx = Transaction::new_unsigned(message);
-
-  tx.sign(&[keypair], recent_blockhash.to_owned());
+  let tx = sign_swap_tx(unsigned_tx, keypair, recent_blockhash)?;
 
   // Attempt to send transaction with retries
   let mut retries = 0;
@@ -142,28 +83,34 @@ pub async fn execute_raydium_swap_tx(
   ))
 }
 
-pub async fn confirm_executed_swap_tx(signature: &Signature) -> Result<bool> {
-  let rpc_url =
-    env::var("SOLANA_RPC_URL").context("Failed to retrieve SOLANA_RPC_URL from environment")?;
-
-  let client = AsyncClient::new_with_timeout(rpc_url, Duration::from_secs(60));
-
-  // Get latest blockhash with retry
-  let latest_blockhash = client.get_latest_blockhash().await?;
-  // Confirm the transaction
-  client
-    .confirm_transaction_with_spinner(signature, &latest_blockhash, CommitmentConfig::finalized())
-    .await
-    .context("Failed to confirm transaction")?;
-
-  Ok(true)
+// watch-only wallets have no keys to sign with
+fn wallet_key_details(wallet: &wallets::Model) -> Result<EncryptPKDetails> {
+  match (
+    &wallet.salt,
+    &wallet.secret_key,
+    &wallet.encrypted_private_key,
+  ) {
+    (Some(salt), Some(secret_key), Some(encrypted_private_key)) => Ok(EncryptPKDetails {
+      salt: salt.clone(),
+      secret_key: secret_key.clone(),
+      encrypted_private_key: encrypted_private_key.clone(),
+    }),
+    _ => Err(anyhow!("Wallet {} has no keys to trade with", wallet.id)),
+  }
 }
 
 /// Result structure for swap transactions
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SwapTxResult {
   pub transaction_hash: String,
+  // route the swap went through
+  pub route: SwapRoute,
+  // false for a swap that was sent, it is confirmed later by the confirmation job
   pub success: bool,
+  // set when the swap was simulated for a paper wallet
+  pub simulated_fill: Option<SimulatedFill>,
+  // pre-flight simulation of the swap transaction sent through the RPC
+  pub preflight: Option<PreflightResult>,
 }
 
 /// Execute a swap transaction for a specific user
@@ -180,11 +127,7 @@ pub async fn execute_user_swap_tx(
     .ok_or_else(|| anyhow!("Wallet not found: {}", wallet_id))?;
 
   // Prepare wallet details for decryption
-  let encrypted_wallet_details = EncryptPKDetails {
-    salt: wallet.salt,
-    secret_key: wallet.secret_key,
-    encrypted_private_key: wallet.encrypted_private_key,
-  };
+  let encrypted_wallet_details = wallet_key_details(&wallet)?;
 
   let decrypted_pk =
     decrypt_private_key(&encrypted_wallet_details).context("Failed to decrypt private key")?;
@@ -201,14 +144,12 @@ pub async fn execute_user_swap_tx(
     }
   };
 
-  // Confirm the transaction
-  let is_confirmed = confirm_executed_swap_tx(&signature)
-    .await
-    .context("Failed to confirm transaction")?;
-
   Ok(SwapTxResult {
     transaction_hash: signature.to_string(),
-    success: is_confirmed,
+    route: SwapRoute::Raydium,
+    success: false,
+    simulated_fill: None,
+    preflight: None,
   })
 }
 
@@ -242,29 +183,50 @@ where
   ))
 }
 
+/// Sign an unsigned swap transaction, legacy or V0, with the wallet's keypair against a recent
+/// blockhash. The address lookup tables of a V0 message are resolved by the cluster.
+pub fn sign_swap_tx(
+  unsigned_tx: VersionedTransaction,
+  keypair: &Keypair,
+  recent_blockhash: Hash,
+) -> Result<VersionedTransaction> {
+  let mut message = unsigned_tx.message;
+  message.set_recent_blockhash(recent_blockhash);
+
+  VersionedTransaction::try_new(message, &[keypair]).context("Failed to sign transaction")
+}
+
+pub fn sign_swap_txs(
+  unsigned_txs: Vec<VersionedTransaction>,
+  keypair: &Keypair,
+  recent_blockhash: Hash,
+) -> Result<Vec<VersionedTransaction>> {
+  unsigned_txs
+    .into_iter()
+    .map(|unsigned_tx| sign_swap_tx(unsigned_tx, keypair, recent_blockhash))
+    .collect()
+}
+
+/// Sign and send swap transactions in order, each one simulated before it goes out. Returns
+/// their signatures and the simulation of the last one.
 pub async fn execute_raydium_swap_txs(
   unsigned_txs: Vec<VersionedTransaction>,
   keypair: &Keypair,
-) -> Result<Vec<Signature>> {
+) -> Result<(Vec<Signature>, Option<PreflightResult>)> {
   let rpc_url =
     env::var("SOLANA_RPC_URL").context("Failed to retrieve SOLANA_RPC_URL from environment")?;
   let client = AsyncClient::new(rpc_url);
 
-  let recent_blockhash = client.get_latest_blockhash().await?;
+  let mut recent_blockhash = client.get_latest_blockhash().await?;
 
   let mut signatures = Vec::new();
+  let mut preflight = None;
 
   for unsigned_tx in unsigned_txs {
-    let message = match unsigned_tx.message {
-      solana_sdk::message::VersionedMessage::Legacy(message) => message,
-      e => {
-        eprintln!("Error: Unsupported message version in transaction: {:?}", e);
-        return Err(anyhow!("Unsupported message version"));
-      }
-    };
+    let mut tx = sign_swap_tx(unsigned_tx.clone(), keypair, recent_blockhash)?;
 
-    let mut tx = Transaction::new_unsigned(message);
-    tx.sign(&[keypair], recent_blockhash.to_owned());
+    // a transaction that would fail is never sent
+    preflight = Some(simulate_swap_tx(&client, &tx).await?);
 
     // Attempt to send transaction with retries
     let mut retries = 0;
@@ -278,6 +240,7 @@ pub async fn execute_raydium_swap_txs(
             signature
           );
           signatures.push(signature);
+          last_error = None;
           break; // Break out of retry loop on success
         }
         Err(err) => {
@@ -286,6 +249,12 @@ pub async fn execute_raydium_swap_txs(
             retries + 1,
             err
           );
+          // a transaction whose blockhash expired can't land anymore, so it is signed again
+          // on a fresh one
+          if err.get_transaction_error() == Some(TransactionError::BlockhashNotFound) {
+            recent_blockhash = client.get_latest_blockhash().await?;
+            tx = sign_swap_tx(unsigned_tx.clone(), keypair, recent_blockhash)?;
+          }
           last_error = Some(err);
           retries += 1;
           if retries < MAX_RETRIES {
@@ -304,14 +273,19 @@ pub async fn execute_raydium_swap_txs(
     }
   }
 
-  Ok(signatures)
+  Ok((signatures, preflight))
 }
 
+/// Execute a quoted swap for a user's wallet. Paper wallets get a simulated fill from the quote
+/// and nothing is signed or sent. Swaps of a kind the user opted into Jito for go out as a
+/// bundle.
 pub async fn execute_user_swap_txs(
   _user_id: i32,
   wallet_id: i32,
   db: DatabaseConnection,
-  unsigned_txs: Vec<VersionedTransaction>,
+  quote: SwapQuote,
+  kind: SwapKind,
+  check: QuoteCheck,
 ) -> Result<SwapTxResult> {
   // Validate wallet existence
   let wallet = wallets::Entity::find_by_id(wallet_id)
@@ -320,12 +294,36 @@ pub async fn execute_user_swap_txs(
     .context("Database error while fetching wallet")?
     .ok_or_else(|| anyhow!("Wallet not found: {}", wallet_id))?;
 
+  if wallet.watch_only {
+    return Err(anyhow!(
+      "Wallet {} is watch-only and can't trade",
+      wallet.id
+    ));
+  }
+
+  let settings = find_or_create_user_settings(&db, wallet.user_id).await?;
+  check_swap_quote(&quote, settings.max_price_impact_percentage, check)?;
+
+  if is_paper_wallet(&db, &wallet).await? {
+    let fill = simulate_swap_fill(&quote);
+
+    return Ok(SwapTxResult {
+      transaction_hash: format!("paper-{}", Uuid::new_v4()),
+      route: quote.route,
+      success: true,
+      simulated_fill: Some(fill),
+      preflight: None,
+    });
+  }
+
+  let (quote, unsigned_txs) = build_swap_txs_with_fallback(&wallet.address, quote, |quote| {
+    check_swap_quote(quote, settings.max_price_impact_percentage, check)
+  })
+  .await
+  .map_err(|e| anyhow!("Failed to get transaction: {}", e))?;
+
   // Prepare wallet details for decryption
-  let encrypted_wallet_details = EncryptPKDetails {
-    salt: wallet.salt,
-    secret_key: wallet.secret_key,
-    encrypted_private_key: wallet.encrypted_private_key,
-  };
+  let encrypted_wallet_details = wallet_key_details(&wallet)?;
 
   // Decrypt private key and create keypair
   let decrypted_pk =
@@ -335,10 +333,21 @@ pub async fn execute_user_swap_txs(
     keypair_from_private_key(&decrypted_pk).context("Failed to create Keypair from private key")?;
 
   // Execute the swap transactions
-  let signatures = match execute_raydium_swap_txs(unsigned_txs, &keypair).await {
-    Ok(sigs) => sigs,
+  let sent = match jito_tip_lamports(&settings, kind) {
+    Some(tip_lamports) => execute_jito_bundle_txs(unsigned_txs, &keypair, tip_lamports).await,
+    None => execute_raydium_swap_txs(unsigned_txs, &keypair).await,
+  };
+
+  let (signatures, preflight) = match sent {
+    Ok(sent) => sent,
+    // the simulation's reason is what the user gets to see
+    Err(e) if e.is::<PreflightError>() => return Err(e),
     Err(e) => {
-      eprintln!("Failed to execute Raydium swap transactions: {}", e);
+      eprintln!(
+        "Failed to execute {} swap transactions: {}",
+        quote.route.name(),
+        e
+      );
       return Err(anyhow!("Transaction execution failed: {}", e));
     }
   };
@@ -347,13 +356,13 @@ pub async fn execute_user_swap_txs(
     .last()
     .ok_or_else(|| anyhow!("No signatures found"))?;
 
-  let is_confirmed = confirm_executed_swap_tx(last_signature)
-    .await
-    .context("Failed to confirm transaction")?;
-
+  // confirmed in the background, the row stays submitted until then
   Ok(SwapTxResult {
     transaction_hash: last_signature.to_string(),
-    success: is_confirmed,
+    route: quote.route,
+    success: false,
+    simulated_fill: None,
+    preflight,
   })
 }
 
@@ -362,10 +371,12 @@ pub struct TradeParams {
   pub sol_price: f64,
   pub token_price: f64,
   pub buy_size_percentage: f64,
-  pub launch_size_lower_limit: f64,
+  pub min_sol_per_trade: f64,
+  pub max_sol_per_trade: f64,
   pub launch_stop_loss: f32,
 }
 
+/// Record a SOL -> token swap, returning the id of the new onchain_transactions row
 pub async fn record_transaction(
   db: &DatabaseConnection,
   user_id: i32,
@@ -374,13 +385,15 @@ pub async fn record_transaction(
   attempt: SwapTxResult,
   size: f64,
   size_usd: f64,
-) -> Result<()> {
+) -> Result<i32> {
+  let confirmed = attempt.success;
+
   let transaction = onchain_transactions::ActiveModel {
     user_id: Set(user_id),
     wallet_id: Set(wallet_id),
     transaction_hash: Set(Some(attempt.transaction_hash)),
     chain: Set("solana".to_string()),
-    source: Set(Some("raydium".to_string())),
+    source: Set(Some(attempt.route.name().to_string())),
     status: Set(Some(
       if attempt.success {
         "confirmed"
@@ -396,14 +409,138 @@ pub async fn record_transaction(
       "So11111111111111111111111111111111111111112".to_string(),
     )),
     to_token: Set(Some(contract_address.to_string())),
+    simulated: Set(attempt.simulated_fill.is_some()),
+    input_amount: Set(attempt.simulated_fill.map(|fill| fill.input_amount as i64)),
+    output_amount: Set(attempt.simulated_fill.map(|fill| fill.output_amount as i64)),
+    network_fee_lamports: Set(
+      attempt
+        .simulated_fill
+        .map(|fill| fill.network_fee_lamports as i64),
+    ),
+    preflight: Set(attempt.preflight.map(|preflight| preflight.to_json())),
     ..Default::default()
   };
 
-  onchain_transactions::Entity::insert(transaction)
+  let inserted = onchain_transactions::Entity::insert(transaction)
     .exec(db)
     .await
     .context("Failed to record transaction: {}")?;
 
+  // paper swaps are filled as they are recorded, sent ones once they are confirmed
+  if confirmed {
+    try_apply_fill_to_position(db, inserted.last_insert_id).await;
+  }
+
+  Ok(inserted.last_insert_id)
+}
+
+/// Claim an idempotency key for a swap that is about to be sent by inserting its row up front.
+/// Returns None when a swap with the same key was already started.
+pub async fn reserve_transaction(
+  db: &DatabaseConnection,
+  idempotency_key: &str,
+  user_id: i32,
+  wallet_id: i32,
+  from_token: &str,
+  to_token: &str,
+) -> Result<Option<i32>> {
+  let transaction = onchain_transactions::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    chain: Set("solana".to_string()),
+    status: Set(Some("pending".to_string())),
+    r#type: Set(Some("swap".to_string())),
+    from_token: Set(Some(from_token.to_string())),
+    to_token: Set(Some(to_token.to_string())),
+    idempotency_key: Set(Some(idempotency_key.to_string())),
+    ..Default::default()
+  };
+
+  let inserted = onchain_transactions::Entity::insert(transaction)
+    .on_conflict(
+      OnConflict::column(onchain_transactions::Column::IdempotencyKey)
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec(db)
+    .await;
+
+  match inserted {
+    Ok(inserted) => Ok(Some(inserted.last_insert_id)),
+    Err(DbErr::RecordNotInserted) => Ok(None),
+    Err(e) => Err(e).context("Failed to reserve transaction"),
+  }
+}
+
+/// Fill in a reserved transaction once its swap went out
+pub async fn settle_reserved_transaction(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+  attempt: SwapTxResult,
+  size: f64,
+  size_usd: f64,
+) -> Result<()> {
+  let confirmed = attempt.success;
+
+  let settle_update_model = onchain_transactions::ActiveModel {
+    id: Set(transaction_id),
+    transaction_hash: Set(Some(attempt.transaction_hash)),
+    source: Set(Some(attempt.route.name().to_string())),
+    status: Set(Some(
+      if attempt.success {
+        "confirmed"
+      } else {
+        "submitted"
+      }
+      .to_string(),
+    )),
+    value_native: Set(Some(size as f32)),
+    value_usd: Set(Some(size_usd as f32)),
+    simulated: Set(attempt.simulated_fill.is_some()),
+    input_amount: Set(attempt.simulated_fill.map(|fill| fill.input_amount as i64)),
+    output_amount: Set(attempt.simulated_fill.map(|fill| fill.output_amount as i64)),
+    network_fee_lamports: Set(
+      attempt
+        .simulated_fill
+        .map(|fill| fill.network_fee_lamports as i64),
+    ),
+    preflight: Set(attempt.preflight.map(|preflight| preflight.to_json())),
+    updated_at: Set(Utc::now().naive_utc()),
+    ..Default::default()
+  };
+
+  onchain_transactions::Entity::update(settle_update_model)
+    .exec(db)
+    .await
+    .context("Failed to settle transaction")?;
+
+  if confirmed {
+    try_apply_fill_to_position(db, transaction_id).await;
+  }
+
+  Ok(())
+}
+
+/// Mark a reserved transaction whose swap failed, keeping the simulation that stopped it if one
+/// did. The key stays taken so the swap isn't retried under it.
+pub async fn fail_reserved_transaction(
+  db: &DatabaseConnection,
+  transaction_id: i32,
+  error: &anyhow::Error,
+) -> Result<()> {
+  let fail_update_model = onchain_transactions::ActiveModel {
+    id: Set(transaction_id),
+    status: Set(Some("failed".to_string())),
+    preflight: Set(preflight_of_error(error).map(|preflight| preflight.to_json())),
+    updated_at: Set(Utc::now().naive_utc()),
+    ..Default::default()
+  };
+
+  onchain_transactions::Entity::update(fail_update_model)
+    .exec(db)
+    .await
+    .context("Failed to mark transaction as failed")?;
+
   Ok(())
 }
 
@@ -442,71 +579,220 @@ pub async fn create_stop_loss_order(
   Ok(())
 }
 
+pub async fn create_take_profit_order(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  token_id: i32,
+  contract_address: &str,
+  reference_price: f64,
+  target_multiple: f64,
+) -> Result<trade_orders::Model> {
+  if target_multiple <= 1.0 {
+    return Err(anyhow!("Take profit multiple must be greater than 1"));
+  }
+
+  let new_trade_order = trade_orders::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    token_id: Set(token_id),
+    contract_address: Set(contract_address.to_string()),
+    reference_price: Set(reference_price as f32),
+    target_percentage: Set(((target_multiple - 1.0) * 100.0) as f32),
+    target_price: Set((reference_price * target_multiple) as f32),
+    strategy: Set("take_profit".to_string()),
+    created_by: Set("user".to_string()),
+    metadata: Set(None),
+    ..Default::default()
+  };
+
+  new_trade_order
+    .insert(db)
+    .await
+    .context("Failed to create take profit order")
+}
+
+pub async fn create_trailing_stop_order(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+  token_id: i32,
+  contract_address: &str,
+  reference_price: f64,
+  trail_percentage: f32,
+) -> Result<trade_orders::Model> {
+  if trail_percentage <= 0.0 || trail_percentage >= 100.0 {
+    return Err(anyhow!(
+      "Trailing stop percentage must be between 0 and 100"
+    ));
+  }
+
+  let new_trade_order = trade_orders::ActiveModel {
+    user_id: Set(user_id),
+    wallet_id: Set(wallet_id),
+    token_id: Set(token_id),
+    contract_address: Set(contract_address.to_string()),
+    reference_price: Set(reference_price as f32),
+    target_percentage: Set(trail_percentage),
+    target_price: Set(trailing_target_price(reference_price, trail_percentage) as f32),
+    strategy: Set("trailing_stop".to_string()),
+    created_by: Set("user".to_string()),
+    metadata: Set(Some(with_high_water_mark(None, reference_price))),
+    ..Default::default()
+  };
+
+  new_trade_order
+    .insert(db)
+    .await
+    .context("Failed to create trailing stop order")
+}
+
+/// Send a quoted swap. While it fails on slippage, re-quote the same amount with more slippage
+/// until the cap of the swap's kind. The price impact guard still applies to every re-quote.
+pub async fn execute_swap_with_escalation(
+  user_id: i32,
+  wallet_id: i32,
+  db: &DatabaseConnection,
+  mut quote: SwapQuote,
+  kind: SwapKind,
+) -> Result<SwapTxResult> {
+  let settings = find_or_create_user_settings(db, user_id).await?;
+  let policy = SlippagePolicy::for_kind(&settings, kind);
+  let input_mint = quote.input_mint.clone();
+  let output_mint = quote.output_mint.clone();
+  let amount = quote.input_amount.to_string();
+  let mut slippage_bps = policy.initial_bps;
+
+  loop {
+    let check = QuoteCheck::for_attempt(kind, slippage_bps > policy.initial_bps);
+
+    let error =
+      match execute_user_swap_txs(user_id, wallet_id, db.clone(), quote, kind, check).await {
+        Ok(attempt) => return Ok(attempt),
+        Err(e) => e,
+      };
+
+    let next_slippage_bps = match policy.escalate(slippage_bps) {
+      Some(next_slippage_bps) if is_slippage_error(&error) => next_slippage_bps,
+      _ => return Err(error),
+    };
+
+    println!(
+      "Swap of {} {} failed on slippage at {} bps, retrying at {} bps",
+      amount, input_mint, slippage_bps, next_slippage_bps
+    );
+    slippage_bps = next_slippage_bps;
+
+    quote = quote_best_swap(&input_mint, &output_mint, &amount, slippage_bps)
+      .await
+      .context("Failed to get quote")?;
+  }
+}
+
 pub async fn execute_buy_trade(
   user_id: i32,
   wallet_id: i32,
   ca: &str,
   buy_size: f64,
-  wallet_address: &str,
+  kind: SwapKind,
   db: &DatabaseConnection,
-) -> Result<SwapTxResult> {
-  let raydium_client = RaydiumPriceFetcher::new();
-
-  let quote = raydium_client
-    .get_swap_quote(
+) -> Result<BuyTradeResult> {
+  let settings = find_or_create_user_settings(db, user_id).await?;
+  let policy = SlippagePolicy::for_kind(&settings, kind);
+  let mut sol_amount = buy_size;
+  let mut amount = ((sol_amount * 1_000_000_000.0).floor() as u64).to_string();
+
+  let mut quote = quote_best_swap(
+    "So11111111111111111111111111111111111111112",
+    ca,
+    &amount,
+    policy.initial_bps,
+  )
+  .await
+  .context("Failed to get quote")?;
+
+  // a buy too big for the pool is shrunk to fit the user's price impact limit once
+  if let Err(e) = check_swap_quote(
+    &quote,
+    settings.max_price_impact_percentage,
+    QuoteCheck::for_attempt(kind, false),
+  ) {
+    let fraction = downsize_fraction(&quote, settings.max_price_impact_percentage)?.ok_or(e)?;
+
+    sol_amount *= fraction;
+    amount = ((sol_amount * 1_000_000_000.0).floor() as u64).to_string();
+    quote = quote_best_swap(
       "So11111111111111111111111111111111111111112",
       ca,
-      &(buy_size * 1_000_000_000.0).to_string(),
-      "50",
+      &amount,
+      policy.initial_bps,
     )
     .await
-    .context("Failed to get quote: {}")?;
+    .context("Failed to get quote")?;
+  }
 
-  let swap_tx = raydium_client
-    .get_swap_tx(
-      wallet_address,
-      quote,
-      "So11111111111111111111111111111111111111112",
-      ca,
-      wallet_address,
-    )
-    .await
-    .map_err(|e| anyhow!("Failed to get transaction: {}", e))?;
+  let attempt = execute_swap_with_escalation(user_id, wallet_id, db, quote, kind).await?;
 
-  execute_user_swap_txs(user_id, wallet_id, db.clone(), swap_tx).await
+  Ok(BuyTradeResult {
+    attempt,
+    sol_amount,
+  })
 }
 
+/// Result of buying a token with SOL
+#[derive(Debug)]
+pub struct BuyTradeResult {
+  pub attempt: SwapTxResult,
+  // can be less than asked for when the buy was downsized for price impact
+  pub sol_amount: f64,
+}
+
+/// Result of selling part of a wallet's position in a token
+#[derive(Debug)]
+pub struct SellTradeResult {
+  pub attempt: SwapTxResult,
+  pub sold_amount: f64,
+}
+
+/// Sell `sell_fraction` (0 to 1) of the wallet's current balance of a token for SOL
 pub async fn execute_sell_trade(
   user_id: i32,
-  wallet_id: i32,
+  wallet: &wallets::Model,
   ca: &str,
-  sell_size: f64,
-  wallet_address: &str,
+  sell_fraction: f64,
+  kind: SwapKind,
   db: &DatabaseConnection,
-  _decimals: u8,
-) -> Result<SwapTxResult> {
-  let raydium_client = RaydiumPriceFetcher::new();
+) -> Result<SellTradeResult> {
+  if sell_fraction <= 0.0 || sell_fraction > 1.0 {
+    return Err(anyhow!("Sell fraction must be between 0 and 1"));
+  }
 
-  let quote = raydium_client
-    .get_swap_quote(
-      ca,
-      "So11111111111111111111111111111111111111112",
-      &(sell_size).to_string(),
-      "50",
-    )
-    .await
-    .context("Failed to get quote: {}")?;
+  let balance = wallet_token_balance(db, wallet, ca).await?;
 
-  let swap_tx = raydium_client
-    .get_swap_tx(
-      wallet_address,
-      quote,
-      "So11111111111111111111111111111111111111112",
-      ca,
-      wallet_address,
-    )
-    .await
-    .map_err(|e| anyhow!("Failed to get transaction: {}", e))?;
+  // the quote expects the amount in the token's smallest unit
+  let sell_amount = (balance.amount * sell_fraction).floor();
 
-  execute_user_swap_txs(user_id, wallet_id, db.clone(), swap_tx).await
+  if sell_amount <= 0.0 {
+    return Err(anyhow!("No balance of {} to sell", ca));
+  }
+
+  let settings = find_or_create_user_settings(db, user_id).await?;
+  let policy = SlippagePolicy::for_kind(&settings, kind);
+  let amount = (sell_amount as u64).to_string();
+
+  let quote = quote_best_swap(
+    ca,
+    "So11111111111111111111111111111111111111112",
+    &amount,
+    policy.initial_bps,
+  )
+  .await
+  .context("Failed to get quote")?;
+
+  let attempt = execute_swap_with_escalation(user_id, wallet.id, db, quote, kind).await?;
+
+  Ok(SellTradeResult {
+    attempt,
+    sold_amount: balance.ui_amount * sell_fraction,
+  })
 }
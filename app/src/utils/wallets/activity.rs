@@ -0,0 +1,357 @@
+use anyhow::{anyhow, Context, Result};
+use solana_client::{
+  nonblocking::rpc_client::RpcClient as AsyncClient,
+  rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+use std::{
+  collections::{HashMap, HashSet},
+  env,
+  str::FromStr,
+};
+
+// Swaps of a wallet we don't hold the keys of are read back from its transactions: whatever
+// token its balance grew or shrank by, against the SOL (native or wrapped) that moved the other
+// way. Transfers, token to token swaps and failed transactions aren't swaps here.
+
+const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+// every signature pays this, whatever a transaction pays above it is priority fee
+const BASE_FEE_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+// signatures read per poll, a wallet trading faster than this between polls loses the oldest
+pub const MAX_SIGNATURES_PER_POLL: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletSwapSide {
+  Buy,
+  Sell,
+}
+
+/// A swap between SOL and a token made by a wallet
+#[derive(Debug, Clone)]
+pub struct WalletSwap {
+  pub signature: String,
+  pub side: WalletSwapSide,
+  pub mint: String,
+  // SOL spent on a buy or received from a sell, fees excluded
+  pub sol_amount: f64,
+  pub token_amount: f64,
+  // share of the wallet's balance of the token that a sell let go of
+  pub sold_fraction: f64,
+}
+
+fn rpc_client() -> Result<AsyncClient> {
+  let rpc_url =
+    env::var("SOLANA_RPC_URL").context("Failed to retrieve SOLANA_RPC_URL from environment")?;
+
+  Ok(AsyncClient::new_with_commitment(
+    rpc_url,
+    CommitmentConfig::confirmed(),
+  ))
+}
+
+/// Signatures of the transactions of a wallet since `until`, oldest first. Without
+/// `until` only the latest one is returned, to start following from.
+pub async fn fetch_signatures_since(address: &str, until: Option<&str>) -> Result<Vec<String>> {
+  let pubkey = Pubkey::from_str(address).map_err(|e| anyhow!("Invalid Solana address: {}", e))?;
+
+  let until = until
+    .map(Signature::from_str)
+    .transpose()
+    .map_err(|e| anyhow!("Invalid signature: {}", e))?;
+
+  let config = GetConfirmedSignaturesForAddress2Config {
+    before: None,
+    limit: Some(if until.is_some() {
+      MAX_SIGNATURES_PER_POLL
+    } else {
+      1
+    }),
+    until,
+    commitment: Some(CommitmentConfig::confirmed()),
+  };
+
+  let statuses = rpc_client()?
+    .get_signatures_for_address_with_config(&pubkey, config)
+    .await
+    .map_err(|e| anyhow!("Failed to fetch signatures of {}: {}", address, e))?;
+
+  // the RPC returns the newest first
+  Ok(
+    statuses
+      .into_iter()
+      .rev()
+      .map(|status| status.signature)
+      .collect(),
+  )
+}
+
+// raw amounts of each mint held by the owner across its token accounts
+fn owner_token_amounts(
+  balances: Option<Vec<UiTransactionTokenBalance>>,
+  owner: &str,
+) -> HashMap<String, (u64, u8)> {
+  let mut amounts = HashMap::new();
+
+  for balance in balances.unwrap_or_default() {
+    let balance_owner: Option<String> = balance.owner.into();
+    if balance_owner.as_deref() != Some(owner) {
+      continue;
+    }
+
+    let amount = balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0);
+    let entry = amounts
+      .entry(balance.mint)
+      .or_insert((0, balance.ui_token_amount.decimals));
+    entry.0 += amount;
+  }
+
+  amounts
+}
+
+/// How a token balance of a wallet changed in a transaction, in raw amounts
+#[derive(Debug, Clone)]
+pub struct TokenBalanceChange {
+  pub mint: String,
+  pub delta: i128,
+  pub pre_amount: u64,
+  pub decimals: u8,
+}
+
+/// How a transaction changed the balances of a wallet
+#[derive(Debug, Clone)]
+pub struct WalletBalanceChanges {
+  // lamports, wrapped SOL included and the network fee left out
+  pub sol_delta: i128,
+  pub token_changes: Vec<TokenBalanceChange>,
+  // paid by the fee payer, who may not be the wallet
+  pub network_fee_lamports: u64,
+  pub priority_fee_lamports: u64,
+}
+
+impl WalletBalanceChanges {
+  pub fn sol_amount(&self) -> f64 {
+    self.sol_delta.unsigned_abs() as f64 / 1_000_000_000.0
+  }
+}
+
+impl TokenBalanceChange {
+  pub fn ui_amount(&self) -> f64 {
+    self.delta.unsigned_abs() as f64 / 10f64.powi(self.decimals as i32)
+  }
+}
+
+/// The balance changes a successful transaction made to `address`
+pub async fn fetch_balance_changes(
+  address: &str,
+  signature: &str,
+) -> Result<Option<WalletBalanceChanges>> {
+  let parsed_signature =
+    Signature::from_str(signature).map_err(|e| anyhow!("Invalid signature: {}", e))?;
+
+  let transaction = rpc_client()?
+    .get_transaction_with_config(
+      &parsed_signature,
+      RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+      },
+    )
+    .await
+    .map_err(|e| anyhow!("Failed to fetch transaction {}: {}", signature, e))?;
+
+  let meta = match transaction.transaction.meta {
+    Some(meta) if meta.err.is_none() => meta,
+    _ => return Ok(None),
+  };
+
+  let decoded = transaction
+    .transaction
+    .transaction
+    .decode()
+    .ok_or_else(|| anyhow!("Failed to decode transaction {}", signature))?;
+
+  // a signer is always one of the static keys, lookup table accounts come after them. A wallet
+  // that only received tokens may not be among the keys at all, its token accounts are.
+  let account_index = decoded
+    .message
+    .static_account_keys()
+    .iter()
+    .position(|key| key.to_string() == address);
+
+  let mut sol_delta = match account_index {
+    Some(account_index) => {
+      let pre_lamports = *meta.pre_balances.get(account_index).unwrap_or(&0) as i128;
+      let post_lamports = *meta.post_balances.get(account_index).unwrap_or(&0) as i128;
+      post_lamports - pre_lamports
+    }
+    None => 0,
+  };
+
+  // the fee payer's balance also paid the network fee, which isn't part of the transfer
+  if account_index == Some(0) {
+    sol_delta += meta.fee as i128;
+  }
+
+  let pre_tokens = owner_token_amounts(meta.pre_token_balances.into(), address);
+  let post_tokens = owner_token_amounts(meta.post_token_balances.into(), address);
+
+  let mut token_changes = vec![];
+
+  let mints: HashSet<&String> = pre_tokens.keys().chain(post_tokens.keys()).collect();
+
+  for mint in mints {
+    let (pre_amount, decimals) = pre_tokens.get(mint).cloned().unwrap_or((0, 0));
+    let (post_amount, post_decimals) = post_tokens.get(mint).cloned().unwrap_or((0, decimals));
+    let delta = post_amount as i128 - pre_amount as i128;
+
+    if delta == 0 {
+      continue;
+    }
+
+    if mint == WRAPPED_SOL_MINT {
+      sol_delta += delta;
+    } else {
+      token_changes.push(TokenBalanceChange {
+        mint: mint.clone(),
+        delta,
+        pre_amount,
+        decimals: post_decimals,
+      });
+    }
+  }
+
+  let base_fee_lamports = BASE_FEE_LAMPORTS_PER_SIGNATURE * decoded.signatures.len() as u64;
+
+  Ok(Some(WalletBalanceChanges {
+    sol_delta,
+    token_changes,
+    network_fee_lamports: meta.fee,
+    priority_fee_lamports: meta.fee.saturating_sub(base_fee_lamports),
+  }))
+}
+
+/// The SOL to token swap made by `address` in a transaction, if it made one
+pub async fn fetch_wallet_swap(address: &str, signature: &str) -> Result<Option<WalletSwap>> {
+  let changes = match fetch_balance_changes(address, signature).await? {
+    Some(changes) => changes,
+    None => return Ok(None),
+  };
+
+  Ok(balance_changes_swap(signature, &changes))
+}
+
+/// The SOL to token swap the balance changes of a transaction amount to, if they are one
+pub fn balance_changes_swap(signature: &str, changes: &WalletBalanceChanges) -> Option<WalletSwap> {
+  // a swap between SOL and exactly one token
+  let change = match changes.token_changes.as_slice() {
+    [change] => change,
+    _ => return None,
+  };
+
+  let side = match (change.delta > 0, changes.sol_delta < 0) {
+    (true, true) => WalletSwapSide::Buy,
+    (false, false) if changes.sol_delta > 0 => WalletSwapSide::Sell,
+    _ => return None,
+  };
+
+  let sold_fraction = if side == WalletSwapSide::Sell && change.pre_amount > 0 {
+    (change.delta.unsigned_abs() as f64 / change.pre_amount as f64).min(1.0)
+  } else {
+    0.0
+  };
+
+  Some(WalletSwap {
+    signature: signature.to_string(),
+    side,
+    mint: change.mint.clone(),
+    sol_amount: changes.sol_amount(),
+    token_amount: change.ui_amount(),
+    sold_fraction,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn changes(sol_delta: i128, token_changes: Vec<TokenBalanceChange>) -> WalletBalanceChanges {
+    WalletBalanceChanges {
+      sol_delta,
+      token_changes,
+      network_fee_lamports: 5_000,
+      priority_fee_lamports: 0,
+    }
+  }
+
+  fn token_change(mint: &str, delta: i128, pre_amount: u64) -> TokenBalanceChange {
+    TokenBalanceChange {
+      mint: mint.to_string(),
+      delta,
+      pre_amount,
+      decimals: 6,
+    }
+  }
+
+  #[test]
+  fn sol_spent_on_a_token_is_a_buy() {
+    let changes = changes(-500_000_000, vec![token_change("token", 2_000_000, 0)]);
+    let swap = balance_changes_swap("sig", &changes).unwrap();
+
+    assert_eq!(swap.side, WalletSwapSide::Buy);
+    assert_eq!(swap.mint, "token");
+    assert_eq!(swap.sol_amount, 0.5);
+    assert_eq!(swap.token_amount, 2.0);
+    assert_eq!(swap.sold_fraction, 0.0);
+  }
+
+  #[test]
+  fn a_sell_tells_the_share_it_let_go_of() {
+    let changes = changes(
+      250_000_000,
+      vec![token_change("token", -1_000_000, 4_000_000)],
+    );
+    let swap = balance_changes_swap("sig", &changes).unwrap();
+
+    assert_eq!(swap.side, WalletSwapSide::Sell);
+    assert_eq!(swap.sol_amount, 0.25);
+    assert_eq!(swap.token_amount, 1.0);
+    assert_eq!(swap.sold_fraction, 0.25);
+  }
+
+  #[test]
+  fn a_sell_without_a_prior_balance_has_no_sold_fraction() {
+    let changes = changes(250_000_000, vec![token_change("token", -1_000_000, 0)]);
+
+    assert_eq!(
+      balance_changes_swap("sig", &changes).unwrap().sold_fraction,
+      0.0
+    );
+  }
+
+  #[test]
+  fn transfers_and_multi_token_swaps_are_not_swaps() {
+    // tokens received without paying
+    let airdrop = changes(0, vec![token_change("token", 1_000_000, 0)]);
+    assert!(balance_changes_swap("sig", &airdrop).is_none());
+
+    // tokens sent away without being paid
+    let transfer = changes(-5_000, vec![token_change("token", -1_000_000, 1_000_000)]);
+    assert!(balance_changes_swap("sig", &transfer).is_none());
+
+    // a token to token route
+    let route = changes(
+      0,
+      vec![
+        token_change("token", -1_000_000, 1_000_000),
+        token_change("other", 1_000_000, 0),
+      ],
+    );
+    assert!(balance_changes_swap("sig", &route).is_none());
+
+    assert!(balance_changes_swap("sig", &changes(-500_000_000, vec![])).is_none());
+  }
+}
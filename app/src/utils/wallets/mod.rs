@@ -1 +1,4 @@
-pub mod solana;
\ No newline at end of file
+pub mod activity;
+pub mod portfolio;
+pub mod solana;
+pub mod watch;
@@ -0,0 +1,316 @@
+use anyhow::{anyhow, Context, Result};
+use entity::{positions, trade_orders, wallets};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::utils::{
+  orders::format_trade_order,
+  positions::{find_user_positions, position_quantity, unrealized_pnl_usd},
+  price::solana::fetch_token_price,
+  swap::paper::is_paper_wallet,
+  wallets::solana::{get_spl_tokens_in_wallet, get_wallet_sol_balance, TokenInfo},
+};
+
+// A portfolio values what the user's wallets hold on chain right now, or for paper wallets what
+// their paper fills hold. Unrealized PnL is only known for tokens the positions ledger has seen
+// bought, and prices that can't be fetched leave a holding unvalued rather than failing the
+// whole portfolio. A wallet whose balances can't be fetched is shown as unavailable in the same
+// way.
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// A token a wallet holds, at its live price
+#[derive(Debug)]
+pub struct HoldingValuation {
+  pub token: TokenInfo,
+  pub price_usd: Option<f64>,
+  pub value_usd: Option<f64>,
+  pub unrealized_pnl_usd: Option<f64>,
+  pub orders: Vec<trade_orders::Model>,
+}
+
+#[derive(Debug)]
+pub struct WalletValuation {
+  pub wallet: wallets::Model,
+  pub paper: bool,
+  pub sol_balance: f64,
+  pub holdings: Vec<HoldingValuation>,
+  // why the balances of the wallet couldn't be fetched, it is valued at nothing then
+  pub unavailable: Option<String>,
+}
+
+impl WalletValuation {
+  pub fn value_usd(&self, sol_price: Option<f64>) -> f64 {
+    let sol_value_usd = sol_price.map_or(0.0, |sol_price| self.sol_balance * sol_price);
+    let holdings_value_usd: f64 = self
+      .holdings
+      .iter()
+      .filter_map(|holding| holding.value_usd)
+      .sum();
+
+    sol_value_usd + holdings_value_usd
+  }
+
+  pub fn unrealized_pnl_usd(&self) -> f64 {
+    self
+      .holdings
+      .iter()
+      .filter_map(|holding| holding.unrealized_pnl_usd)
+      .sum()
+  }
+}
+
+#[derive(Debug)]
+pub struct Portfolio {
+  pub sol_price: Option<f64>,
+  pub wallets: Vec<WalletValuation>,
+}
+
+impl Portfolio {
+  pub fn value_usd(&self) -> f64 {
+    self
+      .wallets
+      .iter()
+      .map(|wallet| wallet.value_usd(self.sol_price))
+      .sum()
+  }
+
+  pub fn unrealized_pnl_usd(&self) -> f64 {
+    self
+      .wallets
+      .iter()
+      .map(|wallet| wallet.unrealized_pnl_usd())
+      .sum()
+  }
+}
+
+// what a paper position holds, it has no token account on chain
+fn paper_holding(position: &positions::Model) -> TokenInfo {
+  TokenInfo {
+    mint_address: position.contract_address.clone(),
+    token_balance: position_quantity(position),
+    decimals: position.decimals as u8,
+    mint_public_key: String::new(),
+  }
+}
+
+// SOL balance and held tokens of a wallet, a paper wallet holds what its positions do
+async fn fetch_wallet_balances(
+  wallet: &wallets::Model,
+  paper: bool,
+  positions: &[&positions::Model],
+) -> Result<(f64, Vec<TokenInfo>)> {
+  let sol_balance = get_wallet_sol_balance(&wallet.address)
+    .await
+    .map_err(|e| anyhow!("Failed to fetch SOL balance of {}: {}", wallet.address, e))?;
+
+  let held_tokens = if paper {
+    positions
+      .iter()
+      .map(|position| paper_holding(position))
+      .collect()
+  } else {
+    get_spl_tokens_in_wallet(&wallet.address)
+      .await
+      .map_err(|e| anyhow!("Failed to fetch tokens of {}: {}", wallet.address, e))?
+  };
+
+  Ok((sol_balance, held_tokens))
+}
+
+async fn value_wallet(
+  db: &DatabaseConnection,
+  wallet: wallets::Model,
+  positions: &[positions::Model],
+) -> Result<WalletValuation> {
+  let paper = is_paper_wallet(db, &wallet).await?;
+
+  let positions: Vec<&positions::Model> = positions
+    .iter()
+    .filter(|position| position.wallet_id == wallet.id && position.simulated == paper)
+    .collect();
+
+  let (sol_balance, held_tokens) = match fetch_wallet_balances(&wallet, paper, &positions).await {
+    Ok(balances) => balances,
+    Err(e) => {
+      eprintln!("Failed to fetch balances of wallet {}: {:?}", wallet.id, e);
+      return Ok(WalletValuation {
+        wallet,
+        paper,
+        sol_balance: 0.0,
+        holdings: vec![],
+        unavailable: Some(e.to_string()),
+      });
+    }
+  };
+
+  let orders = trade_orders::Entity::find()
+    .filter(trade_orders::Column::WalletId.eq(wallet.id))
+    .filter(trade_orders::Column::Status.eq("active"))
+    .order_by_asc(trade_orders::Column::Id)
+    .all(db)
+    .await
+    .context("Failed to retrieve active orders")?;
+
+  let mut holdings = vec![];
+
+  for token in held_tokens {
+    let price_usd = match fetch_token_price(&token.mint_address).await {
+      Ok(price) => Some(price),
+      Err(e) => {
+        eprintln!("Failed to price {}: {:?}", token.mint_address, e);
+        None
+      }
+    };
+
+    let position = positions
+      .iter()
+      .find(|position| position.contract_address == token.mint_address);
+
+    holdings.push(HoldingValuation {
+      value_usd: price_usd.map(|price| price * token.token_balance),
+      unrealized_pnl_usd: position
+        .zip(price_usd)
+        .map(|(position, price)| unrealized_pnl_usd(position, price)),
+      orders: orders
+        .iter()
+        .filter(|order| order.contract_address == token.mint_address)
+        .cloned()
+        .collect(),
+      price_usd,
+      token,
+    });
+  }
+
+  // the most valuable holdings first, unpriced ones last
+  holdings.sort_by(|a, b| {
+    b.value_usd
+      .unwrap_or(-1.0)
+      .total_cmp(&a.value_usd.unwrap_or(-1.0))
+  });
+
+  Ok(WalletValuation {
+    wallet,
+    paper,
+    sol_balance,
+    holdings,
+    unavailable: None,
+  })
+}
+
+/// Value every wallet the user trades with, leaving out watch-only ones
+pub async fn value_user_portfolio(db: &DatabaseConnection, user_id: i32) -> Result<Portfolio> {
+  let user_wallets = wallets::Entity::find()
+    .filter(wallets::Column::UserId.eq(user_id))
+    .filter(wallets::Column::WatchOnly.eq(false))
+    .order_by_asc(wallets::Column::Id)
+    .all(db)
+    .await
+    .context("Failed to retrieve wallets")?;
+
+  let positions = find_user_positions(db, user_id, true).await?;
+
+  let sol_price = match fetch_token_price(SOL_MINT).await {
+    Ok(price) => Some(price),
+    Err(e) => {
+      eprintln!("Failed to price SOL: {:?}", e);
+      None
+    }
+  };
+
+  let mut wallets = vec![];
+
+  for wallet in user_wallets {
+    wallets.push(value_wallet(db, wallet, &positions).await?);
+  }
+
+  Ok(Portfolio { sol_price, wallets })
+}
+
+fn format_usd(value: Option<f64>) -> String {
+  match value {
+    Some(value) => format!("${:.2}", value),
+    None => "unpriced".to_string(),
+  }
+}
+
+fn format_pnl(pnl: f64) -> String {
+  if pnl < 0.0 {
+    format!("-${:.2}", -pnl)
+  } else {
+    format!("+${:.2}", pnl)
+  }
+}
+
+fn format_holding(holding: &HoldingValuation) -> String {
+  let mut lines = vec![format!(
+    "• {}\n  {} at {} = {}",
+    holding.token.mint_address,
+    holding.token.token_balance,
+    format_usd(holding.price_usd),
+    format_usd(holding.value_usd)
+  )];
+
+  if let Some(pnl) = holding.unrealized_pnl_usd {
+    lines.push(format!("  Unrealized PnL: {}", format_pnl(pnl)));
+  }
+
+  for order in &holding.orders {
+    lines.push(format!("  {}", format_trade_order(order)));
+  }
+
+  lines.join("\n")
+}
+
+pub fn format_wallet_valuation(wallet: &WalletValuation, sol_price: Option<f64>) -> String {
+  let header = format!(
+    "{}. {}{}\n{}",
+    wallet.wallet.id,
+    wallet.wallet.title,
+    if wallet.paper { " (paper)" } else { "" },
+    wallet.wallet.address
+  );
+
+  if let Some(reason) = &wallet.unavailable {
+    return format!("{}\nUnavailable: {}", header, reason);
+  }
+
+  let mut lines = vec![
+    header,
+    format!(
+      "{:.4} SOL = {}",
+      wallet.sol_balance,
+      format_usd(sol_price.map(|sol_price| wallet.sol_balance * sol_price))
+    ),
+  ];
+
+  lines.extend(wallet.holdings.iter().map(format_holding));
+
+  lines.push(format!(
+    "Wallet total: ${:.2}, unrealized PnL {}",
+    wallet.value_usd(sol_price),
+    format_pnl(wallet.unrealized_pnl_usd())
+  ));
+
+  lines.join("\n")
+}
+
+pub fn format_portfolio_total(portfolio: &Portfolio) -> String {
+  let unavailable = portfolio
+    .wallets
+    .iter()
+    .filter(|wallet| wallet.unavailable.is_some())
+    .count();
+
+  let mut total = format!(
+    "Total over {} wallets: ${:.2}, unrealized PnL {}",
+    portfolio.wallets.len() - unavailable,
+    portfolio.value_usd(),
+    format_pnl(portfolio.unrealized_pnl_usd())
+  );
+
+  if unavailable > 0 {
+    total.push_str(&format!(", {} unavailable wallets left out", unavailable));
+  }
+
+  total
+}
@@ -18,7 +18,10 @@ use solana_client::nonblocking::rpc_client::RpcClient as AsyncClient;
 
 use crate::{
   db,
-  utils::{encryption::encrypt_private_key, price::solana::fetch_token_price},
+  utils::{
+    encryption::encrypt_private_key, price::solana::fetch_token_price,
+    settings::find_or_create_user_settings,
+  },
 };
 
 #[derive(Debug)]
@@ -172,6 +175,15 @@ pub async fn register_wallet_tokens(
   user_id: i32,
   // db: &DatabaseConnection,
 ) -> Result<(), String> {
+  //  this setsup  default stop loss on a wallet
+  let db = db::connect_db()
+    .await
+    .expect("Failed to connect to the database");
+
+  let settings = find_or_create_user_settings(&db, user_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
   let mut tokens_to_watch: Vec<SlingTokenInfo> = Vec::new();
 
   match get_spl_tokens_in_wallet(address).await {
@@ -184,12 +196,7 @@ pub async fn register_wallet_tokens(
 
         let token_usd_value = token_usd_price * token.token_balance;
 
-        let min_watchlist_token_amount: f64 = env::var("MINIMUM_WATCHLIST_TOKEN_USD_AMOUNT")
-          .ok()
-          .and_then(|value| value.parse::<f64>().ok())
-          .unwrap_or(10.0);
-
-        if token_usd_value > min_watchlist_token_amount {
+        if token_usd_value > settings.min_watchlist_usd as f64 {
           tokens_to_watch.push(SlingTokenInfo {
             mint_address: token.mint_address,
             token_usd_balance: token_usd_value,
@@ -204,13 +211,9 @@ pub async fn register_wallet_tokens(
     Err(e) => eprintln!("Error getting tokens for user: {}", e),
   };
 
-  //  this setsup  default stop loss on a wallet
-  let db = db::connect_db()
-    .await
-    .expect("Failed to connect to the database");
-
   let wallet_in_db = wallets::Entity::find()
     .filter(wallets::Column::Address.eq(address))
+    .filter(wallets::Column::UserId.eq(user_id))
     .one(&db)
     .await
     .map_err(|e| e.to_string())?;
@@ -219,12 +222,10 @@ pub async fn register_wallet_tokens(
   if let Some(wallet) = wallet_in_db {
     // This sets up the default stop loss on a wallet
     for token in tokens_to_watch {
-      let default_stop_loss: f32 = env::var("DEFAULT_STOP_LOSS_PERCENTAGE")
-        .ok()
-        .and_then(|value| value.parse::<f32>().ok())
-        .unwrap_or(40.0);
+      let default_stop_loss = settings.default_stop_loss_percentage;
 
-      let stop_loss_target_price = token.price * ((default_stop_loss / 100.0) as f64);
+      // the same drop below the price that anchoring to the entry measures
+      let stop_loss_target_price = token.price * (1.0 - (default_stop_loss / 100.0) as f64);
 
       // Check if there's an existing strategy for the token
       let existing_strat = trade_orders::Entity::find()
@@ -0,0 +1,366 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use entity::{tokens, users, wallet_holdings, wallets};
+use sea_orm::{
+  sea_query::{Expr, OnConflict},
+  ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter,
+  QueryOrder, Set,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::utils::{
+  notifications::notify_user_by_telegram,
+  price::solana::fetch_token_price,
+  wallets::{
+    activity::{
+      balance_changes_swap, fetch_balance_changes, fetch_signatures_since, WalletBalanceChanges,
+      WalletSwapSide,
+    },
+    solana::get_spl_tokens_in_wallet,
+  },
+};
+
+// Watch-only wallets are addresses a user keeps an eye on without holding their keys. Their
+// holdings are tracked like those of any wallet and what they send, receive and buy is sent to
+// the user on Telegram. They never trade.
+
+const WATCH_ONLY_ENCRYPTION_SCHEMA: &str = "watch_only";
+
+// SOL moving in or out below this, rent and the like, isn't alerted on
+const MIN_ALERT_SOL: f64 = 0.001;
+
+/// Watch an address. Only activity after this is alerted on.
+pub async fn create_watch_only_wallet(
+  db: &DatabaseConnection,
+  user_id: i32,
+  title: &str,
+  address: &str,
+) -> Result<wallets::Model> {
+  Pubkey::from_str(address).map_err(|_| anyhow!("{} is not a Solana address", address))?;
+
+  let existing_wallet = wallets::Entity::find()
+    .filter(wallets::Column::UserId.eq(user_id))
+    .filter(wallets::Column::Address.eq(address))
+    .one(db)
+    .await
+    .context("Failed to retrieve wallets")?;
+
+  if let Some(wallet) = existing_wallet {
+    return Err(anyhow!(
+      "You already have {} as wallet {}",
+      wallet.address,
+      wallet.id
+    ));
+  }
+
+  let new_wallet = wallets::ActiveModel {
+    title: Set(title.to_string()),
+    chain: Set("Solana".to_string()),
+    user_id: Set(user_id),
+    address: Set(address.to_string()),
+    encryption_schema: Set(WATCH_ONLY_ENCRYPTION_SCHEMA.to_string()),
+    watch_only: Set(true),
+    ..Default::default()
+  };
+
+  new_wallet
+    .insert(db)
+    .await
+    .context("Failed to create watch-only wallet")
+}
+
+pub async fn find_user_watch_only_wallets(
+  db: &DatabaseConnection,
+  user_id: i32,
+) -> Result<Vec<wallets::Model>> {
+  wallets::Entity::find()
+    .filter(wallets::Column::UserId.eq(user_id))
+    .filter(wallets::Column::WatchOnly.eq(true))
+    .order_by_desc(wallets::Column::CreatedAt)
+    .all(db)
+    .await
+    .context("Failed to retrieve watch-only wallets")
+}
+
+pub async fn delete_watch_only_wallet(
+  db: &DatabaseConnection,
+  user_id: i32,
+  wallet_id: i32,
+) -> Result<()> {
+  let deleted = wallets::Entity::delete_many()
+    .filter(wallets::Column::Id.eq(wallet_id))
+    .filter(wallets::Column::UserId.eq(user_id))
+    .filter(wallets::Column::WatchOnly.eq(true))
+    .exec(db)
+    .await
+    .context("Failed to delete watch-only wallet")?;
+
+  if deleted.rows_affected == 0 {
+    return Err(anyhow!("No watch-only wallet {} found", wallet_id));
+  }
+
+  Ok(())
+}
+
+/// Tokens held by a wallet as of the last holdings update, most valuable first
+pub async fn find_wallet_holdings(
+  db: &DatabaseConnection,
+  wallet_id: i32,
+) -> Result<Vec<wallet_holdings::Model>> {
+  wallet_holdings::Entity::find()
+    .filter(wallet_holdings::Column::WalletId.eq(wallet_id))
+    .order_by_desc(wallet_holdings::Column::ValueUsd)
+    .all(db)
+    .await
+    .context("Failed to retrieve wallet holdings")
+}
+
+pub fn format_watch_only_wallet(
+  wallet: &wallets::Model,
+  holdings: &[wallet_holdings::Model],
+) -> String {
+  let value_usd: f32 = holdings
+    .iter()
+    .filter_map(|holding| holding.value_usd)
+    .sum();
+
+  format!(
+    "{}. {}\n{}\nHolds {} tokens worth ${:.2}",
+    wallet.id,
+    wallet.title,
+    wallet.address,
+    holdings.len(),
+    value_usd
+  )
+}
+
+/// Refresh the tokens a watch-only wallet holds and what they are worth
+pub async fn update_wallet_holdings(
+  db: &DatabaseConnection,
+  wallet: &wallets::Model,
+) -> Result<()> {
+  let held_tokens = get_spl_tokens_in_wallet(&wallet.address)
+    .await
+    .map_err(|e| anyhow!("Failed to fetch tokens of {}: {}", wallet.address, e))?;
+
+  let now = Utc::now().naive_utc();
+
+  for token in &held_tokens {
+    // tokens are linked when known, a wallet can hold anything
+    let token_id = tokens::Entity::find()
+      .filter(tokens::Column::ContractAddress.eq(&token.mint_address))
+      .one(db)
+      .await
+      .context("Failed to retrieve token")?
+      .map(|token| token.id);
+
+    let value_usd = fetch_token_price(&token.mint_address)
+      .await
+      .ok()
+      .map(|price| (price * token.token_balance) as f32);
+
+    let holding = wallet_holdings::ActiveModel {
+      wallet_id: Set(wallet.id),
+      token_id: Set(token_id),
+      contract_address: Set(token.mint_address.clone()),
+      balance: Set(token.token_balance),
+      value_usd: Set(value_usd),
+      updated_at: Set(now),
+      ..Default::default()
+    };
+
+    wallet_holdings::Entity::insert(holding)
+      .on_conflict(
+        OnConflict::columns([
+          wallet_holdings::Column::WalletId,
+          wallet_holdings::Column::ContractAddress,
+        ])
+        .update_columns([
+          wallet_holdings::Column::TokenId,
+          wallet_holdings::Column::Balance,
+          wallet_holdings::Column::ValueUsd,
+          wallet_holdings::Column::UpdatedAt,
+        ])
+        .to_owned(),
+      )
+      .exec(db)
+      .await
+      .context("Failed to save wallet holding")?;
+  }
+
+  // whatever wasn't seen this time has been let go of
+  let held_mints: Vec<String> = held_tokens
+    .into_iter()
+    .map(|token| token.mint_address)
+    .collect();
+
+  wallet_holdings::Entity::delete_many()
+    .filter(wallet_holdings::Column::WalletId.eq(wallet.id))
+    .filter(wallet_holdings::Column::ContractAddress.is_not_in(held_mints))
+    .exec(db)
+    .await
+    .context("Failed to remove sold wallet holdings")?;
+
+  Ok(())
+}
+
+fn balance_change_alerts(
+  wallet: &wallets::Model,
+  signature: &str,
+  changes: &WalletBalanceChanges,
+) -> Vec<String> {
+  if let Some(swap) = balance_changes_swap(signature, changes) {
+    let new_token = changes
+      .token_changes
+      .first()
+      .is_some_and(|change| change.pre_amount == 0);
+
+    let message = match swap.side {
+      WalletSwapSide::Buy if new_token => format!(
+        "👀 {} bought a new token {}: {} for {:.4} SOL",
+        wallet.title, swap.mint, swap.token_amount, swap.sol_amount
+      ),
+      WalletSwapSide::Buy => format!(
+        "{} bought {} more of {} for {:.4} SOL",
+        wallet.title, swap.token_amount, swap.mint, swap.sol_amount
+      ),
+      WalletSwapSide::Sell => format!(
+        "{} sold {} of {} for {:.4} SOL",
+        wallet.title, swap.token_amount, swap.mint, swap.sol_amount
+      ),
+    };
+
+    return vec![format!("{}\n{}", message, signature)];
+  }
+
+  let mut alerts = vec![];
+
+  if changes.sol_amount() >= MIN_ALERT_SOL {
+    alerts.push(format!(
+      "{} {} {:.4} SOL\n{}",
+      wallet.title,
+      if changes.sol_delta > 0 {
+        "received"
+      } else {
+        "sent"
+      },
+      changes.sol_amount(),
+      signature
+    ));
+  }
+
+  for change in &changes.token_changes {
+    alerts.push(format!(
+      "{} {} {} of {}\n{}",
+      wallet.title,
+      if change.delta > 0 { "received" } else { "sent" },
+      change.ui_amount(),
+      change.mint,
+      signature
+    ));
+  }
+
+  alerts
+}
+
+// moves the cursor only if no other run moved it first, false when one did
+async fn advance_wallet_cursor(
+  db: &DatabaseConnection,
+  wallet: &wallets::Model,
+  signature: &str,
+) -> Result<bool> {
+  let cursor = match &wallet.last_signature {
+    Some(last_signature) => Condition::all().add(wallets::Column::LastSignature.eq(last_signature)),
+    None => Condition::all().add(wallets::Column::LastSignature.is_null()),
+  };
+
+  let updated = wallets::Entity::update_many()
+    .col_expr(wallets::Column::LastSignature, Expr::value(signature))
+    .col_expr(
+      wallets::Column::UpdatedAt,
+      Expr::value(Utc::now().naive_utc()),
+    )
+    .filter(wallets::Column::Id.eq(wallet.id))
+    .filter(cursor)
+    .exec(db)
+    .await
+    .context("Failed to update wallet cursor")?;
+
+  Ok(updated.rows_affected > 0)
+}
+
+async fn alert_new_activity(db: &DatabaseConnection, wallet: wallets::Model) -> Result<()> {
+  let signatures =
+    fetch_signatures_since(&wallet.address, wallet.last_signature.as_deref()).await?;
+
+  let newest_signature = match signatures.last() {
+    Some(newest_signature) => newest_signature,
+    None => return Ok(()),
+  };
+
+  if !advance_wallet_cursor(db, &wallet, newest_signature).await? {
+    return Ok(());
+  }
+
+  // a new watch-only wallet starts from its latest transaction
+  if wallet.last_signature.is_none() {
+    return Ok(());
+  }
+
+  let tg_id = users::Entity::find_by_id(wallet.user_id)
+    .one(db)
+    .await
+    .context("Failed to retrieve user")?
+    .and_then(|user| user.tg_id.parse::<i64>().ok());
+
+  let tg_id = match tg_id {
+    Some(tg_id) => tg_id,
+    None => return Ok(()),
+  };
+
+  for signature in &signatures {
+    let changes = match fetch_balance_changes(&wallet.address, signature).await {
+      Ok(Some(changes)) => changes,
+      Ok(None) => continue,
+      Err(e) => {
+        eprintln!("Failed to read transaction {}: {:?}", signature, e);
+        continue;
+      }
+    };
+
+    for alert in balance_change_alerts(&wallet, signature, &changes) {
+      if let Err(e) = notify_user_by_telegram(tg_id, &alert).await {
+        eprintln!("Error notifying user {}: {}", tg_id, e);
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Alert users of what their watch-only wallets did since the last run
+pub async fn run_watch_only_wallet_alerts(db: &DatabaseConnection) -> Result<()> {
+  let wallets = wallets::Entity::find()
+    .filter(wallets::Column::WatchOnly.eq(true))
+    .all(db)
+    .await
+    .context("Failed to retrieve watch-only wallets")?;
+
+  let mut tasks = vec![];
+
+  for wallet in wallets {
+    let database = db.clone();
+    let task = tokio::spawn(async move {
+      let wallet_id = wallet.id;
+      if let Err(e) = alert_new_activity(&database, wallet).await {
+        eprintln!("Failed to alert activity of wallet {}: {:?}", wallet_id, e);
+      }
+    });
+    tasks.push(task);
+  }
+
+  futures::future::join_all(tasks).await;
+
+  Ok(())
+}
@@ -2,10 +2,16 @@
 
 pub mod prelude;
 
+pub mod dca_plans;
+pub mod execution_locks;
 pub mod onchain_transactions;
+pub mod positions;
 pub mod raydium_token_launches;
 pub mod token_prices;
 pub mod tokens;
 pub mod trade_orders;
+pub mod user_settings;
 pub mod users;
+pub mod wallet_follows;
+pub mod wallet_holdings;
 pub mod wallets;
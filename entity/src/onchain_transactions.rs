@@ -20,12 +20,36 @@ pub struct Model {
   pub value_usd: Option<f32>,
   pub from_token: Option<String>,
   pub to_token: Option<String>,
+  pub simulated: bool,
+  pub input_amount: Option<i64>,
+  pub output_amount: Option<i64>,
+  #[sea_orm(unique)]
+  pub idempotency_key: Option<String>,
+  pub source_transaction_hash: Option<String>,
+  pub preflight: Option<Json>,
+  // SOL per token of the fill
+  #[sea_orm(column_type = "Float", nullable)]
+  pub effective_price: Option<f32>,
+  pub network_fee_lamports: Option<i64>,
+  pub priority_fee_lamports: Option<i64>,
+  // set once the fill was applied to its position
+  pub position_id: Option<i32>,
   pub created_at: DateTime,
   pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::positions::Entity",
+    from = "Column::PositionId",
+    to = "super::positions::Column::Id",
+    on_update = "NoAction",
+    on_delete = "NoAction"
+  )]
+  Positions,
+  #[sea_orm(has_many = "super::trade_orders::Entity")]
+  TradeOrders,
   #[sea_orm(
     belongs_to = "super::users::Entity",
     from = "Column::UserId",
@@ -44,6 +68,18 @@ pub enum Relation {
   Wallets,
 }
 
+impl Related<super::positions::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Positions.def()
+  }
+}
+
+impl Related<super::trade_orders::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::TradeOrders.def()
+  }
+}
+
 impl Related<super::users::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::Users.def()
@@ -1,9 +1,15 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.0
 
+pub use super::dca_plans::Entity as DcaPlans;
+pub use super::execution_locks::Entity as ExecutionLocks;
 pub use super::onchain_transactions::Entity as OnchainTransactions;
+pub use super::positions::Entity as Positions;
 pub use super::raydium_token_launches::Entity as RaydiumTokenLaunches;
 pub use super::token_prices::Entity as TokenPrices;
 pub use super::tokens::Entity as Tokens;
 pub use super::trade_orders::Entity as TradeOrders;
+pub use super::user_settings::Entity as UserSettings;
 pub use super::users::Entity as Users;
+pub use super::wallet_follows::Entity as WalletFollows;
+pub use super::wallet_holdings::Entity as WalletHoldings;
 pub use super::wallets::Entity as Wallets;
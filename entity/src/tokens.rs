@@ -21,8 +21,18 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(has_many = "super::dca_plans::Entity")]
+  DcaPlans,
   #[sea_orm(has_many = "super::trade_orders::Entity")]
   TradeOrders,
+  #[sea_orm(has_many = "super::wallet_holdings::Entity")]
+  WalletHoldings,
+}
+
+impl Related<super::dca_plans::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::DcaPlans.def()
+  }
 }
 
 impl Related<super::trade_orders::Entity> for Entity {
@@ -31,4 +41,10 @@ impl Related<super::trade_orders::Entity> for Entity {
   }
 }
 
+impl Related<super::wallet_holdings::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::WalletHoldings.def()
+  }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
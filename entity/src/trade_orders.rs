@@ -18,15 +18,43 @@ pub struct Model {
   pub target_percentage: f32,
   pub contract_address: String,
   pub strategy: String,
-  pub active: bool,
+  pub status: String,
   pub created_by: String,
   pub metadata: Option<Json>,
+  pub parent_id: Option<i32>,
+  #[sea_orm(column_type = "Float", nullable)]
+  pub sell_fraction: Option<f32>,
+  pub triggered_at: Option<DateTime>,
+  pub filled_at: Option<DateTime>,
+  pub failed_at: Option<DateTime>,
+  pub cancelled_at: Option<DateTime>,
+  pub expired_at: Option<DateTime>,
+  pub expires_at: Option<DateTime>,
+  #[sea_orm(column_type = "Text", nullable)]
+  pub failure_reason: Option<String>,
+  pub filled_transaction_id: Option<i32>,
   pub created_at: DateTime,
   pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::onchain_transactions::Entity",
+    from = "Column::FilledTransactionId",
+    to = "super::onchain_transactions::Column::Id",
+    on_update = "NoAction",
+    on_delete = "NoAction"
+  )]
+  OnchainTransactions,
+  #[sea_orm(
+    belongs_to = "Entity",
+    from = "Column::ParentId",
+    to = "Column::Id",
+    on_update = "NoAction",
+    on_delete = "NoAction"
+  )]
+  SelfRef,
   #[sea_orm(
     belongs_to = "super::tokens::Entity",
     from = "Column::TokenId",
@@ -53,6 +81,12 @@ pub enum Relation {
   Wallets,
 }
 
+impl Related<super::onchain_transactions::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::OnchainTransactions.def()
+  }
+}
+
 impl Related<super::tokens::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::Tokens.def()
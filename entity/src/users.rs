@@ -19,26 +19,58 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(has_many = "super::dca_plans::Entity")]
+  DcaPlans,
   #[sea_orm(has_many = "super::onchain_transactions::Entity")]
   OnchainTransactions,
+  #[sea_orm(has_many = "super::positions::Entity")]
+  Positions,
   #[sea_orm(has_many = "super::trade_orders::Entity")]
   TradeOrders,
+  #[sea_orm(has_one = "super::user_settings::Entity")]
+  UserSettings,
+  #[sea_orm(has_many = "super::wallet_follows::Entity")]
+  WalletFollows,
   #[sea_orm(has_many = "super::wallets::Entity")]
   Wallets,
 }
 
+impl Related<super::dca_plans::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::DcaPlans.def()
+  }
+}
+
 impl Related<super::onchain_transactions::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::OnchainTransactions.def()
   }
 }
 
+impl Related<super::positions::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Positions.def()
+  }
+}
+
 impl Related<super::trade_orders::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::TradeOrders.def()
   }
 }
 
+impl Related<super::user_settings::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::UserSettings.def()
+  }
+}
+
+impl Related<super::wallet_follows::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::WalletFollows.def()
+  }
+}
+
 impl Related<super::wallets::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::Wallets.def()
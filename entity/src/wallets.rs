@@ -9,21 +9,27 @@ pub struct Model {
   pub id: i32,
   pub title: String,
   pub chain: String,
-  #[sea_orm(unique)]
   pub address: String,
-  pub encrypted_private_key: String,
-  pub secret_key: String,
-  pub salt: String,
+  pub encrypted_private_key: Option<String>,
+  pub secret_key: Option<String>,
+  pub salt: Option<String>,
   pub encryption_schema: String,
   pub user_id: i32,
+  pub paper_trading: Option<bool>,
+  pub watch_only: bool,
+  pub last_signature: Option<String>,
   pub created_at: DateTime,
   pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(has_many = "super::dca_plans::Entity")]
+  DcaPlans,
   #[sea_orm(has_many = "super::onchain_transactions::Entity")]
   OnchainTransactions,
+  #[sea_orm(has_many = "super::positions::Entity")]
+  Positions,
   #[sea_orm(has_many = "super::trade_orders::Entity")]
   TradeOrders,
   #[sea_orm(
@@ -34,6 +40,16 @@ pub enum Relation {
     on_delete = "NoAction"
   )]
   Users,
+  #[sea_orm(has_many = "super::wallet_follows::Entity")]
+  WalletFollows,
+  #[sea_orm(has_many = "super::wallet_holdings::Entity")]
+  WalletHoldings,
+}
+
+impl Related<super::dca_plans::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::DcaPlans.def()
+  }
 }
 
 impl Related<super::onchain_transactions::Entity> for Entity {
@@ -42,6 +58,12 @@ impl Related<super::onchain_transactions::Entity> for Entity {
   }
 }
 
+impl Related<super::positions::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Positions.def()
+  }
+}
+
 impl Related<super::trade_orders::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::TradeOrders.def()
@@ -54,4 +76,16 @@ impl Related<super::users::Entity> for Entity {
   }
 }
 
+impl Related<super::wallet_follows::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::WalletFollows.def()
+  }
+}
+
+impl Related<super::wallet_holdings::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::WalletHoldings.def()
+  }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -7,6 +7,23 @@ mod m20241014_191627_create_token_table;
 mod m20241014_194139_create_trade_orders_table;
 mod m20241019_002947_create_onchain_transactions;
 mod m20241101_020551_create_raydium_launch_token;
+mod m20241112_143015_create_user_settings_table;
+mod m20241118_091204_add_ladder_columns_to_trade_orders;
+mod m20241120_104512_create_dca_plans_table;
+mod m20241122_160830_add_status_to_trade_orders;
+mod m20241125_093317_add_paper_trading;
+mod m20241127_140215_create_execution_locks_table;
+mod m20241129_101844_add_max_price_impact_to_user_settings;
+mod m20241202_084510_add_slippage_escalation_to_user_settings;
+mod m20241204_112233_create_wallet_follows_table;
+mod m20241206_153040_add_watch_only_wallets;
+mod m20241209_091502_add_jito_bundles_to_user_settings;
+mod m20241211_140318_add_preflight_to_onchain_transactions;
+mod m20241213_102745_add_fills_to_onchain_transactions;
+mod m20241216_091233_create_positions_table;
+mod m20241218_103512_add_simulated_to_positions;
+mod m20241220_094512_default_auto_buy_on;
+mod m20241221_101530_unique_owned_wallet_addresses;
 
 pub struct Migrator;
 
@@ -21,6 +38,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20241014_194139_create_trade_orders_table::Migration),
             Box::new(m20241019_002947_create_onchain_transactions::Migration),
             Box::new(m20241101_020551_create_raydium_launch_token::Migration),
+            Box::new(m20241112_143015_create_user_settings_table::Migration),
+            Box::new(m20241118_091204_add_ladder_columns_to_trade_orders::Migration),
+            Box::new(m20241120_104512_create_dca_plans_table::Migration),
+            Box::new(m20241122_160830_add_status_to_trade_orders::Migration),
+            Box::new(m20241125_093317_add_paper_trading::Migration),
+            Box::new(m20241127_140215_create_execution_locks_table::Migration),
+            Box::new(m20241129_101844_add_max_price_impact_to_user_settings::Migration),
+            Box::new(m20241202_084510_add_slippage_escalation_to_user_settings::Migration),
+            Box::new(m20241204_112233_create_wallet_follows_table::Migration),
+            Box::new(m20241206_153040_add_watch_only_wallets::Migration),
+            Box::new(m20241209_091502_add_jito_bundles_to_user_settings::Migration),
+            Box::new(m20241211_140318_add_preflight_to_onchain_transactions::Migration),
+            Box::new(m20241213_102745_add_fills_to_onchain_transactions::Migration),
+            Box::new(m20241216_091233_create_positions_table::Migration),
+            Box::new(m20241218_103512_add_simulated_to_positions::Migration),
+            Box::new(m20241220_094512_default_auto_buy_on::Migration),
+            Box::new(m20241221_101530_unique_owned_wallet_addresses::Migration),
         ]
   }
 }
@@ -0,0 +1,124 @@
+use sea_orm_migration::{prelude::*, schema::pk_auto};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+use super::m20241008_115542_create_user_table::User;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(UserSetting::Table)
+          .if_not_exists()
+          .col(pk_auto(UserSetting::Id))
+          .col(
+            ColumnDef::new(UserSetting::UserId)
+              .integer()
+              .not_null()
+              .unique_key(),
+          )
+          .col(
+            ColumnDef::new(UserSetting::AutoBuy)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(
+            ColumnDef::new(UserSetting::BuySizePercentage)
+              .float()
+              .not_null()
+              .default(10.0),
+          )
+          .col(
+            ColumnDef::new(UserSetting::MinSolPerTrade)
+              .float()
+              .not_null()
+              .default(0.01),
+          )
+          .col(
+            ColumnDef::new(UserSetting::MaxSolPerTrade)
+              .float()
+              .not_null()
+              .default(1.0),
+          )
+          .col(
+            ColumnDef::new(UserSetting::LaunchStopLossPercentage)
+              .float()
+              .not_null()
+              .default(20.0),
+          )
+          .col(
+            ColumnDef::new(UserSetting::DefaultStopLossPercentage)
+              .float()
+              .not_null()
+              .default(40.0),
+          )
+          .col(
+            ColumnDef::new(UserSetting::MinWatchlistUsd)
+              .float()
+              .not_null()
+              .default(10.0),
+          )
+          .col(
+            ColumnDef::new(UserSetting::SlippageBps)
+              .integer()
+              .not_null()
+              .default(50),
+          )
+          .col(
+            ColumnDef::new(UserSetting::LaunchClasses)
+              .string()
+              .not_null()
+              .default("crazy_launch"),
+          )
+          .col(
+            ColumnDef::new(UserSetting::CreatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .col(
+            ColumnDef::new(UserSetting::UpdatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_user_settings_user")
+              .from(UserSetting::Table, UserSetting::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(UserSetting::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum UserSetting {
+  #[sea_orm(iden = "user_settings")]
+  Table,
+  Id,
+  UserId,
+  AutoBuy,
+  BuySizePercentage,
+  MinSolPerTrade,
+  MaxSolPerTrade,
+  LaunchStopLossPercentage,
+  DefaultStopLossPercentage,
+  MinWatchlistUsd,
+  SlippageBps,
+  LaunchClasses,
+  CreatedAt,
+  UpdatedAt,
+}
@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(TradeOrder::Table)
+          .add_column(ColumnDef::new(TradeOrder::ParentId).integer())
+          .add_column(ColumnDef::new(TradeOrder::SellFraction).float())
+          .add_column(ColumnDef::new(TradeOrder::FilledAt).timestamp())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_foreign_key(
+        ForeignKey::create()
+          .name("fk_trade_orders_parent")
+          .from(TradeOrder::Table, TradeOrder::ParentId)
+          .to(TradeOrder::Table, TradeOrder::Id)
+          .to_owned(),
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_foreign_key(
+        ForeignKey::drop()
+          .name("fk_trade_orders_parent")
+          .table(TradeOrder::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(TradeOrder::Table)
+          .drop_column(TradeOrder::ParentId)
+          .drop_column(TradeOrder::SellFraction)
+          .drop_column(TradeOrder::FilledAt)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum TradeOrder {
+  #[sea_orm(iden = "trade_orders")]
+  Table,
+  Id,
+  ParentId,
+  SellFraction,
+  FilledAt,
+}
@@ -0,0 +1,113 @@
+use sea_orm_migration::{prelude::*, schema::pk_auto};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+use super::m20241008_115542_create_user_table::User;
+use crate::m20241008_121835_create_wallet_table::Wallet;
+use crate::m20241014_191627_create_token_table::Token;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(DcaPlan::Table)
+          .if_not_exists()
+          .col(pk_auto(DcaPlan::Id))
+          .col(ColumnDef::new(DcaPlan::UserId).integer().not_null())
+          .col(ColumnDef::new(DcaPlan::WalletId).integer().not_null())
+          .col(ColumnDef::new(DcaPlan::TokenId).integer().not_null())
+          .col(ColumnDef::new(DcaPlan::ContractAddress).string().not_null())
+          .col(ColumnDef::new(DcaPlan::AmountPerBuy).float().not_null())
+          .col(
+            ColumnDef::new(DcaPlan::IntervalMinutes)
+              .integer()
+              .not_null(),
+          )
+          .col(ColumnDef::new(DcaPlan::TotalBudget).float().not_null())
+          .col(
+            ColumnDef::new(DcaPlan::SpentSol)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(DcaPlan::BuysCompleted)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(DcaPlan::Status)
+              .string()
+              .not_null()
+              .default("active"),
+          )
+          .col(ColumnDef::new(DcaPlan::NextBuyAt).timestamp().not_null())
+          .col(ColumnDef::new(DcaPlan::LastBuyAt).timestamp())
+          .col(ColumnDef::new(DcaPlan::EndsAt).timestamp().not_null())
+          .col(
+            ColumnDef::new(DcaPlan::CreatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .col(
+            ColumnDef::new(DcaPlan::UpdatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_dca_plans_user")
+              .from(DcaPlan::Table, DcaPlan::UserId)
+              .to(User::Table, User::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_dca_plans_wallet")
+              .from(DcaPlan::Table, DcaPlan::WalletId)
+              .to(Wallet::Table, Wallet::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_dca_plans_token")
+              .from(DcaPlan::Table, DcaPlan::TokenId)
+              .to(Token::Table, Token::Id),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(DcaPlan::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum DcaPlan {
+  #[sea_orm(iden = "dca_plans")]
+  Table,
+  Id,
+  UserId,
+  WalletId,
+  TokenId,
+  ContractAddress,
+  AmountPerBuy,
+  IntervalMinutes,
+  TotalBudget,
+  SpentSol,
+  BuysCompleted,
+  Status,
+  NextBuyAt,
+  LastBuyAt,
+  EndsAt,
+  CreatedAt,
+  UpdatedAt,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+use crate::m20241019_002947_create_onchain_transactions::OnchainTransaction;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(TradeOrder::Table)
+          .add_column(
+            ColumnDef::new(TradeOrder::Status)
+              .string()
+              .not_null()
+              .default("active"),
+          )
+          .add_column(ColumnDef::new(TradeOrder::TriggeredAt).timestamp())
+          .add_column(ColumnDef::new(TradeOrder::FailedAt).timestamp())
+          .add_column(ColumnDef::new(TradeOrder::CancelledAt).timestamp())
+          .add_column(ColumnDef::new(TradeOrder::ExpiredAt).timestamp())
+          .add_column(ColumnDef::new(TradeOrder::ExpiresAt).timestamp())
+          .add_column(ColumnDef::new(TradeOrder::FailureReason).text())
+          .add_column(ColumnDef::new(TradeOrder::FilledTransactionId).integer())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_foreign_key(
+        ForeignKey::create()
+          .name("fk_trade_orders_filled_transaction")
+          .from(TradeOrder::Table, TradeOrder::FilledTransactionId)
+          .to(OnchainTransaction::Table, OnchainTransaction::Id)
+          .to_owned(),
+      )
+      .await?;
+
+    // Orders that were filled through a ladder keep that, anything else switched off was cancelled
+    manager
+      .exec_stmt(
+        Query::update()
+          .table(TradeOrder::Table)
+          .value(TradeOrder::Status, "filled")
+          .and_where(Expr::col(TradeOrder::FilledAt).is_not_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .exec_stmt(
+        Query::update()
+          .table(TradeOrder::Table)
+          .value(TradeOrder::Status, "cancelled")
+          .value(TradeOrder::CancelledAt, Expr::current_timestamp())
+          .and_where(Expr::col(TradeOrder::Active).eq(false))
+          .and_where(Expr::col(TradeOrder::FilledAt).is_null())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(TradeOrder::Table)
+          .drop_column(TradeOrder::Active)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(TradeOrder::Table)
+          .add_column(
+            ColumnDef::new(TradeOrder::Active)
+              .boolean()
+              .not_null()
+              .default(true),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .exec_stmt(
+        Query::update()
+          .table(TradeOrder::Table)
+          .value(TradeOrder::Active, false)
+          .and_where(Expr::col(TradeOrder::Status).is_not_in(["pending", "active"]))
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .drop_foreign_key(
+        ForeignKey::drop()
+          .name("fk_trade_orders_filled_transaction")
+          .table(TradeOrder::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(TradeOrder::Table)
+          .drop_column(TradeOrder::Status)
+          .drop_column(TradeOrder::TriggeredAt)
+          .drop_column(TradeOrder::FailedAt)
+          .drop_column(TradeOrder::CancelledAt)
+          .drop_column(TradeOrder::ExpiredAt)
+          .drop_column(TradeOrder::ExpiresAt)
+          .drop_column(TradeOrder::FailureReason)
+          .drop_column(TradeOrder::FilledTransactionId)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum TradeOrder {
+  #[sea_orm(iden = "trade_orders")]
+  Table,
+  Active,
+  Status,
+  TriggeredAt,
+  FilledAt,
+  FailedAt,
+  CancelledAt,
+  ExpiredAt,
+  ExpiresAt,
+  FailureReason,
+  FilledTransactionId,
+}
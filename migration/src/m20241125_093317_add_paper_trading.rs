@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .add_column(
+            ColumnDef::new(UserSetting::PaperTrading)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // null follows the user's setting
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Wallet::Table)
+          .add_column(ColumnDef::new(Wallet::PaperTrading).boolean())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .add_column(
+            ColumnDef::new(OnchainTransaction::Simulated)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .add_column(ColumnDef::new(OnchainTransaction::InputAmount).big_integer())
+          .add_column(ColumnDef::new(OnchainTransaction::OutputAmount).big_integer())
+          .to_owned(),
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .drop_column(OnchainTransaction::Simulated)
+          .drop_column(OnchainTransaction::InputAmount)
+          .drop_column(OnchainTransaction::OutputAmount)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Wallet::Table)
+          .drop_column(Wallet::PaperTrading)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .drop_column(UserSetting::PaperTrading)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserSetting {
+  #[sea_orm(iden = "user_settings")]
+  Table,
+  PaperTrading,
+}
+
+#[derive(DeriveIden)]
+enum Wallet {
+  #[sea_orm(iden = "wallets")]
+  Table,
+  PaperTrading,
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  Simulated,
+  InputAmount,
+  OutputAmount,
+}
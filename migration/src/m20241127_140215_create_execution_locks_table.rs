@@ -0,0 +1,100 @@
+use sea_orm_migration::{prelude::*, schema::pk_auto};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(ExecutionLock::Table)
+          .if_not_exists()
+          .col(pk_auto(ExecutionLock::Id))
+          .col(
+            ColumnDef::new(ExecutionLock::LockKey)
+              .string()
+              .not_null()
+              .unique_key(),
+          )
+          .col(ColumnDef::new(ExecutionLock::Owner).string().not_null())
+          .col(
+            ColumnDef::new(ExecutionLock::ExpiresAt)
+              .timestamp()
+              .not_null(),
+          )
+          .col(
+            ColumnDef::new(ExecutionLock::CreatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .add_column(ColumnDef::new(OnchainTransaction::IdempotencyKey).string())
+          .to_owned(),
+      )
+      .await?;
+
+    // several rows without a key are still allowed, postgres doesn't compare nulls
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_onchain_transactions_idempotency_key")
+          .table(OnchainTransaction::Table)
+          .col(OnchainTransaction::IdempotencyKey)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_onchain_transactions_idempotency_key")
+          .table(OnchainTransaction::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .drop_column(OnchainTransaction::IdempotencyKey)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .drop_table(Table::drop().table(ExecutionLock::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum ExecutionLock {
+  #[sea_orm(iden = "execution_locks")]
+  Table,
+  Id,
+  LockKey,
+  Owner,
+  ExpiresAt,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  IdempotencyKey,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .add_column(
+            ColumnDef::new(UserSetting::MaxPriceImpactPercentage)
+              .float()
+              .not_null()
+              .default(10.0),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .drop_column(UserSetting::MaxPriceImpactPercentage)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserSetting {
+  #[sea_orm(iden = "user_settings")]
+  Table,
+  MaxPriceImpactPercentage,
+}
@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .add_column(
+            ColumnDef::new(UserSetting::SellSlippageBps)
+              .integer()
+              .not_null()
+              .default(100),
+          )
+          .add_column(
+            ColumnDef::new(UserSetting::MaxBuySlippageBps)
+              .integer()
+              .not_null()
+              .default(500),
+          )
+          .add_column(
+            ColumnDef::new(UserSetting::MaxSellSlippageBps)
+              .integer()
+              .not_null()
+              .default(1000),
+          )
+          .add_column(
+            ColumnDef::new(UserSetting::MaxStopLossSlippageBps)
+              .integer()
+              .not_null()
+              .default(2500),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .drop_column(UserSetting::SellSlippageBps)
+          .drop_column(UserSetting::MaxBuySlippageBps)
+          .drop_column(UserSetting::MaxSellSlippageBps)
+          .drop_column(UserSetting::MaxStopLossSlippageBps)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserSetting {
+  #[sea_orm(iden = "user_settings")]
+  Table,
+  SellSlippageBps,
+  MaxBuySlippageBps,
+  MaxSellSlippageBps,
+  MaxStopLossSlippageBps,
+}
@@ -0,0 +1,148 @@
+use sea_orm_migration::{prelude::*, schema::pk_auto};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+use super::m20241008_115542_create_user_table::User;
+use crate::m20241008_121835_create_wallet_table::Wallet;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WalletFollow::Table)
+          .if_not_exists()
+          .col(pk_auto(WalletFollow::Id))
+          .col(ColumnDef::new(WalletFollow::UserId).integer().not_null())
+          .col(ColumnDef::new(WalletFollow::WalletId).integer().not_null())
+          .col(
+            ColumnDef::new(WalletFollow::FollowedAddress)
+              .string()
+              .not_null(),
+          )
+          .col(
+            ColumnDef::new(WalletFollow::SizeMode)
+              .string()
+              .not_null()
+              .default("fixed"),
+          )
+          .col(ColumnDef::new(WalletFollow::SizeValue).float().not_null())
+          .col(
+            ColumnDef::new(WalletFollow::MaxSolPerTrade)
+              .float()
+              .not_null(),
+          )
+          .col(ColumnDef::new(WalletFollow::MaxTotalSol).float())
+          .col(
+            ColumnDef::new(WalletFollow::SpentSol)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(WalletFollow::MirrorSells)
+              .boolean()
+              .not_null()
+              .default(true),
+          )
+          .col(
+            ColumnDef::new(WalletFollow::Enabled)
+              .boolean()
+              .not_null()
+              .default(true),
+          )
+          .col(ColumnDef::new(WalletFollow::LastSignature).string())
+          .col(
+            ColumnDef::new(WalletFollow::CreatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .col(
+            ColumnDef::new(WalletFollow::UpdatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_wallet_follows_user")
+              .from(WalletFollow::Table, WalletFollow::UserId)
+              .to(User::Table, User::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_wallet_follows_wallet")
+              .from(WalletFollow::Table, WalletFollow::WalletId)
+              .to(Wallet::Table, Wallet::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wallet_follows_user_followed_address")
+          .table(WalletFollow::Table)
+          .col(WalletFollow::UserId)
+          .col(WalletFollow::FollowedAddress)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .add_column(ColumnDef::new(OnchainTransaction::SourceTransactionHash).string())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .drop_column(OnchainTransaction::SourceTransactionHash)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .drop_table(Table::drop().table(WalletFollow::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum WalletFollow {
+  #[sea_orm(iden = "wallet_follows")]
+  Table,
+  Id,
+  UserId,
+  WalletId,
+  FollowedAddress,
+  SizeMode,
+  SizeValue,
+  MaxSolPerTrade,
+  MaxTotalSol,
+  SpentSol,
+  MirrorSells,
+  Enabled,
+  LastSignature,
+  CreatedAt,
+  UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  SourceTransactionHash,
+}
@@ -0,0 +1,174 @@
+use sea_orm_migration::{prelude::*, schema::pk_auto};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+use crate::m20241014_191627_create_token_table::Token;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // watch-only wallets have no keys
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Wallet::Table)
+          .modify_column(ColumnDef::new(Wallet::EncryptedPrivateKey).string().null())
+          .modify_column(ColumnDef::new(Wallet::SecretKey).string().null())
+          .modify_column(ColumnDef::new(Wallet::Salt).string().null())
+          .add_column(
+            ColumnDef::new(Wallet::WatchOnly)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .add_column(ColumnDef::new(Wallet::LastSignature).string())
+          .to_owned(),
+      )
+      .await?;
+
+    // several users can watch the same address, so it is only unique per user
+    manager
+      .get_connection()
+      .execute_unprepared("ALTER TABLE wallets DROP CONSTRAINT IF EXISTS wallets_address_key")
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wallets_user_address")
+          .table(Wallet::Table)
+          .col(Wallet::UserId)
+          .col(Wallet::Address)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_table(
+        Table::create()
+          .table(WalletHolding::Table)
+          .if_not_exists()
+          .col(pk_auto(WalletHolding::Id))
+          .col(ColumnDef::new(WalletHolding::WalletId).integer().not_null())
+          .col(ColumnDef::new(WalletHolding::TokenId).integer())
+          .col(
+            ColumnDef::new(WalletHolding::ContractAddress)
+              .string()
+              .not_null(),
+          )
+          .col(ColumnDef::new(WalletHolding::Balance).double().not_null())
+          .col(ColumnDef::new(WalletHolding::ValueUsd).float())
+          .col(
+            ColumnDef::new(WalletHolding::CreatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .col(
+            ColumnDef::new(WalletHolding::UpdatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_wallet_holdings_wallet")
+              .from(WalletHolding::Table, WalletHolding::WalletId)
+              .to(Wallet::Table, Wallet::Id)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_wallet_holdings_token")
+              .from(WalletHolding::Table, WalletHolding::TokenId)
+              .to(Token::Table, Token::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wallet_holdings_wallet_contract_address")
+          .table(WalletHolding::Table)
+          .col(WalletHolding::WalletId)
+          .col(WalletHolding::ContractAddress)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WalletHolding::Table).to_owned())
+      .await?;
+
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_wallets_user_address")
+          .table(Wallet::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .get_connection()
+      .execute_unprepared("DELETE FROM wallets WHERE watch_only")
+      .await?;
+
+    manager
+      .get_connection()
+      .execute_unprepared("ALTER TABLE wallets ADD CONSTRAINT wallets_address_key UNIQUE (address)")
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Wallet::Table)
+          .drop_column(Wallet::WatchOnly)
+          .drop_column(Wallet::LastSignature)
+          .modify_column(
+            ColumnDef::new(Wallet::EncryptedPrivateKey)
+              .string()
+              .not_null(),
+          )
+          .modify_column(ColumnDef::new(Wallet::SecretKey).string().not_null())
+          .modify_column(ColumnDef::new(Wallet::Salt).string().not_null())
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Wallet {
+  #[sea_orm(iden = "wallets")]
+  Table,
+  Id,
+  UserId,
+  Address,
+  EncryptedPrivateKey,
+  SecretKey,
+  Salt,
+  WatchOnly,
+  LastSignature,
+}
+
+#[derive(DeriveIden)]
+enum WalletHolding {
+  #[sea_orm(iden = "wallet_holdings")]
+  Table,
+  Id,
+  WalletId,
+  TokenId,
+  ContractAddress,
+  Balance,
+  ValueUsd,
+  CreatedAt,
+  UpdatedAt,
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .add_column(
+            ColumnDef::new(UserSetting::JitoTipSol)
+              .float()
+              .not_null()
+              .default(0.001),
+          )
+          .add_column(
+            ColumnDef::new(UserSetting::JitoLaunchBuys)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .add_column(
+            ColumnDef::new(UserSetting::JitoStopLosses)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .drop_column(UserSetting::JitoTipSol)
+          .drop_column(UserSetting::JitoLaunchBuys)
+          .drop_column(UserSetting::JitoStopLosses)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserSetting {
+  #[sea_orm(iden = "user_settings")]
+  Table,
+  JitoTipSol,
+  JitoLaunchBuys,
+  JitoStopLosses,
+}
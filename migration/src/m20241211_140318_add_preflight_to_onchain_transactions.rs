@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .add_column(ColumnDef::new(OnchainTransaction::Preflight).json())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .drop_column(OnchainTransaction::Preflight)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  Preflight,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .add_column(ColumnDef::new(OnchainTransaction::EffectivePrice).float())
+          .add_column(ColumnDef::new(OnchainTransaction::NetworkFeeLamports).big_integer())
+          .add_column(ColumnDef::new(OnchainTransaction::PriorityFeeLamports).big_integer())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .drop_column(OnchainTransaction::EffectivePrice)
+          .drop_column(OnchainTransaction::NetworkFeeLamports)
+          .drop_column(OnchainTransaction::PriorityFeeLamports)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  EffectivePrice,
+  NetworkFeeLamports,
+  PriorityFeeLamports,
+}
@@ -0,0 +1,179 @@
+use sea_orm_migration::{prelude::*, schema::pk_auto};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+use super::m20241008_115542_create_user_table::User;
+use crate::m20241008_121835_create_wallet_table::Wallet;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Position::Table)
+          .if_not_exists()
+          .col(pk_auto(Position::Id))
+          .col(ColumnDef::new(Position::UserId).integer().not_null())
+          .col(ColumnDef::new(Position::WalletId).integer().not_null())
+          .col(
+            ColumnDef::new(Position::ContractAddress)
+              .string()
+              .not_null(),
+          )
+          .col(ColumnDef::new(Position::Decimals).integer().not_null())
+          .col(
+            ColumnDef::new(Position::Quantity)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(Position::CostBasisSol)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(Position::CostBasisUsd)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(Position::AverageEntryPrice)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(Position::AverageEntryPriceUsd)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(Position::RealizedPnlSol)
+              .float()
+              .not_null()
+              .default(0.0),
+          )
+          .col(
+            ColumnDef::new(Position::FeesLamports)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(Position::CreatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .col(
+            ColumnDef::new(Position::UpdatedAt)
+              .timestamp()
+              .not_null()
+              .default(Expr::current_timestamp()),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_positions_user")
+              .from(Position::Table, Position::UserId)
+              .to(User::Table, User::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk_positions_wallet")
+              .from(Position::Table, Position::WalletId)
+              .to(Wallet::Table, Wallet::Id)
+              .on_delete(ForeignKeyAction::Cascade),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_positions_wallet_contract_address")
+          .table(Position::Table)
+          .col(Position::WalletId)
+          .col(Position::ContractAddress)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .add_column(ColumnDef::new(OnchainTransaction::PositionId).integer())
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_foreign_key(
+        ForeignKey::create()
+          .name("fk_onchain_transactions_position")
+          .from(OnchainTransaction::Table, OnchainTransaction::PositionId)
+          .to(Position::Table, Position::Id)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_foreign_key(
+        ForeignKey::drop()
+          .name("fk_onchain_transactions_position")
+          .table(OnchainTransaction::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(OnchainTransaction::Table)
+          .drop_column(OnchainTransaction::PositionId)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .drop_table(Table::drop().table(Position::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Position {
+  #[sea_orm(iden = "positions")]
+  Table,
+  Id,
+  UserId,
+  WalletId,
+  ContractAddress,
+  Decimals,
+  Quantity,
+  CostBasisSol,
+  CostBasisUsd,
+  AverageEntryPrice,
+  AverageEntryPriceUsd,
+  RealizedPnlSol,
+  FeesLamports,
+  CreatedAt,
+  UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  PositionId,
+}
@@ -0,0 +1,115 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Position::Table)
+          .add_column(
+            ColumnDef::new(Position::Simulated)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // paper fills of a wallet add up to a position of their own
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_positions_wallet_contract_address")
+          .table(Position::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_positions_wallet_contract_address_simulated")
+          .table(Position::Table)
+          .col(Position::WalletId)
+          .col(Position::ContractAddress)
+          .col(Position::Simulated)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_positions_wallet_contract_address_simulated")
+          .table(Position::Table)
+          .to_owned(),
+      )
+      .await?;
+
+    // only one position per wallet and token is left, the paper ones go
+    manager
+      .exec_stmt(
+        Query::update()
+          .table(OnchainTransaction::Table)
+          .value(OnchainTransaction::PositionId, Option::<i32>::None)
+          .and_where(Expr::col(OnchainTransaction::Simulated).eq(true))
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .exec_stmt(
+        Query::delete()
+          .from_table(Position::Table)
+          .and_where(Expr::col(Position::Simulated).eq(true))
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_positions_wallet_contract_address")
+          .table(Position::Table)
+          .col(Position::WalletId)
+          .col(Position::ContractAddress)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Position::Table)
+          .drop_column(Position::Simulated)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Position {
+  #[sea_orm(iden = "positions")]
+  Table,
+  WalletId,
+  ContractAddress,
+  Simulated,
+}
+
+#[derive(DeriveIden)]
+enum OnchainTransaction {
+  #[sea_orm(iden = "onchain_transactions")]
+  Table,
+  PositionId,
+  Simulated,
+}
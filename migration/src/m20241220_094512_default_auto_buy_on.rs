@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // every user with a telegram account used to buy launches, which stays the default
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .modify_column(
+            ColumnDef::new(UserSetting::AutoBuy)
+              .boolean()
+              .not_null()
+              .default(true),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // rows created with the old default that their user never saved
+    manager
+      .exec_stmt(
+        Query::update()
+          .table(UserSetting::Table)
+          .value(UserSetting::AutoBuy, true)
+          .and_where(Expr::col(UserSetting::AutoBuy).eq(false))
+          .and_where(Expr::col(UserSetting::UpdatedAt).equals(UserSetting::CreatedAt))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(UserSetting::Table)
+          .modify_column(
+            ColumnDef::new(UserSetting::AutoBuy)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserSetting {
+  #[sea_orm(iden = "user_settings")]
+  Table,
+  AutoBuy,
+  CreatedAt,
+  UpdatedAt,
+}
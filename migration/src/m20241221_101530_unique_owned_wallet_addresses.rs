@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // an address can be watched by many users but its keys belong to one wallet only
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "CREATE UNIQUE INDEX idx_wallets_owned_address ON wallets (address) WHERE NOT watch_only",
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_wallets_owned_address")
+          .table(Wallet::Table)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Wallet {
+  #[sea_orm(iden = "wallets")]
+  Table,
+}